@@ -1,6 +1,6 @@
 use fontdue::Font;
-use std::time::Instant;
-use xerune::{Model, Runtime, XeruneTemplate};
+use std::time::{Duration, Instant};
+use xerune::{Command, Model, Runtime, Subscription, XeruneTemplate};
 use skia_renderer::TinySkiaMeasurer;
 
 #[global_allocator]
@@ -109,7 +109,11 @@ impl std::str::FromStr for AnimationMsg {
 impl Model for AnimationModel {
     type Message = AnimationMsg;
 
-    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) {
+    fn subscriptions(&self) -> Vec<Subscription> {
+        vec![Subscription::Interval(Duration::from_millis(16), "tick".to_string())]
+    }
+
+    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) -> Command<Self::Message> {
         match msg {
             AnimationMsg::RenderTime(ms) => {
                 self.render_time = Some(ms);
@@ -140,6 +144,7 @@ impl Model for AnimationModel {
                 }
             }
         }
+        Command::none()
     }
 }
 
@@ -153,15 +158,14 @@ fn main() -> anyhow::Result<()> {
     let fonts = vec![roboto_regular, roboto_bold];
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     
     // Create 100 items for benchmark
     let model = AnimationModel::new(100);
     let mut runtime = Runtime::new(model, measurer);
-    runtime.set_interval("tick".to_string(), 16);
     
     if std::env::var("HEADLESS").is_ok() {
-        let mut image_cache = std::collections::HashMap::new();
+        let mut image_cache = xerune::decode::ImageCache::default();
         let mut glyph_cache = std::collections::HashMap::new();
         
         #[cfg(feature = "fast-renderer")]
@@ -170,6 +174,9 @@ fn main() -> anyhow::Result<()> {
         let start = std::time::Instant::now();
         for _ in 0..500 {
             let _ = runtime.tick();
+            // `render` skips frames where nothing changed; every frame is
+            // drawn in full so that all 500 are measured.
+            runtime.invalidate();
             
             #[cfg(feature = "fast-renderer")]
             {
@@ -1,5 +1,5 @@
 use fontdue::Font;
-use xerune::{Model, Runtime, XeruneTemplate};
+use xerune::{Command, Model, Runtime, XeruneTemplate};
 use skia_renderer::TinySkiaMeasurer;
 
 #[path = "support/mod.rs"]
@@ -12,7 +12,9 @@ struct ShowcaseModel;
 impl Model for ShowcaseModel {
     type Message = String;
 
-    fn update(&mut self, _msg: Self::Message, _context: &mut xerune::Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut xerune::Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 fn main() -> anyhow::Result<()> {
@@ -26,7 +28,7 @@ fn main() -> anyhow::Result<()> {
     let fonts = vec![roboto_regular, roboto_bold];
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     let model = ShowcaseModel;
     let runtime = Runtime::new(model, measurer);
 
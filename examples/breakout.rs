@@ -1,8 +1,8 @@
 use fontdue::Font;
 use std::collections::HashSet;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use xerune::{Model, Runtime, XeruneTemplate};
+use xerune::{Command, Model, Runtime, Subscription, XeruneTemplate};
 use skia_renderer::TinySkiaMeasurer;
 use std::f32::consts::PI;
 
@@ -136,14 +136,18 @@ impl std::str::FromStr for Msg {
 impl Model for BreakoutModel {
     type Message = Msg;
 
-    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) {
+    fn subscriptions(&self) -> Vec<Subscription> {
+        vec![Subscription::Interval(Duration::from_millis(16), "tick".to_string())]
+    }
+
+    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) -> Command<Self::Message> {
         match msg {
             Msg::Tick => {
                 let now = Instant::now();
                 let dt = now.duration_since(self.last_tick).as_secs_f32();
                 self.last_tick = now;
 
-                if self.game_over || self.won { return; }
+                if self.game_over || self.won { return Command::none(); }
 
                 // --- Paddle Movement ---
                 let mut paddle_dir = 0.0;
@@ -267,6 +271,7 @@ impl Model for BreakoutModel {
                 self.keys_held.remove(&key);
             },
         }
+        Command::none()
     }
 }
 
@@ -281,10 +286,9 @@ fn main() -> anyhow::Result<()> {
     let fonts = vec![roboto_regular, roboto_bold];
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     let model = BreakoutModel::new();
     let mut runtime = Runtime::new(model, measurer);
-    runtime.set_interval("tick".to_string(), 16);
     
     #[cfg(not(any(
         all(target_os = "linux", feature = "linuxfb", feature = "evdev"),
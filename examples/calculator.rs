@@ -1,5 +1,5 @@
 use fontdue::Font;
-use xerune::{Model, Runtime, XeruneTemplate};
+use xerune::{Command, Model, Runtime, XeruneTemplate};
 use skia_renderer::TinySkiaMeasurer;
 
 #[path = "support/mod.rs"]
@@ -117,7 +117,7 @@ impl std::str::FromStr for Msg {
 impl Model for CalculatorModel {
     type Message = Msg;
 
-    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) {
+    fn update(&mut self, msg: Self::Message, _context: &mut xerune::Context) -> Command<Self::Message> {
         match msg {
             Msg::Digit(d) => {
                 if self.new_input {
@@ -160,6 +160,7 @@ impl Model for CalculatorModel {
                 self.new_input = true;
             }
         }
+        Command::none()
     }
 }
 
@@ -174,7 +175,7 @@ fn main() -> anyhow::Result<()> {
     let fonts = vec![roboto_regular, roboto_bold];
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     let model = CalculatorModel::new();
     let runtime = Runtime::new(model, measurer);
     
@@ -5,7 +5,7 @@ use std::fs;
 use std::time::{Duration, Instant};
 
 // Import from the library and renderer
-use xerune::{Model, Runtime, XeruneTemplate};
+use xerune::{Command, Model, Runtime, Subscription, XeruneTemplate};
 
 #[cfg(not(feature = "fast-renderer"))]
 use skia_renderer::TinySkiaMeasurer;
@@ -158,7 +158,11 @@ impl std::str::FromStr for Msg {
 impl Model for MusicPlayerModel {
     type Message = Msg;
 
-    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) {
+    fn subscriptions(&self) -> Vec<Subscription> {
+        vec![Subscription::Interval(Duration::from_millis(33), "tick".to_string())]
+    }
+
+    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) -> Command<Self::Message> {
          match msg {
              Msg::SelectTrack(id_str) => {
                  if let Some(index) = self.tracks.iter().position(|t| t.id == id_str) {
@@ -329,6 +333,7 @@ impl Model for MusicPlayerModel {
              }
          }
          self.update_derived_fields();
+        Command::none()
     }
 }
 
@@ -409,13 +414,12 @@ fn main() -> anyhow::Result<()> {
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
     #[cfg(not(feature = "fast-renderer"))]
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     #[cfg(feature = "fast-renderer")]
-    let measurer = FastMeasurer { fonts: fonts_ref };
+    let measurer = FastMeasurer { fonts: fonts_ref, families: &[] };
 
     let model = MusicPlayerModel::new();
     let mut runtime = Runtime::new(model, measurer);
-    runtime.set_interval("tick".to_string(), 33);
     
     #[cfg(not(any(
         all(target_os = "linux", feature = "linuxfb", feature = "evdev"),
@@ -1,4 +1,5 @@
-use xerune::{Model, Runtime, XeruneTemplate};
+use std::time::Duration;
+use xerune::{Command, Model, Runtime, Subscription, XeruneTemplate};
 use fontdue::Font;
 use tiny_skia::{PixmapMut, Paint, Color, Transform, Rect};
 use rand::Rng;
@@ -48,7 +49,11 @@ impl std::str::FromStr for ShowcaseMsg {
 impl Model for ShowcaseModel {
     type Message = ShowcaseMsg;
 
-    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) {
+    fn subscriptions(&self) -> Vec<Subscription> {
+        vec![Subscription::Interval(Duration::from_millis(300), "tick".to_string())]
+    }
+
+    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) -> Command<Self::Message> {
         match msg {
             ShowcaseMsg::IncrementProgress => {
                 self.system_load_value += 10.0;
@@ -141,6 +146,7 @@ impl Model for ShowcaseModel {
                  }
             }
         }
+        Command::none()
     }
 }
 
@@ -155,9 +161,9 @@ fn main() -> anyhow::Result<()> {
     let fonts_ref: &'static [Font] = Box::leak(fonts.into_boxed_slice());
 
     #[cfg(not(feature = "fast-renderer"))]
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     #[cfg(feature = "fast-renderer")]
-    let measurer = FastMeasurer { fonts: fonts_ref };
+    let measurer = FastMeasurer { fonts: fonts_ref, families: &[] };
 
     let model = ShowcaseModel {
         system_load_value: 30.0,
@@ -171,8 +177,7 @@ fn main() -> anyhow::Result<()> {
         user_counter: 1234,
     };
 
-    let mut runtime = Runtime::new(model, measurer);
-    runtime.set_interval("tick".to_string(), 300);
+    let runtime = Runtime::new(model, measurer);
 
     #[cfg(not(any(
         all(target_os = "linux", feature = "linuxfb", feature = "evdev"),
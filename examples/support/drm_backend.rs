@@ -123,6 +123,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     fonts: &'static [Font],
     setup: impl FnOnce(std::sync::mpsc::Sender<String>),
 ) -> anyhow::Result<()> {
+    runtime.clear_color = Some(super::CLEAR_COLOR);
     println!("Initializing DRM/KMS Backend...");
     let card = Card::open_dri_card()?;
     
@@ -187,10 +188,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     let (msg_tx, msg_rx) = std::sync::mpsc::channel::<String>();
     setup(msg_tx);
     
-    #[cfg(not(feature = "fast-renderer"))]
-    let mut image_cache = std::collections::HashMap::new();
-    #[cfg(feature = "fast-renderer")]
-    let mut image_cache = std::collections::HashMap::new();
+    let mut image_cache = xerune::decode::ImageCache::default();
 
     #[cfg(not(feature = "fast-renderer"))]
     let mut gradient_cache = std::collections::HashMap::new();
@@ -238,7 +236,10 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                 },
                 evdev::InputEventKind::Key(evdev::Key::BTN_LEFT) | evdev::InputEventKind::Key(evdev::Key::BTN_TOUCH) => {
                     if ev.value() == 1 {
+                        dirty |= runtime.handle_event(InputEvent::PointerDown { x: mouse_x, y: mouse_y });
                         dirty |= runtime.handle_event(InputEvent::Click { x: mouse_x, y: mouse_y });
+                    } else if ev.value() == 0 {
+                        dirty |= runtime.handle_event(InputEvent::PointerUp { x: mouse_x, y: mouse_y });
                     }
                 },
                 _ => {}
@@ -268,17 +269,20 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                 (fb1, map1.as_mut())
             };
             
+            // An empty region means nothing was drawn into the back buffer,
+            // which still holds an older frame, so it must not be shown.
             #[cfg(not(feature = "fast-renderer"))]
-            {
-                if let Some(fb_pixmap) = tiny_skia::PixmapMut::from_bytes(draw_slice, w, h) {
+            let changed = match tiny_skia::PixmapMut::from_bytes(draw_slice, w, h) {
+                Some(fb_pixmap) => {
                      let mut renderer = TinySkiaRenderer::new(fb_pixmap, fonts, &mut image_cache, &mut gradient_cache, &mut glyph_cache);
                      renderer.swap_rb = true; // Xrgb8888 is BGRA in memory
-                     runtime.render(&mut renderer);
+                     !runtime.render(&mut renderer).is_some_and(|dirty| dirty.is_empty())
                 }
-            }
+                None => false,
+            };
 
             #[cfg(feature = "fast-renderer")]
-            {
+            let changed = {
                 let draw_slice_u32 = unsafe {
                     std::slice::from_raw_parts_mut(
                         draw_slice.as_mut_ptr() as *mut u32,
@@ -287,11 +291,11 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                 };
                 let mut renderer = FastRenderer::new(draw_slice_u32, w, h, fonts, &mut image_cache, &mut glyph_cache);
                 renderer.swap_rb = false; // Xrgb8888 matches FastRenderer default
-                runtime.render(&mut renderer);
-            }
+                !runtime.render(&mut renderer).is_some_and(|dirty| dirty.is_empty())
+            };
             
             // Perform hardware page flip!
-            loop {
+            while changed {
                 match card.page_flip(crtc_handle, target_fb, drm::control::PageFlipFlags::empty(), None) {
                     Ok(_) => {
                         current_fb = target_fb;
@@ -24,6 +24,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     fonts: &'static [Font],
     setup: impl FnOnce(std::sync::mpsc::Sender<String>),
 ) -> anyhow::Result<()> {
+    runtime.clear_color = Some(super::CLEAR_COLOR);
     #[cfg(feature = "profile")]
     #[allow(unused_imports)]
     use coarse_prof::profile;
@@ -70,10 +71,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
         
         let _ = fb.set_offset(0, 0); // Ensure no panning is applied
         
-        #[cfg(not(feature = "fast-renderer"))]
-        let mut image_cache = std::collections::HashMap::new();
-        #[cfg(feature = "fast-renderer")]
-        let mut image_cache = std::collections::HashMap::new();
+        let mut image_cache = xerune::decode::ImageCache::default();
 
         #[cfg(not(feature = "fast-renderer"))]
         let mut gradient_cache = std::collections::HashMap::new();
@@ -125,7 +123,10 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                     },
                     InputEventKind::Key(Key::BTN_LEFT) | InputEventKind::Key(Key::BTN_TOUCH) => {
                         if ev.value() == 1 {
+                            dirty |= runtime.handle_event(InputEvent::PointerDown { x: mouse_x, y: mouse_y });
                             dirty |= runtime.handle_event(InputEvent::Click { x: mouse_x, y: mouse_y });
+                        } else if ev.value() == 0 {
+                            dirty |= runtime.handle_event(InputEvent::PointerUp { x: mouse_x, y: mouse_y });
                         }
                     },
                     _ => {}
@@ -171,20 +172,23 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                         &mut fb_mmap[0..page_size]
                     };
 
+                    // An empty region means nothing was drawn into the page,
+                    // which still holds an older frame, so it must not be shown.
                     #[cfg(not(feature = "fast-renderer"))]
-                    {
-                        if let Some(fb_pixmap) = tiny_skia::PixmapMut::from_bytes(draw_slice, fb_w, fb_h) {
+                    let changed = match tiny_skia::PixmapMut::from_bytes(draw_slice, fb_w, fb_h) {
+                        Some(fb_pixmap) => {
                              let mut renderer = TinySkiaRenderer::new(fb_pixmap, fonts, &mut image_cache, &mut gradient_cache, &mut glyph_cache);
                              renderer.swap_rb = fb_is_bgra;
                              if rotate {
                                  renderer.transform = tiny_skia::Transform::from_rotate(90.0).post_translate(fb_w as f32, 0.0);
                              }
-                             runtime.render(&mut renderer);
+                             !runtime.render(&mut renderer).is_some_and(|dirty| dirty.is_empty())
                         }
-                    }
+                        None => false,
+                    };
 
                     #[cfg(feature = "fast-renderer")]
-                    {
+                    let changed = {
                         if double_buffered {
                             let draw_slice_u32 = unsafe {
                                 std::slice::from_raw_parts_mut(
@@ -197,7 +201,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                             renderer.rotate = rotate;
                             renderer.physical_width = fb_w;
                             renderer.physical_height = fb_h;
-                            runtime.render(&mut renderer);
+                            !runtime.render(&mut renderer).is_some_and(|dirty| dirty.is_empty())
                         } else {
                             let mut renderer = FastRenderer::new(&mut back_buffer, w, h, fonts, &mut image_cache, &mut glyph_cache);
                             renderer.swap_rb = !fb_is_bgra;
@@ -212,15 +216,18 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                             unsafe {
                                 std::ptr::copy_nonoverlapping(ptr_src, ptr_dst, page_size);
                             }
+                            true
                         }
-                    }
+                    };
                     
                     // Flip the display registers to instantly show the newly drawn virtual offset!
                     if double_buffered && mmap_len >= page_size * 2 {
-                        if let Err(e) = fb.set_offset(0, y_offset) {
-                            log::warn!("Failed to flip page: {:?}", e);
-                        } else {
-                            active_page = if active_page == 0 { 1 } else { 0 };
+                        if changed {
+                            if let Err(e) = fb.set_offset(0, y_offset) {
+                                log::warn!("Failed to flip page: {:?}", e);
+                            } else {
+                                active_page = if active_page == 0 { 1 } else { 0 };
+                            }
                         }
                     } else {
                         // Force flush for single-buffered display to avoid deferred I/O delay (crucial for QEMU/virtio-gpu)
@@ -274,7 +281,7 @@ fn render_16bit_fallback<M: Model + xerune::ui::TemplateLayout, TM: TextMeasurer
     fb_w: u32,
     fb_mmap: &mut [u8],
     fonts: &[Font],
-    image_cache: &mut std::collections::HashMap<String, Pixmap>,
+    image_cache: &mut xerune::decode::ImageCache<Pixmap>,
     gradient_cache: &mut std::collections::HashMap<String, Pixmap>,
     glyph_cache: &mut std::collections::HashMap<(usize, u16, u32, [u8; 4]), Pixmap>,
 ) -> anyhow::Result<()> {
@@ -333,7 +340,7 @@ fn render_16bit_fallback<M: Model + xerune::ui::TemplateLayout, TM: TextMeasurer
     fb_w: u32,
     fb_mmap: &mut [u8],
     fonts: &[Font],
-    image_cache: &mut std::collections::HashMap<String, (u32, u32, Vec<u32>)>,
+    image_cache: &mut fast_renderer::ImageCache,
     glyph_cache: &mut std::collections::HashMap<(usize, u16, u32), fast_renderer::CachedGlyph>,
 ) -> anyhow::Result<()> {
     #[cfg(feature = "profile")]
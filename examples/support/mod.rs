@@ -1,3 +1,7 @@
+/// Color every backend clears to where the document paints no background.
+#[allow(dead_code)]
+pub const CLEAR_COLOR: xerune::Color = xerune::Color { r: 34, g: 34, b: 34, a: 255 };
+
 #[cfg(not(any(
     all(target_os = "linux", feature = "linuxfb", feature = "evdev"),
     all(target_os = "linux", feature = "drm", feature = "evdev")
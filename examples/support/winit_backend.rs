@@ -1,15 +1,13 @@
-use winit::event::{Event, WindowEvent, ElementState, MouseButton, MouseScrollDelta};
+use winit::event::{Event, WindowEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase};
 use winit::event_loop::{ControlFlow};
-use winit::window::WindowBuilder;
+use winit::window::{CursorIcon, WindowBuilder};
 use std::rc::Rc;
 
 use std::num::NonZeroU32;
 use xerune::{Model, InputEvent, Runtime, TextMeasurer};
 
 #[cfg(not(feature = "fast-renderer"))]
-use skia_renderer::TinySkiaRenderer;
-#[cfg(not(feature = "fast-renderer"))]
-use tiny_skia::Color;
+use skia_renderer::{LayerCache, TinySkiaRenderer};
 #[cfg(feature = "fast-renderer")]
 use fast_renderer::FastRenderer;
 
@@ -23,6 +21,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     fonts: &'static [Font],
     setup: impl FnOnce(winit::event_loop::EventLoopProxy<String>),
 ) -> anyhow::Result<()> {
+    runtime.clear_color = Some(super::CLEAR_COLOR);
     let event_loop = winit::event_loop::EventLoopBuilder::<String>::with_user_event().build()?;
     let proxy = event_loop.create_proxy();
     setup(proxy.clone());
@@ -42,16 +41,15 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     
 
 
-    #[cfg(not(feature = "fast-renderer"))]
-    let mut image_cache = std::collections::HashMap::new();
-    #[cfg(feature = "fast-renderer")]
-    let mut image_cache = std::collections::HashMap::new();
+    let mut image_cache = xerune::decode::ImageCache::default();
 
     #[cfg(not(feature = "fast-renderer"))]
     let mut gradient_cache = std::collections::HashMap::new();
 
     #[cfg(not(feature = "fast-renderer"))]
     let mut glyph_cache = std::collections::HashMap::new();
+    #[cfg(not(feature = "fast-renderer"))]
+    let mut layer_cache = LayerCache::default();
     #[cfg(feature = "fast-renderer")]
     let mut glyph_cache = std::collections::HashMap::new();
 
@@ -68,17 +66,16 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                 let now = std::time::Instant::now();
                 if now >= next_trigger {
                     let res = runtime.tick();
-                    if res.needs_redraw {
-                        window_clone.request_redraw();
-                    }
                     next_trigger = std::time::Instant::now() + res.next_tick_in;
                 }
+                // Events, timers, animations and canvas updates all feed the runtime's redraw flag.
+                if runtime.needs_redraw() {
+                    window_clone.request_redraw();
+                }
                 target.set_control_flow(ControlFlow::WaitUntil(next_trigger));
             }
             Event::UserEvent(msg) => {
-                if runtime.handle_event(InputEvent::Message(msg)) {
-                    window_clone.request_redraw();
-                }
+                runtime.handle_event(InputEvent::Message(msg));
             },
             Event::WindowEvent { window_id, event } if window_id == window_clone.id() => {
                 match event {
@@ -111,23 +108,23 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                         {
                             if app_pixmap.is_none() || app_pixmap.as_ref().unwrap().width() != width || app_pixmap.as_ref().unwrap().height() != height {
                                 app_pixmap = tiny_skia::Pixmap::new(width, height);
-                                if let Some(p) = app_pixmap.as_mut() {
-                                    p.fill(Color::from_rgba8(34, 34, 34, 255));
-                                }
+                                runtime.invalidate();
                             }
 
                             if let Some(pixmap) = app_pixmap.as_mut() {
-                                let mut renderer = TinySkiaRenderer::new(pixmap.as_mut(), fonts, &mut image_cache, &mut gradient_cache, &mut glyph_cache);
-                                runtime.render(&mut renderer);
+                                let mut renderer = TinySkiaRenderer::new(pixmap.as_mut(), fonts, &mut image_cache, &mut gradient_cache, &mut glyph_cache)
+                                    .with_layer_cache(&mut layer_cache);
+                                let dirty = runtime.render(&mut renderer);
 
                                 let data = pixmap.data();
-                                for (i, chunk) in data.chunks_exact(4).enumerate() {
-                                    let r = chunk[0] as u32;
-                                    let g = chunk[1] as u32;
-                                    let b = chunk[2] as u32;
-                                    buffer[i] = (r << 16) | (g << 8) | b;
-                                }
-                                buffer.present().unwrap();
+                                let age = buffer.age();
+                                let damage = copy_damage(&mut buffer, age, dirty, width, height, |i| {
+                                    let r = data[i * 4] as u32;
+                                    let g = data[i * 4 + 1] as u32;
+                                    let b = data[i * 4 + 2] as u32;
+                                    (r << 16) | (g << 8) | b
+                                });
+                                buffer.present_with_damage(&damage).unwrap();
                             }
                         }
 
@@ -135,15 +132,17 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                         {
                             let buffer_len = (width * height) as usize;
                             if app_buffer.is_none() || app_buffer.as_ref().unwrap().len() != buffer_len {
-                                app_buffer = Some(vec![0xFF222222; buffer_len]);
+                                app_buffer = Some(vec![0; buffer_len]);
+                                runtime.invalidate();
                             }
 
                             if let Some(ref mut app_buf) = app_buffer {
                                 let mut renderer = FastRenderer::new(app_buf, width, height, fonts, &mut image_cache, &mut glyph_cache);
-                                runtime.render(&mut renderer);
+                                let dirty = runtime.render(&mut renderer);
 
-                                buffer.copy_from_slice(app_buf);
-                                buffer.present().unwrap();
+                                let age = buffer.age();
+                                let damage = copy_damage(&mut buffer, age, dirty, width, height, |i| app_buf[i]);
+                                buffer.present_with_damage(&damage).unwrap();
                             }
                         }
                     },
@@ -153,36 +152,42 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                     WindowEvent::CursorMoved { position, .. } => {
                         mouse_x = position.x as f32;
                         mouse_y = position.y as f32;
-                        if runtime.handle_event(InputEvent::Hover { x: mouse_x, y: mouse_y }) {
-                            window_clone.request_redraw();
-                        }
+                        runtime.handle_event(InputEvent::Hover { x: mouse_x, y: mouse_y });
+                        window_clone.set_cursor_icon(match runtime.cursor() {
+                            xerune::Cursor::Default => CursorIcon::Default,
+                            xerune::Cursor::Pointer => CursorIcon::Pointer,
+                            xerune::Cursor::Text => CursorIcon::Text,
+                        });
                     },
                     WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
                         if state == ElementState::Pressed {
-                             if runtime.handle_event(InputEvent::Click { x: mouse_x, y: mouse_y }) {
-                                window_clone.request_redraw();
-                             }
+                             runtime.handle_event(InputEvent::PointerDown { x: mouse_x, y: mouse_y });
+                             runtime.handle_event(InputEvent::Click { x: mouse_x, y: mouse_y });
+                        } else {
+                             runtime.handle_event(InputEvent::PointerUp { x: mouse_x, y: mouse_y });
                         }
                     },
+                    WindowEvent::Touch(touch) => {
+                        let (id, x, y) = (touch.id, touch.location.x as f32, touch.location.y as f32);
+                        runtime.handle_event(match touch.phase {
+                            TouchPhase::Started => InputEvent::TouchStart { id, x, y },
+                            TouchPhase::Moved => InputEvent::TouchMove { id, x, y },
+                            TouchPhase::Ended | TouchPhase::Cancelled => InputEvent::TouchEnd { id, x, y },
+                        });
+                    },
                     WindowEvent::MouseWheel { delta, .. } => {
                         let (dx, dy) = match delta {
                             MouseScrollDelta::LineDelta(x, y) => (x * 20.0, y * 20.0),
                             MouseScrollDelta::PixelDelta(pos) => (pos.x as f32, pos.y as f32),
                         };
-                        if runtime.handle_event(InputEvent::Scroll { x: mouse_x, y: mouse_y, delta_x: dx, delta_y: dy }) {
-                            window_clone.request_redraw();
-                        }
+                        runtime.handle_event(InputEvent::Scroll { x: mouse_x, y: mouse_y, delta_x: dx, delta_y: dy });
                     },
                     WindowEvent::KeyboardInput { event: kb_event, .. } => {
                         // For winit 0.29
-                        let mut redraw = false;
                         if kb_event.state == ElementState::Pressed {
                             if let Some(text) = &kb_event.text {
                                 if !text.is_empty() {
-                                    let text_event = InputEvent::TextInput { id: String::new(), text: text.to_string() };
-                                    if runtime.handle_event(text_event) {
-                                        redraw = true;
-                                    }
+                                    runtime.handle_event(InputEvent::TextInput { id: String::new(), text: text.to_string() });
                                 }
                             }
                         }
@@ -194,13 +199,7 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
                             } else {
                                 InputEvent::KeyUp(key_name)
                             };
-                            if runtime.handle_event(input_event) {
-                                redraw = true;
-                            }
-                        }
-
-                        if redraw {
-                            window_clone.request_redraw();
+                            runtime.handle_event(input_event);
                         }
                     },
                     _ => {}
@@ -211,3 +210,28 @@ pub fn run_app<M: Model + xerune::ui::TemplateLayout + 'static, TM: TextMeasurer
     })?;
     Ok(())
 }
+
+/// Copies the pixels `render` repainted into the surface buffer and returns
+/// the damaged region to present. The buffer holds the previous frame only
+/// when its `age` is 1; otherwise every pixel is copied.
+fn copy_damage(buffer: &mut [u32], age: u8, dirty: Option<xerune::Rect>, width: u32, height: u32, pixel: impl Fn(usize) -> u32) -> Vec<softbuffer::Rect> {
+    let (x0, y0, x1, y1) = match dirty {
+        Some(r) if age == 1 => (
+            (r.x.floor().max(0.0) as u32).min(width),
+            (r.y.floor().max(0.0) as u32).min(height),
+            ((r.x + r.width).ceil().max(0.0) as u32).min(width),
+            ((r.y + r.height).ceil().max(0.0) as u32).min(height),
+        ),
+        _ => (0, 0, width, height),
+    };
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let i = (y * width + x) as usize;
+            buffer[i] = pixel(i);
+        }
+    }
+    match (NonZeroU32::new(x1.saturating_sub(x0)), NonZeroU32::new(y1.saturating_sub(y0))) {
+        (Some(w), Some(h)) => vec![softbuffer::Rect { x: x0, y: y0, width: w, height: h }],
+        _ => Vec::new(),
+    }
+}
@@ -1,7 +1,7 @@
 // Force rebuild 2
 use xerune::XeruneTemplate;
 use fontdue::Font;
-use xerune::{Runtime, Model};
+use xerune::{Command, Runtime, Model};
 
 #[cfg(not(feature = "fast-renderer"))]
 use skia_renderer::TinySkiaMeasurer;
@@ -63,7 +63,7 @@ impl std::str::FromStr for TodoMsg {
 impl Model for TodoList {
     type Message = TodoMsg;
 
-    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) {
+    fn update(&mut self, msg: Self::Message, context: &mut xerune::Context) -> Command<Self::Message> {
         match msg {
             TodoMsg::Toggle(index) => {
                 if index < self.items.len() {
@@ -132,6 +132,7 @@ impl Model for TodoList {
                 }
             }
         }
+        Command::none()
     }
 }
 
@@ -156,9 +157,9 @@ fn main() -> anyhow::Result<()> {
     let todo_list = TodoList { items, active_item: 0, new_item_title: String::new() };
 
     #[cfg(not(feature = "fast-renderer"))]
-    let measurer = TinySkiaMeasurer { fonts: fonts_ref };
+    let measurer = TinySkiaMeasurer { fonts: fonts_ref, families: &[] };
     #[cfg(feature = "fast-renderer")]
-    let measurer = FastMeasurer { fonts: fonts_ref };
+    let measurer = FastMeasurer { fonts: fonts_ref, families: &[] };
     
     let runtime = Runtime::new(todo_list, measurer);
     
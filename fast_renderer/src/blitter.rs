@@ -199,6 +199,39 @@ pub fn blend_solid_rect(
     }
 }
 
+/// Replaces every pixel inside `clip_rect` with `color`, alpha included,
+/// instead of blending over what was there.
+pub fn fill_rect(buffer: &mut [u32], logical_w: u32, logical_h: u32, physical_w: u32, color: u32, clip_rect: Option<xerune::Rect>, rotate: bool) {
+    let (start_x, start_y, end_x, end_y) = if let Some(cr) = clip_rect {
+        (
+            cr.x.max(0.0) as usize,
+            cr.y.max(0.0) as usize,
+            (cr.x + cr.width).min(logical_w as f32).max(0.0) as usize,
+            (cr.y + cr.height).min(logical_h as f32).max(0.0) as usize,
+        )
+    } else {
+        (0, 0, logical_w as usize, logical_h as usize)
+    };
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
+
+    let physical_w = physical_w as usize;
+    if rotate {
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                if let Some(pixel) = buffer.get_mut(x * physical_w + (physical_w - 1 - y)) {
+                    *pixel = color;
+                }
+            }
+        }
+    } else {
+        for y in start_y..end_y {
+            buffer[y * physical_w + start_x..y * physical_w + end_x].fill(color);
+        }
+    }
+}
+
 pub fn blend_glyph_span(dst: &mut [u32], coverage: &[u8], color: u32) {
     let color_a = (color >> 24) & 0xff;
     if color_a == 0 {
@@ -163,3 +163,56 @@ pub fn draw_gradient_rect(
         }
     }
 }
+
+/// Fills a rect with a centered radial gradient, pixel by pixel. Rounded
+/// corners are cut without anti-aliasing.
+pub fn draw_radial_gradient_rect(
+    buffer: &mut [u32],
+    logical_w: u32,
+    logical_h: u32,
+    physical_w: u32,
+    rect: xerune::Rect,
+    radius: f32,
+    gradient: &xerune::RadialGradient,
+    swap_rb: bool,
+    clip_rect: Option<xerune::Rect>,
+    rotate: bool,
+) {
+    let (clip_x1, clip_y1, clip_x2, clip_y2) = if let Some(cr) = clip_rect {
+        (cr.x.max(0.0), cr.y.max(0.0), (cr.x + cr.width).min(logical_w as f32), (cr.y + cr.height).min(logical_h as f32))
+    } else {
+        (0.0, 0.0, logical_w as f32, logical_h as f32)
+    };
+    let start_x = rect.x.max(clip_x1) as i32;
+    let start_y = rect.y.max(clip_y1) as i32;
+    let end_x = (rect.x + rect.width).min(clip_x2) as i32;
+    let end_y = (rect.y + rect.height).min(clip_y2) as i32;
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
+
+    let (rx, ry) = gradient.radii(rect.width, rect.height);
+    let (cx, cy) = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    let r = radius.min(rect.width / 2.0).min(rect.height / 2.0).max(0.0);
+    for y in start_y..end_y {
+        for x in start_x..end_x {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            // Distance past the corner circle's center, if in a corner.
+            let ox = (rect.x + r - px).max(px - (rect.x + rect.width - r)).max(0.0);
+            let oy = (rect.y + r - py).max(py - (rect.y + rect.height - r)).max(0.0);
+            if ox * ox + oy * oy > r * r {
+                continue;
+            }
+            let t = ((px - cx) / rx.max(f32::EPSILON)).hypot((py - cy) / ry.max(f32::EPSILON));
+            let color = pack_color(sample_gradient(&gradient.stops, t), swap_rb);
+            let idx = if rotate {
+                x as usize * physical_w as usize + (physical_w as usize - 1 - y as usize)
+            } else {
+                y as usize * physical_w as usize + x as usize
+            };
+            if idx < buffer.len() {
+                blend_pixel(&mut buffer[idx], color);
+            }
+        }
+    }
+}
@@ -4,10 +4,11 @@ pub mod rounded_rect;
 
 use std::collections::HashMap;
 use fontdue::Font;
-use xerune::{Canvas, DrawCommand, Rect, Renderer, TextMeasurer};
+use xerune::{Canvas, DrawCommand, Gradient, Rect, Renderer, TextMeasurer};
 
-use blitter::{pack_color, blend_solid_rect, blend_pixel, blend_glyph_span, div_255};
+use blitter::{pack_color, blend_solid_rect, fill_rect, blend_pixel, blend_glyph_span, div_255};
 use rounded_rect::{draw_rounded_rect, draw_rounded_border};
+use gradient::draw_radial_gradient_rect;
 
 #[cfg(feature = "profile")]
 macro_rules! profile {
@@ -20,7 +21,12 @@ macro_rules! profile {
 }
 
 pub struct FastMeasurer<'a> {
+    /// Regular, bold, italic and bold italic faces, then those of further
+    /// families; see `xerune::font_index` and `xerune::FontRegistry`.
     pub fonts: &'a [Font],
+    /// Names of the families after the default one, as
+    /// `xerune::FontRegistry::families` gives them.
+    pub families: &'a [String],
 }
 
 impl<'a> TextMeasurer for FastMeasurer<'a> {
@@ -50,11 +56,11 @@ impl<'a> TextMeasurer for FastMeasurer<'a> {
             return dims;
         }
 
-        let font_index = if weight > 0 && self.fonts.len() > 1 { 1 } else { 0 };
+        let font_index = xerune::font_index(weight, self.fonts.len());
 
         let mut layout = fontdue::layout::Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown);
         layout.reset(&fontdue::layout::LayoutSettings::default());
-        layout.append(self.fonts, &fontdue::layout::TextStyle::new(text, font_size, font_index));
+        xerune::fonts::append_text(&mut layout, self.fonts, text, font_size, font_index);
 
         let mut min_x = f32::MAX;
         let mut min_y = f32::MAX;
@@ -73,7 +79,14 @@ impl<'a> TextMeasurer for FastMeasurer<'a> {
             if gy + gh > max_y { max_y = gy + gh; }
         }
 
-        let width = if max_x > min_x { max_x - min_x } else { 0.0 };
+        // Glyphs are drawn from the pen origin at x = 0, so reserve up to the
+        // pen position after the last advance (trailing spaces included), or
+        // further if the ink overhangs it, as italics may.
+        let pen_end = layout.glyphs().last().map_or(0.0, |glyph| {
+            let metrics = self.fonts[glyph.font_index].metrics_indexed(glyph.key.glyph_index, glyph.key.px);
+            glyph.x - metrics.xmin as f32 + metrics.advance_width
+        });
+        let width = if max_x > min_x { pen_end.max(max_x) } else { 0.0 };
         
         let height = if let Some(metrics) = self.fonts[font_index].horizontal_line_metrics(font_size) {
             metrics.new_line_size
@@ -91,6 +104,14 @@ impl<'a> TextMeasurer for FastMeasurer<'a> {
 
         result
     }
+
+    fn font_family_id(&self, name: &str) -> Option<u16> {
+        xerune::font_family_id(self.families, name)
+    }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        xerune::fonts::text_layout(self.fonts, text, font_size, weight)
+    }
 }
 
 pub struct CachedGlyph {
@@ -99,17 +120,26 @@ pub struct CachedGlyph {
     pub bitmap: Vec<u8>,
 }
 
+/// An image as (width, height, pixels), the pixels packed for the target.
+pub type DecodedImage = (u32, u32, Vec<u32>);
+
+/// Decoded images and tinted icons, kept between frames by the caller.
+pub type ImageCache = xerune::decode::ImageCache<DecodedImage>;
+
 pub struct FastRenderer<'a> {
     pub buffer: &'a mut [u32],
     pub width: u32,
     pub height: u32,
     pub physical_width: u32,
     pub physical_height: u32,
+    /// Regular, bold, italic and bold italic faces, then those of further
+    /// families; see `xerune::font_index` and `xerune::FontRegistry`.
     pub fonts: &'a [Font],
     pub clip_stack: Vec<Rect>,
     pub swap_rb: bool,
     pub rotate: bool,
-    pub image_cache: &'a mut HashMap<String, (u32, u32, Vec<u32>)>, // (width, height, pixels)
+    /// Decoded images and tinted icons; see `xerune::decode::ImageCache`.
+    pub image_cache: &'a mut ImageCache,
     pub glyph_cache: &'a mut HashMap<(usize, u16, u32), CachedGlyph>,
     pub layout: fontdue::layout::Layout,
 }
@@ -120,7 +150,7 @@ impl<'a> FastRenderer<'a> {
         width: u32,
         height: u32,
         fonts: &'a [Font],
-        image_cache: &'a mut HashMap<String, (u32, u32, Vec<u32>)>,
+        image_cache: &'a mut ImageCache,
         glyph_cache: &'a mut HashMap<(usize, u16, u32), CachedGlyph>,
     ) -> Self {
         Self {
@@ -139,6 +169,12 @@ impl<'a> FastRenderer<'a> {
         }
     }
 
+    /// Drops every decoded image, e.g. after image files changed on disk;
+    /// they are loaded again the next time they are drawn.
+    pub fn clear_image_cache(&mut self) {
+        self.image_cache.clear();
+    }
+
     fn get_clip_rect(&self) -> Option<Rect> {
         self.clip_stack.last().copied()
     }
@@ -146,9 +182,14 @@ impl<'a> FastRenderer<'a> {
 
 impl<'a> TextMeasurer for FastRenderer<'a> {
     fn measure_text(&self, text: &str, font_size: f32, weight: u16) -> (f32, f32) {
-        let measurer = FastMeasurer { fonts: self.fonts };
+        let measurer = FastMeasurer { fonts: self.fonts, families: &[] };
         measurer.measure_text(text, font_size, weight)
     }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        let measurer = FastMeasurer { fonts: self.fonts, families: &[] };
+        measurer.text_layout(text, font_size, weight)
+    }
 }
 
 impl<'a> Renderer for FastRenderer<'a> {
@@ -158,7 +199,22 @@ impl<'a> Renderer for FastRenderer<'a> {
             self.clip_stack.push(dr);
         }
 
+        // Value labels are drawn as text right after their slider or progress bar.
+        let labels: Vec<Option<DrawCommand>> = commands.iter().map(|c| c.label_command(&*self)).collect();
+        let commands = commands.iter().zip(labels.iter()).flat_map(|(command, label)| std::iter::once(command).chain(label.as_ref()));
+
+        // Offsets of the enclosing transforms. Only translations are applied
+        // here; a scaled or rotated element is drawn untransformed.
+        let mut offsets: Vec<(f32, f32)> = Vec::new();
         for command in commands {
+            let shifted;
+            let command = match offsets.last() {
+                Some(&(dx, dy)) if dx != 0.0 || dy != 0.0 => {
+                    shifted = command.translated(dx, dy);
+                    &shifted
+                }
+                _ => command,
+            };
             let cmd_bounds = command.bounds();
 
             if let Some(dr) = dirty_rect {
@@ -170,7 +226,8 @@ impl<'a> Renderer for FastRenderer<'a> {
             }
 
             match command {
-                DrawCommand::Clip { rect } => {
+                // Corners are not rounded: clips are kept as plain rects here.
+                DrawCommand::Clip { rect, .. } => {
                     profile!("render_clip");
                     let intersected = if let Some(top) = self.clip_stack.last() {
                         let x1 = top.x.max(rect.x);
@@ -192,6 +249,13 @@ impl<'a> Renderer for FastRenderer<'a> {
                     profile!("render_pop_clip");
                     self.clip_stack.pop();
                 }
+                DrawCommand::PushTransform { matrix, .. } => {
+                    let (dx, dy) = offsets.last().copied().unwrap_or((0.0, 0.0));
+                    offsets.push(if matrix.is_translation() { (dx + matrix.e, dy + matrix.f) } else { (dx, dy) });
+                }
+                DrawCommand::PopTransform => {
+                    offsets.pop();
+                }
                 DrawCommand::DrawRect {
                     rect,
                     color,
@@ -203,7 +267,24 @@ impl<'a> Renderer for FastRenderer<'a> {
                     profile!("render_rect");
                     let clip = self.get_clip_rect();
 
-                    if color.is_some() || gradient.is_some() {
+                    if let Some(Gradient::Radial(grad)) = gradient {
+                        draw_radial_gradient_rect(
+                            self.buffer,
+                            self.width,
+                            self.height,
+                            self.physical_width,
+                            *rect,
+                            *border_radius,
+                            grad,
+                            self.swap_rb,
+                            clip,
+                            self.rotate,
+                        );
+                    } else if color.is_some() || gradient.is_some() {
+                        let linear = match gradient {
+                            Some(Gradient::Linear(grad)) => Some(grad),
+                            _ => None,
+                        };
                         draw_rounded_rect(
                             self.buffer,
                             self.width,
@@ -215,7 +296,7 @@ impl<'a> Renderer for FastRenderer<'a> {
                             rect.height as i32,
                             *border_radius,
                             *color,
-                            gradient.as_ref(),
+                            linear,
                             self.swap_rb,
                             clip,
                             self.rotate,
@@ -249,14 +330,20 @@ impl<'a> Renderer for FastRenderer<'a> {
                     color,
                     font_size,
                     weight,
+                    word_spacing,
+                    max_width,
                 } => {
                     profile!("render_text");
-                    let font_index = if *weight > 0 && self.fonts.len() > 1 { 1 } else { 0 };
+                    let font_index = xerune::font_index(*weight, self.fonts.len());
+                    let text = match max_width {
+                        Some(max_width) => self.truncate_text(text, *font_size, *weight, *word_spacing, *max_width),
+                        None => std::borrow::Cow::Borrowed(text.as_str()),
+                    };
 
                     {
                         profile!("text_layout");
                         self.layout.reset(&fontdue::layout::LayoutSettings::default());
-                        self.layout.append(self.fonts, &fontdue::layout::TextStyle::new(text, *font_size, font_index));
+                        xerune::fonts::append_text(&mut self.layout, self.fonts, &text, *font_size, font_index);
                     }
 
                     let packed_color = pack_color(*color, self.swap_rb);
@@ -278,6 +365,7 @@ impl<'a> Renderer for FastRenderer<'a> {
                     let g = (packed_color >> 8) & 0xff;
                     let b = packed_color & 0xff;
 
+                    let mut extra_advance = 0.0;
                     for glyph in self.layout.glyphs() {
                         let sub_px = (glyph.key.px * 16.0) as u32;
                         let cache_key = (glyph.font_index, glyph.key.glyph_index, sub_px);
@@ -297,7 +385,7 @@ impl<'a> Renderer for FastRenderer<'a> {
                         }
 
                         if let Some(cached) = self.glyph_cache.get(&cache_key) {
-                            let gx = (rect.x + glyph.x) as i32;
+                            let gx = (rect.x + glyph.x + extra_advance) as i32;
                             let gy = (rect.y + glyph.y) as i32;
                             let gw = cached.width as i32;
                             let gh = cached.height as i32;
@@ -351,49 +439,77 @@ impl<'a> Renderer for FastRenderer<'a> {
                                 }
                             }
                         }
+
+                        if glyph.parent == ' ' {
+                            extra_advance += *word_spacing;
+                        }
                     }
                 }
                 DrawCommand::DrawImage {
                     src,
                     rect,
                     border_radius,
+                    src_rect,
+                    tint,
+                    opacity,
+                    size,
+                    position,
                 } => {
                     profile!("render_image");
-                    if !self.image_cache.contains_key(src) {
-                        if let Ok(data) = std::fs::read(src) {
-                            if let Ok(png_pixmap) = tiny_skia::Pixmap::decode_png(&data) {
-                                let w = png_pixmap.width();
-                                let h = png_pixmap.height();
-                                let mut pixels = Vec::with_capacity((w * h) as usize);
-                                for chunk in png_pixmap.data().chunks_exact(4) {
-                                    let r = chunk[0];
-                                    let g = chunk[1];
-                                    let b = chunk[2];
-                                    let a = chunk[3];
-                                    let col = xerune::Color::new(r, g, b, a);
-                                    pixels.push(pack_color(col, self.swap_rb));
-                                }
-                                self.image_cache.insert(src.clone(), (w, h, pixels));
-                            } else {
-                                log::warn!("Failed to decode PNG image: {}", src);
+                    let swap_rb = self.swap_rb;
+                    // Failures are cached too; they are drawn as the grey placeholder.
+                    self.image_cache.load(src, || match xerune::decode::load_image(src) {
+                        Ok(image) => {
+                            let w = image.width();
+                            let h = image.height();
+                            let mut pixels = Vec::with_capacity((w * h) as usize);
+                            for chunk in image.data().chunks_exact(4) {
+                                let r = chunk[0];
+                                let g = chunk[1];
+                                let b = chunk[2];
+                                let a = chunk[3];
+                                let col = xerune::Color::new(r, g, b, a);
+                                pixels.push(pack_color(col, swap_rb));
                             }
-                        } else {
-                            log::warn!("Failed to read image file: {}", src);
+                            Some((w, h, pixels))
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to load image {}: {}", src, e);
+                            None
                         }
+                    });
+
+                    // Tinted icons are cached once per color.
+                    let tint_key = tint.map(|tint| [tint.r, tint.g, tint.b, tint.a]);
+                    if let (Some(key), Some(tint)) = (tint_key, tint) {
+                        self.image_cache.tint(src, key, |(w, h, pixels)| {
+                            let tinted = pixels.iter().map(|p| {
+                                let a = (p >> 24) * tint.a as u32 / 255;
+                                pack_color(xerune::Color::new(tint.r, tint.g, tint.b, a as u8), swap_rb)
+                            }).collect();
+                            (*w, *h, tinted)
+                        });
                     }
+                    let image = self.image_cache.get(src, tint_key);
 
-                    if let Some(&(img_w, img_h, ref img_pixels)) = self.image_cache.get(src) {
+                    if let Some(&(img_w, img_h, ref img_pixels)) = image {
+                        let whole = xerune::Rect::new(0.0, 0.0, img_w as f32, img_h as f32);
+                        let Some((rect, source)) = size.fit(*rect, src_rect.unwrap_or(whole), *position) else {
+                            continue;
+                        };
                         let clip = self.get_clip_rect();
                         blit_image(
                             self.buffer,
                             self.width,
                             self.height,
                             self.physical_width,
-                            rect,
+                            &rect,
                             *border_radius,
                             img_w,
                             img_h,
                             img_pixels,
+                            Some(source),
+                            *opacity,
                             clip,
                             self.rotate,
                         );
@@ -415,6 +531,71 @@ impl<'a> Renderer for FastRenderer<'a> {
                         );
                     }
                 }
+                DrawCommand::DrawCaret { color, .. } => {
+                    let Some(caret) = command.caret_rect(&*self) else {
+                        continue;
+                    };
+                    let clip = self.get_clip_rect();
+                    draw_rounded_rect(
+                        self.buffer,
+                        self.width,
+                        self.height,
+                        self.physical_width,
+                        caret.x as i32,
+                        caret.y as i32,
+                        caret.width as i32,
+                        caret.height as i32,
+                        0.0,
+                        Some(*color),
+                        None,
+                        self.swap_rb,
+                        clip,
+                        self.rotate,
+                    );
+                }
+                DrawCommand::DrawTextSelection { color, .. } => {
+                    let Some(selection) = command.selection_rect(&*self) else {
+                        continue;
+                    };
+                    let clip = self.get_clip_rect();
+                    draw_rounded_rect(
+                        self.buffer,
+                        self.width,
+                        self.height,
+                        self.physical_width,
+                        selection.x as i32,
+                        selection.y as i32,
+                        selection.width as i32,
+                        selection.height as i32,
+                        0.0,
+                        Some(*color),
+                        None,
+                        self.swap_rb,
+                        clip,
+                        self.rotate,
+                    );
+                }
+                DrawCommand::DrawScrollbar { rect, thumb_rect, .. } => {
+                    let clip = self.get_clip_rect();
+                    for (r, color) in [(rect, xerune::Color::new(0, 0, 0, 20)), (thumb_rect, xerune::Color::new(0, 0, 0, 100))] {
+                        draw_rounded_rect(
+                            self.buffer,
+                            self.width,
+                            self.height,
+                            self.physical_width,
+                            r.x as i32,
+                            r.y as i32,
+                            r.width as i32,
+                            r.height as i32,
+                            r.width.min(r.height) / 2.0,
+                            Some(color),
+                            None,
+                            self.swap_rb,
+                            clip,
+                            self.rotate,
+                        );
+                    }
+                }
                 DrawCommand::DrawCheckbox { rect, checked, color } => {
                     profile!("render_checkbox");
                     let clip = self.get_clip_rect();
@@ -435,28 +616,19 @@ impl<'a> Renderer for FastRenderer<'a> {
                         self.rotate,
                     );
                     if *checked {
-                        let inset = 4;
-                        let inner_x = rect.x as i32 + inset;
-                        let inner_y = rect.y as i32 + inset;
-                        let inner_w = rect.width as i32 - inset * 2;
-                        let inner_h = rect.height as i32 - inset * 2;
-                        let packed = pack_color(*color, self.swap_rb);
-                        blend_solid_rect(
+                        draw_checkmark(
                             self.buffer,
                             self.width,
                             self.height,
                             self.physical_width,
-                            inner_x,
-                            inner_y,
-                            inner_w,
-                            inner_h,
-                            packed,
+                            *rect,
+                            pack_color(*color, self.swap_rb),
                             clip,
                             self.rotate,
                         );
                     }
                 }
-                DrawCommand::DrawSlider { rect, value, color } => {
+                DrawCommand::DrawSlider { rect, value, color, .. } => {
                     profile!("render_slider");
                     let clip = self.get_clip_rect();
 
@@ -543,7 +715,7 @@ impl<'a> Renderer for FastRenderer<'a> {
                         self.rotate,
                     );
                 }
-                DrawCommand::DrawProgress { rect, value, max, color } => {
+                DrawCommand::DrawProgress { rect, value, max, color, .. } => {
                     profile!("render_progress");
                     let clip = self.get_clip_rect();
 
@@ -610,11 +782,74 @@ impl<'a> Renderer for FastRenderer<'a> {
                             canvas.width,
                             canvas.height,
                             &pixels,
+                            None,
+                            1.0,
+                            clip,
+                            self.rotate,
+                        );
+                    }
+                }
+                DrawCommand::DrawShadow { rect, border_radius, blur, color } => {
+                    profile!("render_shadow");
+                    // Approximates the blur with translucent rects stacked from the outer
+                    // edge of the blur inwards, so the overlap darkens towards the middle.
+                    let clip = self.get_clip_rect();
+                    let steps = (*blur as i32).clamp(1, 4);
+                    let step_color = xerune::Color { a: (color.a as i32 / steps) as u8, ..*color };
+                    for i in 0..steps {
+                        let grow = if *blur > 0.0 { blur / 2.0 * (1.0 - 2.0 * i as f32 / steps as f32) } else { 0.0 };
+                        let r = rect.inflate(grow);
+                        draw_rounded_rect(
+                            self.buffer,
+                            self.width,
+                            self.height,
+                            self.physical_width,
+                            r.x as i32,
+                            r.y as i32,
+                            r.width as i32,
+                            r.height as i32,
+                            (border_radius + grow).max(0.0),
+                            Some(step_color),
+                            None,
+                            self.swap_rb,
                             clip,
                             self.rotate,
                         );
                     }
                 }
+                DrawCommand::BackdropBlur { rect, radius } => {
+                    profile!("render_backdrop_blur");
+                    let clip = self.get_clip_rect().unwrap_or(Rect::new(0.0, 0.0, self.width as f32, self.height as f32));
+                    let x0 = rect.x.max(clip.x).max(0.0) as usize;
+                    let y0 = rect.y.max(clip.y).max(0.0) as usize;
+                    let x1 = ((rect.x + rect.width).min(clip.x + clip.width).min(self.width as f32).max(0.0)) as usize;
+                    let y1 = ((rect.y + rect.height).min(clip.y + clip.height).min(self.height as f32).max(0.0)) as usize;
+                    // A rotated buffer stores logical columns as rows; the blur is the same either way.
+                    let pw = self.physical_width as usize;
+                    let (px0, py0, px1, py1) = if self.rotate {
+                        (pw.saturating_sub(y1), x0, pw.saturating_sub(y0), x1)
+                    } else {
+                        (x0, y0, x1, y1)
+                    };
+                    if px1 > px0 && py1 > py0 && py1 * pw <= self.buffer.len() {
+                        let w = px1 - px0;
+                        let mut pixels = Vec::with_capacity(w * (py1 - py0));
+                        for y in py0..py1 {
+                            pixels.extend_from_slice(&self.buffer[y * pw + px0..y * pw + px1]);
+                        }
+                        xerune::box_blur(&mut pixels, w, py1 - py0, *radius);
+                        for (y, src) in (py0..py1).zip(pixels.chunks_exact(w)) {
+                            self.buffer[y * pw + px0..y * pw + px1].copy_from_slice(src);
+                        }
+                    }
+                }
+                DrawCommand::Clear { color } => {
+                    profile!("render_clear");
+                    let clip = self.get_clip_rect();
+                    fill_rect(self.buffer, self.width, self.height, self.physical_width, pack_color(*color, self.swap_rb), clip, self.rotate);
+                }
+                // Drawing straight into the buffer is already cheap; layers are drawn inline.
+                DrawCommand::BeginLayer { .. } | DrawCommand::EndLayer => {}
             }
         }
 
@@ -634,6 +869,8 @@ pub fn blit_image(
     img_w: u32,
     img_h: u32,
     img_pixels: &[u32],
+    src_rect: Option<Rect>,
+    opacity: f32,
     clip: Option<Rect>,
     rotate: bool,
 ) {
@@ -662,50 +899,51 @@ pub fn blit_image(
         return;
     }
 
-    let scale_x = img_w as f32 / rw as f32;
-    let scale_y = img_h as f32 / rh as f32;
+    let source = src_rect.unwrap_or(Rect::new(0.0, 0.0, img_w as f32, img_h as f32));
+    let scale_x = source.width / rw as f32;
+    let scale_y = source.height / rh as f32;
 
     let r_f32 = border_radius.min(rw as f32 / 2.0).min(rh as f32 / 2.0).max(0.0);
 
     for py in start_y..end_y {
         let dy_offset = py - ry;
-        let src_y = ((dy_offset as f32 * scale_y) as u32).min(img_h - 1);
+        let src_y = ((source.y + dy_offset as f32 * scale_y).max(0.0) as u32).min(img_h - 1);
         let src_row_start = (src_y * img_w) as usize;
 
         for px in start_x..end_x {
             let dx_offset = px - rx;
-            let src_x = ((dx_offset as f32 * scale_x) as u32).min(img_w - 1);
+            let src_x = ((source.x + dx_offset as f32 * scale_x).max(0.0) as u32).min(img_w - 1);
             let pixel = img_pixels[src_row_start + src_x as usize];
 
-            let mut coverage = 1.0;
+            let mut coverage = opacity;
             if r_f32 > 0.0 {
                 if dx_offset < r_f32 as i32 && dy_offset < r_f32 as i32 {
                     let cx = rx as f32 + r_f32;
                     let cy = ry as f32 + r_f32;
                     let dx = px as f32 + 0.5 - cx;
                     let dy = py as f32 + 0.5 - cy;
-                    coverage = (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
+                    coverage = opacity * (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
                 }
                 else if dx_offset >= rw - r_f32 as i32 && dy_offset < r_f32 as i32 {
                     let cx = rx as f32 + rw as f32 - r_f32;
                     let cy = ry as f32 + r_f32;
                     let dx = px as f32 + 0.5 - cx;
                     let dy = py as f32 + 0.5 - cy;
-                    coverage = (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
+                    coverage = opacity * (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
                 }
                 else if dx_offset < r_f32 as i32 && dy_offset >= rh - r_f32 as i32 {
                     let cx = rx as f32 + r_f32;
                     let cy = ry as f32 + rh as f32 - r_f32;
                     let dx = px as f32 + 0.5 - cx;
                     let dy = py as f32 + 0.5 - cy;
-                    coverage = (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
+                    coverage = opacity * (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
                 }
                 else if dx_offset >= rw - r_f32 as i32 && dy_offset >= rh - r_f32 as i32 {
                     let cx = rx as f32 + rw as f32 - r_f32;
                     let cy = ry as f32 + rh as f32 - r_f32;
                     let dx = px as f32 + 0.5 - cx;
                     let dy = py as f32 + 0.5 - cy;
-                    coverage = (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
+                    coverage = opacity * (r_f32 + 0.5 - (dx*dx + dy*dy).sqrt()).clamp(0.0, 1.0);
                 }
             }
 
@@ -730,3 +968,71 @@ pub fn blit_image(
         }
     }
 }
+
+/// Strokes the two-segment check mark of a checked checkbox, blending each pixel once
+/// with coverage taken from its distance to the nearer segment.
+fn draw_checkmark(
+    buffer: &mut [u32],
+    logical_w: u32,
+    logical_h: u32,
+    physical_w: u32,
+    rect: Rect,
+    color: u32,
+    clip: Option<Rect>,
+    rotate: bool,
+) {
+    let (clip_x1, clip_y1, clip_x2, clip_y2) = if let Some(cr) = clip {
+        (
+            cr.x.max(0.0) as i32,
+            cr.y.max(0.0) as i32,
+            (cr.x + cr.width).min(logical_w as f32) as i32,
+            (cr.y + cr.height).min(logical_h as f32) as i32,
+        )
+    } else {
+        (0, 0, logical_w as i32, logical_h as i32)
+    };
+
+    let start_x = (rect.x as i32).max(clip_x1);
+    let start_y = (rect.y as i32).max(clip_y1);
+    let end_x = ((rect.x + rect.width).ceil() as i32).min(clip_x2);
+    let end_y = ((rect.y + rect.height).ceil() as i32).min(clip_y2);
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
+
+    let points = [
+        (rect.x + rect.width * 0.22, rect.y + rect.height * 0.52),
+        (rect.x + rect.width * 0.42, rect.y + rect.height * 0.72),
+        (rect.x + rect.width * 0.78, rect.y + rect.height * 0.30),
+    ];
+    let half_width = (rect.width.min(rect.height) / 8.0).max(2.0) / 2.0;
+
+    let segment_distance = |px: f32, py: f32, (ax, ay): (f32, f32), (bx, by): (f32, f32)| {
+        let (dx, dy) = (bx - ax, by - ay);
+        let len_sq = dx * dx + dy * dy;
+        let t = if len_sq > 0.0 { (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+        let (cx, cy) = (ax + t * dx - px, ay + t * dy - py);
+        (cx * cx + cy * cy).sqrt()
+    };
+
+    let src_alpha = ((color >> 24) & 0xff) as f32;
+    for py in start_y..end_y {
+        for px in start_x..end_x {
+            let (fx, fy) = (px as f32 + 0.5, py as f32 + 0.5);
+            let dist = segment_distance(fx, fy, points[0], points[1]).min(segment_distance(fx, fy, points[1], points[2]));
+            let coverage = (half_width + 0.5 - dist).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let pixel = (color & 0x00ffffff) | (((src_alpha * coverage).round() as u32) << 24);
+            let idx = if rotate {
+                (px as usize * physical_w as usize) + (physical_w as usize - 1 - py as usize)
+            } else {
+                (py as usize * physical_w as usize) + px as usize
+            };
+            if idx < buffer.len() {
+                blend_pixel(&mut buffer[idx], pixel);
+            }
+        }
+    }
+}
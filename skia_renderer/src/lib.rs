@@ -1,7 +1,8 @@
-use xerune::{Canvas, DrawCommand, TextMeasurer, Renderer};
+use xerune::{Canvas, DrawCommand, Gradient, TextMeasurer, Renderer};
 use fontdue::Font;
 use tiny_skia::{Pixmap, Transform, PixmapPaint, Mask, PathBuilder, FillRule, PixmapRef};
 use std::collections::HashMap;
+use xerune::decode::ImageCache;
 
 #[cfg(feature = "profile")]
 macro_rules! profile {
@@ -14,7 +15,12 @@ macro_rules! profile {
 }
 
 pub struct TinySkiaMeasurer<'a> {
+    /// Regular, bold, italic and bold italic faces, then those of further
+    /// families; see `xerune::font_index` and `xerune::FontRegistry`.
     pub fonts: &'a [Font],
+    /// Names of the families after the default one, as
+    /// `xerune::FontRegistry::families` gives them.
+    pub families: &'a [String],
 }
 
 impl<'a> TextMeasurer for TinySkiaMeasurer<'a> {
@@ -43,14 +49,13 @@ impl<'a> TextMeasurer for TinySkiaMeasurer<'a> {
             return dims;
         }
 
-        // Simple font selection: 0 = Regular, >0 = Bold (if available)
-        let font_index = if weight > 0 && self.fonts.len() > 1 { 1 } else { 0 };
+        let font_index = xerune::font_index(weight, self.fonts.len());
 
         let mut layout = fontdue::layout::Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown);
         layout.reset(&fontdue::layout::LayoutSettings {
             ..fontdue::layout::LayoutSettings::default()
         });
-        layout.append(&self.fonts[..], &fontdue::layout::TextStyle::new(text, font_size, font_index));
+        xerune::fonts::append_text(&mut layout, self.fonts, text, font_size, font_index);
 
         let mut min_x = f32::MAX;
         let mut min_y = f32::MAX;
@@ -69,7 +74,14 @@ impl<'a> TextMeasurer for TinySkiaMeasurer<'a> {
             if gy + gh > max_y { max_y = gy + gh; }
         }
 
-        let width = if max_x > min_x { max_x - min_x } else { 0.0 };
+        // Glyphs are drawn from the pen origin at x = 0, so reserve up to the
+        // pen position after the last advance (trailing spaces included), or
+        // further if the ink overhangs it, as italics may.
+        let pen_end = layout.glyphs().last().map_or(0.0, |glyph| {
+            let metrics = self.fonts[glyph.font_index].metrics_indexed(glyph.key.glyph_index, glyph.key.px);
+            glyph.x - metrics.xmin as f32 + metrics.advance_width
+        });
+        let width = if max_x > min_x { pen_end.max(max_x) } else { 0.0 };
         
         // Use font metrics for stable height
         let height = if let Some(metrics) = self.fonts[font_index].horizontal_line_metrics(font_size) {
@@ -89,27 +101,75 @@ impl<'a> TextMeasurer for TinySkiaMeasurer<'a> {
 
         result
     }
+
+    fn font_family_id(&self, name: &str) -> Option<u16> {
+        xerune::font_family_id(self.families, name)
+    }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        xerune::fonts::text_layout(self.fonts, text, font_size, weight)
+    }
+}
+
+/// Lays `runtime` out at `width` x `height` and renders a full frame into a
+/// new pixmap, without a window: for snapshot tests and thumbnails. `None` if
+/// either side is zero.
+pub fn render_to_pixmap<M, R>(runtime: &mut xerune::Runtime<M, R>, width: u32, height: u32, fonts: &[Font]) -> Option<Pixmap>
+where
+    M: xerune::Model + xerune::TemplateLayout,
+    R: TextMeasurer,
+{
+    let mut pixmap = Pixmap::new(width, height)?;
+    let (mut image_cache, mut gradient_cache, mut glyph_cache) = (ImageCache::default(), HashMap::new(), HashMap::new());
+    runtime.set_size(width as f32, height as f32);
+    runtime.invalidate();
+    let mut renderer = TinySkiaRenderer::new(pixmap.as_mut(), fonts, &mut image_cache, &mut gradient_cache, &mut glyph_cache);
+    runtime.render(&mut renderer);
+    Some(pixmap)
+}
+
+/// Pixmaps of `will-change` layers kept between frames, so that a layer whose
+/// contents did not change is only blitted at its new position.
+#[derive(Default)]
+pub struct LayerCache {
+    layers: Vec<Option<CachedLayer>>,
+}
+
+struct CachedLayer {
+    // The layer's commands relative to its box, which is what has to match.
+    commands: Vec<DrawCommand>,
+    swap_rb: bool,
+    // Top-left of the pixmap relative to the layer's box.
+    origin: (f32, f32),
+    pixmap: Pixmap,
 }
 
 pub struct TinySkiaRenderer<'a> {
     pub pixmap: tiny_skia::PixmapMut<'a>,
+    /// Regular, bold, italic and bold italic faces, then those of further
+    /// families; see `xerune::font_index` and `xerune::FontRegistry`.
     pub fonts: &'a [Font],
-    pub clip_stack: Vec<tiny_skia::Rect>,
+    /// Clip rects with their corner radius.
+    pub clip_stack: Vec<(tiny_skia::Rect, f32)>,
     pub current_mask: Option<Mask>,
     pub clip_mask_dirty: bool,
-    pub image_cache: &'a mut HashMap<String, Pixmap>,
+    /// Decoded images and tinted icons; see `xerune::decode::ImageCache`.
+    pub image_cache: &'a mut ImageCache<Pixmap>,
     pub gradient_cache: &'a mut HashMap<String, Pixmap>,
     pub glyph_cache: &'a mut HashMap<(usize, u16, u32, [u8; 4]), Pixmap>,
     pub layout: fontdue::layout::Layout,
     pub swap_rb: bool,
     pub transform: Transform,
+    /// Transforms to restore at each `PopTransform`.
+    transform_stack: Vec<Transform>,
+    pub layer_cache: Option<&'a mut LayerCache>,
 }
 
 impl<'a> TinySkiaRenderer<'a> {
     pub fn new(
         pixmap: tiny_skia::PixmapMut<'a>,
         fonts: &'a [Font],
-        image_cache: &'a mut HashMap<String, Pixmap>,
+        image_cache: &'a mut ImageCache<Pixmap>,
         gradient_cache: &'a mut HashMap<String, Pixmap>,
         glyph_cache: &'a mut HashMap<(usize, u16, u32, [u8; 4]), Pixmap>,
     ) -> Self {
@@ -126,9 +186,23 @@ impl<'a> TinySkiaRenderer<'a> {
             layout: fontdue::layout::Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown),
             swap_rb: false,
             transform: Transform::identity(),
+            transform_stack: Vec::new(),
+            layer_cache: None,
         }
     }
 
+    /// Drops every decoded image, e.g. after image files changed on disk;
+    /// they are loaded again the next time they are drawn.
+    pub fn clear_image_cache(&mut self) {
+        self.image_cache.clear();
+    }
+
+    /// Renders `will-change` layers through `cache`; without one they are drawn inline.
+    pub fn with_layer_cache(mut self, cache: &'a mut LayerCache) -> Self {
+        self.layer_cache = Some(cache);
+        self
+    }
+
     fn to_skia_color(&self, color: xerune::Color) -> tiny_skia::Color {
         if self.swap_rb {
             tiny_skia::Color::from_rgba8(color.b, color.g, color.r, color.a)
@@ -150,8 +224,8 @@ impl<'a> TinySkiaRenderer<'a> {
         }
 
         // Calculate intersection
-        let mut intersect = self.clip_stack[0];
-        for r in self.clip_stack.iter().skip(1) {
+        let mut intersect = self.clip_stack[0].0;
+        for (r, _) in self.clip_stack.iter().skip(1) {
             if let Some(i) = intersect.intersect(r) {
                 intersect = i;
             } else {
@@ -170,7 +244,7 @@ impl<'a> TinySkiaRenderer<'a> {
             let pm_h = self.pixmap.height() as f32;
             let covers = phys_bounds.x() <= 0.1 && phys_bounds.y() <= 0.1 && 
                          phys_bounds.right() >= pm_w - 0.1 && phys_bounds.bottom() >= pm_h - 0.1;
-            if covers {
+            if covers && !self.has_rounded_clip() {
                  self.current_mask = None;
                  return;
             }
@@ -179,6 +253,11 @@ impl<'a> TinySkiaRenderer<'a> {
         // Create mask
         if let Some(mut mask) = Mask::new(self.pixmap.width(), self.pixmap.height()) {
              mask.fill_path(&path, FillRule::Winding, true, self.transform); // true = anti-alias
+             for (r, radius) in &self.clip_stack {
+                 if *radius > 0.0 && let Some(rounded) = rounded_rect_path(*r, *radius) {
+                     mask.intersect_path(&rounded, FillRule::Winding, true, self.transform);
+                 }
+             }
              self.current_mask = Some(mask);
         }
     }
@@ -190,20 +269,31 @@ impl<'a> TinySkiaRenderer<'a> {
                 return false;
             }
             let eps = 0.5; // Epsilon tolerance for edge-touching float elements
-            return intersect.x() - eps <= logical_bounds.x() && 
-                   intersect.y() - eps <= logical_bounds.y() && 
-                   intersect.right() + eps >= logical_bounds.right() && 
-                   intersect.bottom() + eps >= logical_bounds.bottom();
+            let inside = |clip: tiny_skia::Rect| {
+                clip.x() - eps <= logical_bounds.x() &&
+                clip.y() - eps <= logical_bounds.y() &&
+                clip.right() + eps >= logical_bounds.right() &&
+                clip.bottom() + eps >= logical_bounds.bottom()
+            };
+            // Inside a rounded clip, only the rect touching its corner arcs is safe.
+            return inside(intersect) && self.clip_stack.iter().all(|(r, radius)| {
+                let inset = radius.min(r.width() / 2.0).min(r.height() / 2.0).max(0.0) * (1.0 - std::f32::consts::FRAC_1_SQRT_2);
+                inset == 0.0 || tiny_skia::Rect::from_ltrb(r.left() + inset, r.top() + inset, r.right() - inset, r.bottom() - inset).is_some_and(inside)
+            });
         }
         true
     }
 
+    fn has_rounded_clip(&self) -> bool {
+        self.clip_stack.iter().any(|(_, radius)| *radius > 0.0)
+    }
+
     fn get_clip_rect(&self) -> Option<tiny_skia::Rect> {
         if self.clip_stack.is_empty() {
             return None;
         }
-        let mut intersect = self.clip_stack[0];
-        for clip_rect in self.clip_stack.iter().skip(1) {
+        let mut intersect = self.clip_stack[0].0;
+        for (clip_rect, _) in self.clip_stack.iter().skip(1) {
             if let Some(i) = intersect.intersect(clip_rect) {
                 intersect = i;
             } else {
@@ -217,9 +307,14 @@ impl<'a> TinySkiaRenderer<'a> {
 impl<'a> TextMeasurer for TinySkiaRenderer<'a> {
     fn measure_text(&self, text: &str, font_size: f32, weight: u16) -> (f32, f32) {
         profile!("text_measure");
-        let measurer = TinySkiaMeasurer { fonts: self.fonts };
+        let measurer = TinySkiaMeasurer { fonts: self.fonts, families: &[] };
         measurer.measure_text(text, font_size, weight)
     }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        let measurer = TinySkiaMeasurer { fonts: self.fonts, families: &[] };
+        measurer.text_layout(text, font_size, weight)
+    }
 }
 
 impl<'a> Renderer for TinySkiaRenderer<'a> {
@@ -227,16 +322,45 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
         profile!("render_full");
         if let Some(dr) = dirty_rect {
             if let Some(tr) = tiny_skia::Rect::from_xywh(dr.x, dr.y, dr.width, dr.height) {
-                self.clip_stack.push(tr);
+                self.clip_stack.push((tr, 0.0));
                 self.update_clip_mask();
             }
         }
 
-        for command in commands {
+        // Value labels are drawn as text right after their slider or progress bar.
+        let labels: Vec<Option<DrawCommand>> = commands.iter().map(|c| c.label_command(&*self)).collect();
+        let commands: Vec<&DrawCommand> = commands.iter().zip(labels.iter()).flat_map(|(command, label)| std::iter::once(command).chain(label.as_ref())).collect();
+        self.render_commands(&commands, canvases, dirty_rect);
+
+        if dirty_rect.is_some() {
+            self.clip_stack.pop();
+            self.update_clip_mask();
+        }
+    }
+}
+
+impl<'a> TinySkiaRenderer<'a> {
+    fn render_commands(&mut self, commands: &[&DrawCommand], canvases: &HashMap<String, Canvas>, dirty_rect: Option<xerune::Rect>) {
+        let mut layer_index = 0;
+        let mut i = 0;
+        while i < commands.len() {
+            let command = commands[i];
+            i += 1;
+
+            if let DrawCommand::BeginLayer { rect } = command {
+                let end = matching_end_layer(commands, i);
+                if self.draw_layer(layer_index, *rect, &commands[i..end], canvases) {
+                    i = end + 1;
+                }
+                layer_index += 1;
+                continue;
+            }
+
             let cmd_bounds = command.bounds();
 
             // Optimization: Skip drawing commands that are strictly outside the dirty_rect
-            if let Some(dr) = dirty_rect {
+            // (their bounds are untransformed, so not inside a transform)
+            if let Some(dr) = dirty_rect.filter(|_| self.transform_stack.is_empty()) {
                 if let Some(cb) = cmd_bounds {
                     // Only draw commands that actually intersect the dirty region
                     if !cb.intersects(&dr) {
@@ -247,20 +371,28 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
             
             // Extracted strictly un-padded optical bounds without safety bleeds
             let item_rect = match command {
-                DrawCommand::Clip { rect } => Some(*rect),
+                DrawCommand::Clip { rect, .. } => Some(*rect),
                 DrawCommand::PopClip => None,
+                DrawCommand::PushTransform { .. } | DrawCommand::PopTransform => None,
                 DrawCommand::DrawRect { rect, .. } => Some(*rect),
                 DrawCommand::DrawText { rect, .. } => Some(*rect),
+                DrawCommand::DrawCaret { rect, .. } => Some(*rect),
+                DrawCommand::DrawTextSelection { rect, .. } => Some(*rect),
+                DrawCommand::DrawScrollbar { rect, .. } => Some(*rect),
                 DrawCommand::DrawImage { rect, .. } => Some(*rect),
                 DrawCommand::DrawCheckbox { rect, .. } => Some(*rect),
                 DrawCommand::DrawSlider { rect, .. } => Some(*rect),
                 DrawCommand::DrawProgress { rect, .. } => Some(*rect),
                 DrawCommand::DrawCanvas { rect, .. } => Some(*rect),
+                DrawCommand::DrawShadow { rect, blur, .. } => Some(rect.inflate(xerune::graphics::shadow_margin(*blur))),
+                DrawCommand::BackdropBlur { rect, .. } => Some(*rect),
+                DrawCommand::Clear { .. } => None,
+                DrawCommand::BeginLayer { .. } | DrawCommand::EndLayer => None,
             };
 
-            let needs_mask = if match command { DrawCommand::Clip {..} | DrawCommand::PopClip => true, _ => false } {
+            let needs_mask = if match command { DrawCommand::Clip {..} | DrawCommand::PopClip | DrawCommand::PushTransform {..} | DrawCommand::PopTransform | DrawCommand::BeginLayer {..} | DrawCommand::EndLayer => true, _ => false } {
                 false // Ignore for mask-adjusting commands
-            } else if let Some(r) = item_rect {
+            } else if let Some(r) = item_rect.filter(|_| self.transform_stack.is_empty()) {
                  let strict_rect = tiny_skia::Rect::from_xywh(r.x, r.y, r.width, r.height);
                  if let Some(r) = strict_rect {
                      !self.is_fully_inside_clip(r)
@@ -273,10 +405,10 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
             let mask_to_use = if needs_mask { self.current_mask.as_ref() } else { None };
 
             match command {
-                DrawCommand::Clip { rect } => {
+                DrawCommand::Clip { rect, border_radius } => {
                     profile!("render_clip");
                     if let Some(r) = tiny_skia::Rect::from_xywh(rect.x, rect.y, rect.width, rect.height) {
-                        self.clip_stack.push(r);
+                        self.clip_stack.push((r, *border_radius));
                         self.clip_mask_dirty = true;
                     }
                 }
@@ -285,21 +417,39 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                     self.clip_stack.pop();
                     self.clip_mask_dirty = true;
                 }
-                DrawCommand::DrawText { text, rect, color, font_size, weight } => {
+                DrawCommand::PushTransform { matrix, .. } => {
+                    // Clips are given untransformed, so the mask is built before the transform applies.
+                    if self.clip_mask_dirty {
+                        self.generate_mask();
+                    }
+                    self.transform_stack.push(self.transform);
+                    self.transform = self.transform.pre_concat(Transform::from_row(matrix.a, matrix.b, matrix.c, matrix.d, matrix.e, matrix.f));
+                }
+                DrawCommand::PopTransform => {
+                    if let Some(transform) = self.transform_stack.pop() {
+                        self.transform = transform;
+                    }
+                }
+                DrawCommand::DrawText { text, rect, color, font_size, weight, word_spacing, max_width } => {
                     profile!("render_text");
-                    let font_index = if *weight > 0 && self.fonts.len() > 1 { 1 } else { 0 };
+                    let font_index = xerune::font_index(*weight, self.fonts.len());
+                    let text = match max_width {
+                        Some(max_width) => self.truncate_text(text, *font_size, *weight, *word_spacing, *max_width),
+                        None => std::borrow::Cow::Borrowed(text.as_str()),
+                    };
 
                     {
                         profile!("text_layout");
                         self.layout.reset(&fontdue::layout::LayoutSettings {
                             ..fontdue::layout::LayoutSettings::default()
                         });
-                        self.layout.append(self.fonts, &fontdue::layout::TextStyle::new(text, *font_size, font_index));
+                        xerune::fonts::append_text(&mut self.layout, self.fonts, &text, *font_size, font_index);
                     }
 
                     let color_skia = self.to_skia_color(*color);
 
                     profile!("text_rasterize");
+                    let mut extra_advance = 0.0;
                     for glyph in self.layout.glyphs() {
                         let sub_px = (glyph.key.px * 16.0) as u32; // cache at subpixel alignment or just int
                         let r_u8 = (color_skia.red() * 255.0).round() as u8;
@@ -331,7 +481,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         }
 
                         if let Some(glyph_pixmap) = self.glyph_cache.get(&cache_key) {
-                            let gx = rect.x + glyph.x;
+                            let gx = rect.x + glyph.x + extra_advance;
                             let gy = rect.y + glyph.y;
 
                             self.pixmap.draw_pixmap(
@@ -343,6 +493,10 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                                 mask_to_use,
                             );
                         }
+
+                        if glyph.parent == ' ' {
+                            extra_advance += *word_spacing;
+                        }
                     }
                 }
                 DrawCommand::DrawRect { rect, color, gradient, border_radius, border_width, border_color } => {
@@ -353,11 +507,31 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         let mut paint = tiny_skia::Paint::default();
                         paint.anti_alias = false;
 
-                        if let Some(grad) = gradient {
+                        if let Some(Gradient::Radial(grad)) = gradient {
+                            // Drawn around the unit circle, scaled to the ending shape.
+                            let (rx, ry) = grad.radii(rect.width, rect.height);
+                            let stops = grad.stops.iter().map(|(c, p)| tiny_skia::GradientStop::new(*p, self.to_skia_color(*c))).collect();
+                            if let Some(shader) = tiny_skia::RadialGradient::new(
+                                tiny_skia::Point::zero(),
+                                0.0,
+                                tiny_skia::Point::zero(),
+                                1.0,
+                                stops,
+                                tiny_skia::SpreadMode::Pad,
+                                // The fill applies `self.transform` to the shader as well.
+                                Transform::from_translate(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0)
+                                    .pre_scale(rx.max(f32::EPSILON), ry.max(f32::EPSILON)),
+                            ) {
+                                paint.shader = shader;
+                            }
+                        } else if let Some(Gradient::Linear(grad)) = gradient {
                              // Gradient logic
                              let width_int = rect.width.max(1.0) as u32;
                              let height_int = rect.height.max(1.0) as u32;
-                             let cache_key = format!("grad_{}_{}_{}", grad.angle, width_int, height_int);
+                             let mut cache_key = format!("grad_{}_{}_{}", grad.angle, width_int, height_int);
+                             for (c, p) in grad.stops.iter() {
+                                 cache_key.push_str(&format!("_{:02x}{:02x}{:02x}{:02x}@{}", c.r, c.g, c.b, c.a, p));
+                             }
                              
                              if !self.gradient_cache.contains_key(&cache_key) {
                                  if let Some(mut grad_pixmap) = Pixmap::new(width_int, height_int) {
@@ -366,15 +540,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                                      }).collect();
                                      
                                      // Create gradient relative to 0,0 for caching
-                                     let gcx = width_int as f32 / 2.0;
-                                     let gcy = height_int as f32 / 2.0;
-                                     let (gsx, gsy, gex, gey) = if (grad.angle - 180.0).abs() < 5.0 {
-                                         (gcx, 0.0, gcx, height_int as f32)
-                                     } else if (grad.angle - 90.0).abs() < 5.0 {
-                                         (0.0, gcy, width_int as f32, gcy)
-                                     } else {
-                                         (gcx, 0.0, gcx, height_int as f32)
-                                     };
+                                     let ((gsx, gsy), (gex, gey)) = grad.line(width_int as f32, height_int as f32);
 
                                      if let Some(shader) = tiny_skia::LinearGradient::new(
                                          tiny_skia::Point::from_xy(gsx, gsy),
@@ -421,7 +587,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                                  let mut m = mask_to_use;
                                  let mut should_draw = true;
                                  
-                                 if mask_to_use.is_some() {
+                                 if mask_to_use.is_some() && !self.has_rounded_clip() && self.transform_stack.is_empty() {
                                      // Hardware bypass for axis-aligned shapes: clamp them directly!
                                      if let Some(clip) = self.get_clip_rect() {
                                          if let Some(intersected) = clamped_r.intersect(&clip) {
@@ -462,29 +628,36 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         }
                     }
                 }
-                DrawCommand::DrawImage { src, rect, border_radius } => {
+                DrawCommand::DrawImage { src, rect, border_radius, src_rect, tint, opacity, size, position } => {
                     profile!("render_image");
-                    if !self.image_cache.contains_key(src) {
-                        if let Ok(data) = std::fs::read(src) {
-                            if let Ok(png_pixmap) = Pixmap::decode_png(&data) {
-                                self.image_cache.insert(src.clone(), png_pixmap);
-                            } else {
-                                log::warn!("Failed to decode PNG: {}", src);
-                            }
-                        } else {
-                            log::warn!("Failed to read image file: {}", src);
-                        }
+                    self.image_cache.load(src, || xerune::decode::load_image(src).inspect_err(|e| log::warn!("Failed to load image {}: {}", src, e)).ok());
+
+                    // Tinted icons are cached once per color.
+                    let tint_key = tint.map(|tint| [tint.r, tint.g, tint.b, tint.a]);
+                    if let (Some(key), Some(tint)) = (tint_key, tint) {
+                        let color = self.to_skia_color(*tint);
+                        self.image_cache.tint(src, key, |image| tint_pixmap(image, color));
                     }
+                    let image = self.image_cache.get(src, tint_key);
 
-                     if let Some(png_pixmap) = self.image_cache.get(src) {
-                         let sx = rect.width / png_pixmap.width() as f32;
-                         let sy = rect.height / png_pixmap.height() as f32;
-                         let transform = self.transform.pre_scale(sx, sy).pre_translate(rect.x / sx, rect.y / sy);
+                     if let Some(png_pixmap) = image {
+                         let whole = xerune::Rect::new(0.0, 0.0, png_pixmap.width() as f32, png_pixmap.height() as f32);
+                         let Some((rect, source)) = size.fit(*rect, src_rect.unwrap_or(whole), *position) else {
+                             continue;
+                         };
+                         let rect = &rect;
+                         let sx = rect.width / source.width;
+                         let sy = rect.height / source.height;
+                         let transform = self.transform
+                             .pre_translate(rect.x, rect.y)
+                             .pre_scale(sx, sy)
+                             .pre_translate(-source.x, -source.y);
                              
                              // Proper clipping for rounded corners on image needs a mask or clip_path.
                              // We create a shader from the image and fill the rounded rect path.
                              
-                             if *border_radius > 0.0 {
+                             // A source sub-rect also goes through the pattern path so pixels outside it are not drawn.
+                             if *border_radius > 0.0 || source != whole {
                                  if let Some(r) = tiny_skia::Rect::from_xywh(rect.x, rect.y, rect.width, rect.height) {
                                      if let Some(path) = rounded_rect_path(r, *border_radius) {
                                           let mut paint = tiny_skia::Paint::default();
@@ -497,7 +670,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                                                png_pixmap.as_ref(),
                                                tiny_skia::SpreadMode::Pad, 
                                                tiny_skia::FilterQuality::Bilinear, 
-                                               1.0, 
+                                               *opacity, 
                                                transform // Transform applied to pattern
                                            );
                                            paint.shader = shader;
@@ -508,7 +681,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                                  self.pixmap.draw_pixmap(
                                      0, 0,
                                      png_pixmap.as_ref(), // Use internal identity if pre-transformed
-                                     &PixmapPaint::default(),
+                                     &PixmapPaint { opacity: *opacity, ..PixmapPaint::default() },
                                      transform,
                                      mask_to_use
                                  );
@@ -523,6 +696,41 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         }
                     }
                 }
+                DrawCommand::DrawCaret { color, .. } => {
+                    let Some(caret) = command.caret_rect(&*self) else {
+                        continue;
+                    };
+                    let mut paint = tiny_skia::Paint::default();
+                    paint.anti_alias = false;
+                    paint.set_color(self.to_skia_color(*color));
+                    if let Some(r) = tiny_skia::Rect::from_xywh(caret.x, caret.y, caret.width, caret.height) {
+                        self.pixmap.fill_rect(r, &paint, self.transform, mask_to_use);
+                    }
+                }
+                DrawCommand::DrawTextSelection { color, .. } => {
+                    let Some(selection) = command.selection_rect(&*self) else {
+                        continue;
+                    };
+                    let mut paint = tiny_skia::Paint::default();
+                    paint.anti_alias = false;
+                    paint.set_color(self.to_skia_color(*color));
+                    if let Some(r) = tiny_skia::Rect::from_xywh(selection.x, selection.y, selection.width, selection.height) {
+                        self.pixmap.fill_rect(r, &paint, self.transform, mask_to_use);
+                    }
+                }
+                DrawCommand::DrawScrollbar { rect, thumb_rect, .. } => {
+                    // A faint track under a translucent thumb, readable on light and dark backgrounds.
+                    let mut paint = tiny_skia::Paint::default();
+                    paint.anti_alias = true;
+                    for (r, color) in [(rect, xerune::Color::new(0, 0, 0, 20)), (thumb_rect, xerune::Color::new(0, 0, 0, 100))] {
+                        paint.set_color(self.to_skia_color(color));
+                        if let Some(r) = tiny_skia::Rect::from_xywh(r.x, r.y, r.width, r.height)
+                            && let Some(path) = rounded_rect_path(r, r.width().min(r.height()) / 2.0)
+                        {
+                            self.pixmap.fill_path(&path, &paint, tiny_skia::FillRule::Winding, self.transform, mask_to_use);
+                        }
+                    }
+                }
                 DrawCommand::DrawCheckbox { rect, checked, color } => {
                      profile!("render_checkbox");
                      let mut paint = tiny_skia::Paint::default();
@@ -542,14 +750,23 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                          self.pixmap.stroke_path(&path, &paint, &stroke, self.transform, mask_to_use);
                          
                          if *checked {
-                             let inset = 4.0;
-                             if let Some(inner) = tiny_skia::Rect::from_xywh(rect.x + inset, rect.y + inset, rect.width - inset*2.0, rect.height - inset*2.0) {
-                                  self.pixmap.fill_rect(inner, &paint, self.transform, mask_to_use);
+                             // Two-segment check mark: down to the bottom-left elbow, then up to the top-right.
+                             let mut pb = tiny_skia::PathBuilder::new();
+                             pb.move_to(rect.x + rect.width * 0.22, rect.y + rect.height * 0.52);
+                             pb.line_to(rect.x + rect.width * 0.42, rect.y + rect.height * 0.72);
+                             pb.line_to(rect.x + rect.width * 0.78, rect.y + rect.height * 0.30);
+                             if let Some(check) = pb.finish() {
+                                 let mut check_stroke = tiny_skia::Stroke::default();
+                                 check_stroke.width = (rect.width.min(rect.height) / 8.0).max(2.0);
+                                 check_stroke.line_cap = tiny_skia::LineCap::Round;
+                                 check_stroke.line_join = tiny_skia::LineJoin::Round;
+                                 paint.anti_alias = true;
+                                 self.pixmap.stroke_path(&check, &paint, &check_stroke, self.transform, mask_to_use);
                              }
                          }
                      }
                 }
-                DrawCommand::DrawSlider { rect, value, color } => {
+                DrawCommand::DrawSlider { rect, value, color, .. } => {
                     profile!("render_slider");
                     let mut paint = tiny_skia::Paint::default();
                     paint.anti_alias = false;
@@ -608,7 +825,7 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         self.pixmap.stroke_path(&p, &stroke_paint, &stroke, self.transform, mask_to_use);
                       }
                 }
-                DrawCommand::DrawProgress { rect, value, max, color } => {
+                DrawCommand::DrawProgress { rect, value, max, color, .. } => {
                     profile!("render_progress");
                     let mut paint = tiny_skia::Paint::default();
                     paint.anti_alias = false;
@@ -629,8 +846,8 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                              self.pixmap.fill_rect(track_rect, &bg_paint, self.transform, mask_to_use);
                         }
 
-                        // Filled bar
-                        let progress = (value / max).clamp(0.0, 1.0);
+                        // Filled bar; an empty range shows an empty track.
+                        let progress = if *max > 0.0 { (value / max).clamp(0.0, 1.0) } else { 0.0 };
                         if progress > 0.0 {
                             if let Some(active_rect) = tiny_skia::Rect::from_xywh(rect.x, rect.y, rect.width * progress, track_height) {
                                 if let Some(path) = rounded_rect_path(active_rect, track_height / 2.0) {
@@ -646,11 +863,15 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
         
                 DrawCommand::DrawCanvas { id, rect } => {
                     profile!("render_canvas");
-                    if let Some(canvas) = canvases.get(id) {
+                    if let Some(canvas) = canvases.get(id)
+                        && rect.width > 0.0
+                        && rect.height > 0.0
+                    {
                         if let Some(canvas_pixmap) = PixmapRef::from_bytes(&canvas.data, canvas.width, canvas.height) {
+                            // Stretched to `rect` when the canvas is a different size.
                             let sx = rect.width / canvas.width as f32;
                             let sy = rect.height / canvas.height as f32;
-                            let transform = self.transform.pre_scale(sx, sy).pre_translate(rect.x / sx, rect.y / sy);
+                            let transform = self.transform.pre_translate(rect.x, rect.y).pre_scale(sx, sy);
 
                             self.pixmap.draw_pixmap(
                                 0, 0,
@@ -662,14 +883,195 @@ impl<'a> Renderer for TinySkiaRenderer<'a> {
                         }
                     }
                 }
+                DrawCommand::DrawShadow { rect, border_radius, blur, color } => {
+                    profile!("render_shadow");
+                    // The blurred shape is cached like gradients, keyed by everything that shapes it.
+                    let margin = xerune::graphics::shadow_margin(*blur);
+                    let cache_key = format!("shadow_{}_{}_{}_{}_{:?}_{}", rect.width, rect.height, border_radius, blur, color, self.swap_rb);
+                    if !self.gradient_cache.contains_key(&cache_key) {
+                        let shape = tiny_skia::Rect::from_xywh(margin, margin, rect.width, rect.height)
+                            .and_then(|r| rounded_rect_path(r, *border_radius));
+                        let pixmap = Pixmap::new((rect.width + margin * 2.0).ceil() as u32, (rect.height + margin * 2.0).ceil() as u32);
+                        if let (Some(path), Some(mut pixmap)) = (shape, pixmap) {
+                            let mut paint = tiny_skia::Paint::default();
+                            paint.anti_alias = true;
+                            paint.set_color(self.to_skia_color(*color));
+                            pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+                            blur_pixmap(&mut pixmap, blur / 2.0);
+                            self.gradient_cache.insert(cache_key.clone(), pixmap);
+                        }
+                    }
+                    if let Some(shadow) = self.gradient_cache.get(&cache_key) {
+                        self.pixmap.draw_pixmap(
+                            0,
+                            0,
+                            shadow.as_ref(),
+                            &PixmapPaint::default(),
+                            self.transform.pre_translate(rect.x - margin, rect.y - margin),
+                            mask_to_use,
+                        );
+                    }
+                }
+                DrawCommand::BackdropBlur { rect, radius } => {
+                    profile!("render_backdrop_blur");
+                    // Blur in device space over the part of `rect` inside the clip.
+                    let region = tiny_skia::Rect::from_xywh(rect.x, rect.y, rect.width, rect.height)
+                        .and_then(|r| match self.get_clip_rect() {
+                            Some(clip) => r.intersect(&clip),
+                            None => Some(r),
+                        })
+                        .and_then(|r| r.transform(self.transform));
+                    if let Some(region) = region {
+                        let (pw, ph) = (self.pixmap.width() as i32, self.pixmap.height() as i32);
+                        let x0 = (region.left().floor() as i32).clamp(0, pw) as usize;
+                        let y0 = (region.top().floor() as i32).clamp(0, ph) as usize;
+                        let x1 = (region.right().ceil() as i32).clamp(0, pw) as usize;
+                        let y1 = (region.bottom().ceil() as i32).clamp(0, ph) as usize;
+                        let (w, h, stride) = (x1.saturating_sub(x0), y1.saturating_sub(y0), pw as usize);
+
+                        let data = self.pixmap.data_mut();
+                        let mut pixels = Vec::with_capacity(w * h);
+                        for y in y0..y1 {
+                            let row = &data[(y * stride + x0) * 4..(y * stride + x1) * 4];
+                            pixels.extend(row.chunks_exact(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])));
+                        }
+                        xerune::box_blur(&mut pixels, w, h, *radius);
+                        for (y, src) in (y0..y1).zip(pixels.chunks_exact(w.max(1))) {
+                            let row = &mut data[(y * stride + x0) * 4..(y * stride + x1) * 4];
+                            for (dst, p) in row.chunks_exact_mut(4).zip(src) {
+                                dst.copy_from_slice(&p.to_ne_bytes());
+                            }
+                        }
+                    }
+                }
+                DrawCommand::Clear { color } => {
+                    profile!("render_clear");
+                    // Filling the intersection of axis-aligned clips needs no mask, rounded ones do.
+                    match self.get_clip_rect() {
+                        Some(clip) => {
+                            let mut paint = tiny_skia::Paint::default();
+                            paint.anti_alias = false;
+                            paint.blend_mode = tiny_skia::BlendMode::Source;
+                            paint.set_color(self.to_skia_color(*color));
+                            let mask = if self.has_rounded_clip() { mask_to_use } else { None };
+                            self.pixmap.fill_rect(clip, &paint, self.transform, mask);
+                        }
+                        None => self.pixmap.fill(self.to_skia_color(*color)),
+                    }
+                }
+                // Reached only when the layer is drawn inline.
+                DrawCommand::BeginLayer { .. } | DrawCommand::EndLayer => {}
             }
         }
 
-        if dirty_rect.is_some() {
-            self.clip_stack.pop();
-            self.update_clip_mask();
+        // Layers that no longer exist drop their pixmaps.
+        if let Some(cache) = self.layer_cache.as_deref_mut() {
+            cache.layers.truncate(layer_index);
+        }
+    }
+
+    /// Draws the layer at `rect` from the cache, rasterizing it first if its
+    /// commands changed. Returns false if the layer must be drawn inline: there
+    /// is no cache, or it holds a canvas or backdrop blur, which can change
+    /// without its commands changing.
+    fn draw_layer(&mut self, index: usize, rect: xerune::Rect, inner: &[&DrawCommand], canvases: &HashMap<String, Canvas>) -> bool {
+        profile!("render_layer");
+        if inner.iter().any(|c| matches!(c, DrawCommand::DrawCanvas { .. } | DrawCommand::BackdropBlur { .. })) {
+            return false;
+        }
+        let Some(cache) = self.layer_cache.take() else {
+            return false;
+        };
+
+        let commands: Vec<DrawCommand> = inner.iter().map(|c| c.translated(-rect.x, -rect.y)).collect();
+        if cache.layers.len() <= index {
+            cache.layers.resize_with(index + 1, || None);
+        }
+        let cached = matches!(&cache.layers[index], Some(layer) if layer.commands == commands && layer.swap_rb == self.swap_rb);
+        if !cached {
+            cache.layers[index] = self.rasterize_layer(commands, canvases);
+        }
+
+        if let Some(layer) = &cache.layers[index] {
+            let (ox, oy) = layer.origin;
+            let bounds = tiny_skia::Rect::from_xywh(rect.x + ox, rect.y + oy, layer.pixmap.width() as f32, layer.pixmap.height() as f32);
+            let needs_mask = bounds.is_none_or(|b| !self.is_fully_inside_clip(b));
+            if needs_mask && self.clip_mask_dirty {
+                self.generate_mask();
+            }
+            self.pixmap.draw_pixmap(
+                0,
+                0,
+                layer.pixmap.as_ref(),
+                &PixmapPaint::default(),
+                self.transform.pre_translate(rect.x + ox, rect.y + oy),
+                if needs_mask { self.current_mask.as_ref() } else { None },
+            );
+        }
+        self.layer_cache = Some(cache);
+        true
+    }
+
+    /// Draws `commands`, relative to their layer's box, into a pixmap just
+    /// large enough to hold them.
+    fn rasterize_layer(&mut self, commands: Vec<DrawCommand>, canvases: &HashMap<String, Canvas>) -> Option<CachedLayer> {
+        let mut min = (0.0f32, 0.0f32);
+        let mut max = (0.0f32, 0.0f32);
+        for b in commands.iter().filter_map(|c| c.bounds()) {
+            min = (min.0.min(b.x), min.1.min(b.y));
+            max = (max.0.max(b.x + b.width), max.1.max(b.y + b.height));
         }
+        let origin = (min.0.floor(), min.1.floor());
+        let mut pixmap = Pixmap::new((max.0 - origin.0).ceil() as u32, (max.1 - origin.1).ceil() as u32)?;
+
+        {
+            let mut renderer = TinySkiaRenderer::new(pixmap.as_mut(), self.fonts, &mut *self.image_cache, &mut *self.gradient_cache, &mut *self.glyph_cache);
+            renderer.swap_rb = self.swap_rb;
+            renderer.transform = Transform::from_translate(-origin.0, -origin.1);
+            let refs: Vec<&DrawCommand> = commands.iter().collect();
+            renderer.render_commands(&refs, canvases, None);
+        }
+
+        Some(CachedLayer { commands, swap_rb: self.swap_rb, origin, pixmap })
+    }
+}
+
+/// Index of the `EndLayer` closing the layer whose contents start at `start`,
+/// or the end of `commands` if it is missing.
+fn matching_end_layer(commands: &[&DrawCommand], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, command) in commands.iter().enumerate().skip(start) {
+        match command {
+            DrawCommand::BeginLayer { .. } => depth += 1,
+            DrawCommand::EndLayer if depth == 0 => return i,
+            DrawCommand::EndLayer => depth -= 1,
+            _ => {}
+        }
+    }
+    commands.len()
+}
+
+fn blur_pixmap(pixmap: &mut Pixmap, radius: f32) {
+    let (w, h) = (pixmap.width() as usize, pixmap.height() as usize);
+    let mut pixels: Vec<u32> = pixmap.data().chunks_exact(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect();
+    xerune::box_blur(&mut pixels, w, h, radius);
+    for (dst, p) in pixmap.data_mut().chunks_exact_mut(4).zip(pixels) {
+        dst.copy_from_slice(&p.to_ne_bytes());
+    }
+}
+
+/// Replaces every pixel's color with `color`, keeping only the image's alpha.
+fn tint_pixmap(image: &Pixmap, color: tiny_skia::Color) -> Pixmap {
+    let mut tinted = image.clone();
+    let c = color.premultiply().to_color_u8();
+    for px in tinted.data_mut().chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        px[0] = (c.red() as u32 * a / 255) as u8;
+        px[1] = (c.green() as u32 * a / 255) as u8;
+        px[2] = (c.blue() as u32 * a / 255) as u8;
+        px[3] = (c.alpha() as u32 * a / 255) as u8;
     }
+    tinted
 }
 
 fn rounded_rect_path(rect: tiny_skia::Rect, radius: f32) -> Option<tiny_skia::Path> {
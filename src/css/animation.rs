@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use crate::style::{ContainerStyle, AnimationIterationCount};
+use crate::style::{ContainerStyle, AnimationIterationCount, Transition};
 
 #[derive(Clone, Debug)]
 pub struct Keyframe {
@@ -75,6 +75,41 @@ pub fn parse_animation_shorthand(val: &str, current_style: &mut ContainerStyle)
     }
 }
 
+/// Parses `transition`: comma-separated entries of a property, a duration, an
+/// optional timing function and an optional delay. `none` clears them.
+pub fn parse_transition(val: &str) -> Vec<Transition> {
+    if val.trim() == "none" {
+        return Vec::new();
+    }
+    super::parser::split_top_level(val, ',').into_iter()
+        .map(|entry| {
+            let mut transition = Transition {
+                property: std::sync::Arc::from("all"),
+                duration: 0.0,
+                timing_function: std::sync::Arc::from("ease"),
+                delay: 0.0,
+            };
+            let mut durations_found = 0;
+            for part in entry.split_whitespace() {
+                let part_lower = part.to_lowercase();
+                if part_lower.ends_with("s") && part_lower.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                    if durations_found == 0 {
+                        transition.duration = parse_duration_sec(part);
+                    } else {
+                        transition.delay = parse_duration_sec(part);
+                    }
+                    durations_found += 1;
+                } else if ["linear", "ease", "ease-in", "ease-out", "ease-in-out"].contains(&part_lower.as_str()) || part_lower.starts_with("cubic-bezier(") {
+                    transition.timing_function = std::sync::Arc::from(part_lower);
+                } else {
+                    transition.property = std::sync::Arc::from(part_lower);
+                }
+            }
+            transition
+        })
+        .collect()
+}
+
 pub fn strip_css_comments(css: &str) -> String {
     let mut result = String::new();
     let mut chars = css.chars().peekable();
@@ -1,11 +1,11 @@
 pub mod parser;
 pub mod animation;
 
-pub use parser::{parse_hex_color, parse_px, parse_dimension, parse_length_percentage, parse_length_percentage_auto};
-use parser::{parse_padding, parse_margin};
-pub use animation::{Keyframe, KeyframesAnimation, parse_keyframes, parse_duration_sec, parse_animation_shorthand};
+pub use parser::{parse_aspect_ratio, parse_hex_color, parse_line_height, parse_px, resolve_font_relative, parse_dimension, parse_length_percentage, parse_length_percentage_auto};
+use parser::{parse_padding, parse_margin, parse_background_size, parse_url, parse_xywh, parse_transform, parse_transform_origin, parse_align_items, parse_box_shadow};
+pub use animation::{Keyframe, KeyframesAnimation, parse_keyframes, parse_duration_sec, parse_animation_shorthand, parse_transition};
 
-use crate::{Color, ContainerStyle, Display, TextAlign, Direction, WritingMode, FlexDirection, FlexWrap, AlignContent, AlignItems, MyJustifyContent, BoxSizing, style::AnimationIterationCount};
+use crate::{Color, ContainerStyle, Gradient, Display, TextAlign, Direction, WritingMode, FlexDirection, FlexWrap, AlignContent, MyJustifyContent, BoxSizing, style::AnimationIterationCount, style::BorderStyle};
 use taffy::prelude::*;
 use taffy::style::Style;
 
@@ -87,6 +87,20 @@ fn parse_layout_style_fast(s: &str) -> Option<FastLayoutStyle> {
     })
 }
 
+/// Orders rules so that applying them in turn lets the more specific selector
+/// win (`#id` over `.class` over tag), and the later rule among equals.
+pub fn sort_by_specificity(stylesheet: &mut simplecss::StyleSheet<'_>) {
+    stylesheet.rules.sort_by_key(|rule| rule.selector.specificity());
+}
+
+/// The id of the first family in the `font-family` list `names` that
+/// `measurer` has fonts for, else 0 for the default family.
+pub fn pick_font_family(names: &str, measurer: &impl crate::graphics::TextMeasurer) -> u16 {
+    names.split(',')
+        .find_map(|family| measurer.font_family_id(family.trim().trim_matches(|c| c == '"' || c == '\'')))
+        .unwrap_or(0)
+}
+
 pub fn parse_inline_style(style_str: &str, current_style: &mut ContainerStyle, taffy_style: &mut Style) {
     if style_str.starts_with("left:") {
         if let Some(parsed) = parse_layout_style_fast(style_str) {
@@ -94,6 +108,7 @@ pub fn parse_inline_style(style_str: &str, current_style: &mut ContainerStyle, t
             taffy_style.inset.top = LengthPercentageAuto::length(parsed.top);
             taffy_style.size.width = Dimension::length(parsed.width);
             taffy_style.size.height = Dimension::length(parsed.height);
+            current_style.sized = (true, true);
             current_style.background_color = Some(parsed.bg_color);
             return;
         }
@@ -120,32 +135,85 @@ pub fn parse_inline_style(style_str: &str, current_style: &mut ContainerStyle, t
     }
 }
 
+/// Maps a `border-<side>*` property to its index in `ContainerStyle::border_side_*`.
+fn border_side_index(prop: &str) -> usize {
+    if prop.starts_with("border-top") {
+        0
+    } else if prop.starts_with("border-right") {
+        1
+    } else if prop.starts_with("border-bottom") {
+        2
+    } else {
+        3
+    }
+}
+
+/// `border-style` keywords. The `border` shorthands check `none` first, where
+/// it means a zero width instead.
+fn parse_border_style(val: &str) -> Option<BorderStyle> {
+    match val {
+        "solid" | "double" | "groove" | "ridge" | "inset" | "outset" => Some(BorderStyle::Solid),
+        "dashed" => Some(BorderStyle::Dashed),
+        "dotted" => Some(BorderStyle::Dotted),
+        "none" | "hidden" => Some(BorderStyle::None),
+        _ => None,
+    }
+}
+
+/// Parses a color value. `currentColor` is the element's `color` as set so
+/// far: the inherited one, unless a `color` declaration came before.
+fn parse_color_value(val: &str, current_style: &ContainerStyle) -> Option<Color> {
+    if val.trim().eq_ignore_ascii_case("currentcolor") {
+        Some(current_style.color)
+    } else {
+        parse_hex_color(val)
+    }
+}
+
 pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerStyle, taffy_style: &mut Style) {
+    // `em` in `font-size` itself is the parent's size, not one set by a tag
+    // default or an earlier rule, so matching `2em` rules don't compound.
+    let em = if prop == "font-size" { current_style.inherited_font_size } else { current_style.font_size };
+    let val = &*resolve_font_relative(val, em, current_style.root_font_size);
     match prop {
         "display" => {
+            // Everything but grid is laid out as (emulated) flexbox.
+            taffy_style.display = if val == "grid" { taffy::style::Display::Grid } else { taffy::style::Display::Flex };
             match val {
                 "block" => current_style.display = Display::Block,
                 "inline-block" | "inline" => current_style.display = Display::InlineBlock,
                 "flex" => current_style.display = Display::Flex,
+                "grid" => current_style.display = Display::Grid,
                 "none" => current_style.display = Display::None,
                 _ => {}
             }
         }
+        "grid-template-columns" => {
+            if let Some(tracks) = parser::parse_grid_tracks(val) {
+                taffy_style.grid_template_columns = tracks.into_iter().map(Into::into).collect();
+            }
+        }
+        "grid-template-rows" => {
+            if let Some(tracks) = parser::parse_grid_tracks(val) {
+                taffy_style.grid_template_rows = tracks.into_iter().map(Into::into).collect();
+            }
+        }
         "text-align" => {
             match val {
                 "left" => current_style.text_align = Some(TextAlign::Left),
                 "center" => current_style.text_align = Some(TextAlign::Center),
                 "right" => current_style.text_align = Some(TextAlign::Right),
+                "justify" => current_style.text_align = Some(TextAlign::Justify),
                 _ => {}
             }
         }
         "color" => {
-            if let Some(c) = parse_hex_color(val) {
+            if let Some(c) = parse_color_value(val, current_style) {
                 current_style.color = c;
             }
         }
         "background-color" => {
-            if let Some(c) = parse_hex_color(val) {
+            if let Some(c) = parse_color_value(val, current_style) {
                 current_style.background_color = Some(c);
                 current_style.background_gradient = None;
             }
@@ -153,10 +221,15 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
          "background" => {
              if val.contains("linear-gradient") {
                  if let Some(grad) = parser::parse_linear_gradient(val) {
-                     current_style.background_gradient = Some(grad);
+                     current_style.background_gradient = Some(Gradient::Linear(grad));
+                     current_style.background_color = None;
+                 }
+             } else if val.contains("radial-gradient") {
+                 if let Some(grad) = parser::parse_radial_gradient(val) {
+                     current_style.background_gradient = Some(Gradient::Radial(grad));
                      current_style.background_color = None;
                  }
-             } else if let Some(c) = parse_hex_color(val) {
+             } else if let Some(c) = parse_color_value(val, current_style) {
                 current_style.background_color = Some(c);
                  current_style.background_gradient = None;
             }
@@ -173,17 +246,49 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                 current_style.weight = 0;
             }
         }
+        "font-style" => {
+            current_style.font_style = u16::from(matches!(val.trim(), "italic" | "oblique"));
+        }
+        "text-decoration" | "text-decoration-line" => {
+            current_style.underline = val.split_whitespace().any(|part| part == "underline");
+        }
+        "cursor" => {
+            current_style.cursor = match val.trim() {
+                "pointer" => crate::style::Cursor::Pointer,
+                "text" => crate::style::Cursor::Text,
+                "default" | "auto" => crate::style::Cursor::Default,
+                _ => current_style.cursor,
+            };
+        }
+        "font-family" => current_style.font_family_names = Some(std::sync::Arc::from(val)),
+        "line-height" => {
+            if let Some(line_height) = parse_line_height(val.trim(), current_style.font_size) {
+                current_style.line_height = line_height;
+            }
+        }
         "font" => {
             let parts: Vec<&str> = val.split_whitespace().collect();
             for part in parts {
                 let subparts: Vec<&str> = part.split('/').collect();
                 if let Some(size) = parse_px(subparts[0]) {
                     current_style.font_size = size;
+                    if let Some(line_height) = subparts.get(1).and_then(|v| parse_line_height(v, size)) {
+                        current_style.line_height = line_height;
+                    }
                 } else if subparts[0] == "bold" {
                     current_style.weight = 1;
+                } else if subparts[0] == "italic" {
+                    current_style.font_style = 1;
                 }
             }
         }
+        "word-spacing" => {
+            if val == "normal" {
+                current_style.word_spacing = 0.0;
+            } else if let Some(s) = parse_px(val) {
+                current_style.word_spacing = s;
+            }
+        }
         "border-radius" => {
             if let Some(r) = parse_px(val) {
                 current_style.border_radius = r;
@@ -196,23 +301,94 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
         "border-width" => {
             if let Some(w) = parse_px(val) {
                 current_style.border_width = w;
+                current_style.border_side_widths = [None; 4];
             }
         }
         "border-color" => {
-            if let Some(c) = parse_hex_color(val) {
+            if let Some(c) = parse_color_value(val, current_style) {
                 current_style.border_color = Some(c);
+                current_style.border_side_colors = [None; 4];
+            }
+        }
+        "fill" => {
+            current_style.fill = if val.trim().eq_ignore_ascii_case("currentcolor") {
+                None
+            } else {
+                parse_hex_color(val)
+            };
+        }
+        "border-style" => {
+            if let Some(style) = parse_border_style(val.trim()) {
+                current_style.border_style = style;
             }
         }
         "border" => {
-            let parts: Vec<&str> = val.split_whitespace().collect();
-            for part in parts {
+            current_style.border_side_widths = [None; 4];
+            current_style.border_side_colors = [None; 4];
+            for part in parser::split_top_level(val, ' ') {
                 if let Some(w) = parse_px(part) {
                     current_style.border_width = w;
-                } else if let Some(c) = parse_hex_color(part) {
+                } else if part == "none" {
+                    current_style.border_width = 0.0;
+                } else if let Some(style) = parse_border_style(part) {
+                    current_style.border_style = style;
+                } else if let Some(c) = parse_color_value(part, current_style) {
                      current_style.border_color = Some(c);
                 }
             }
         }
+        "outline" => {
+            // Any style but `none` draws solid.
+            for part in parser::split_top_level(val, ' ') {
+                if let Some(w) = parse_px(part) {
+                    current_style.outline_width = w;
+                } else if let Some(style) = parse_border_style(part) {
+                    if style == BorderStyle::None {
+                        current_style.outline_width = 0.0;
+                    }
+                } else if part.eq_ignore_ascii_case("currentcolor") {
+                    current_style.outline_color = None;
+                } else if let Some(c) = parse_hex_color(part) {
+                    current_style.outline_color = Some(c);
+                }
+            }
+        }
+        "outline-width" => {
+            if let Some(w) = parse_px(val) {
+                current_style.outline_width = w;
+            }
+        }
+        "outline-color" => {
+            current_style.outline_color = if val.trim().eq_ignore_ascii_case("currentcolor") {
+                None
+            } else {
+                parse_hex_color(val)
+            };
+        }
+        "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            let side = border_side_index(prop);
+            for part in parser::split_top_level(val, ' ') {
+                if let Some(w) = parse_px(part) {
+                    current_style.border_side_widths[side] = Some(w);
+                } else if part == "none" {
+                    current_style.border_side_widths[side] = Some(0.0);
+                } else if let Some(style) = parse_border_style(part) {
+                    current_style.border_style = style;
+                } else if let Some(c) = parse_color_value(part, current_style) {
+                    current_style.border_side_colors[side] = Some(c);
+                }
+            }
+        }
+        "border-top-width" | "border-right-width" | "border-bottom-width" | "border-left-width" => {
+            if let Some(w) = parse_px(val) {
+                current_style.border_side_widths[border_side_index(prop)] = Some(w);
+            }
+        }
+        "border-top-color" | "border-right-color" | "border-bottom-color" | "border-left-color" => {
+            if let Some(c) = parse_color_value(val, current_style) {
+                current_style.border_side_colors[border_side_index(prop)] = Some(c);
+            }
+        }
         "padding" => {
             if let Some(p) = parse_padding(val) {
                 taffy_style.padding = p;
@@ -308,6 +484,7 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
         "width" => {
             if let Some(d) = parse_dimension(val) {
                 taffy_style.size.width = d;
+                current_style.sized.0 = true;
             }
             if let Some(w) = parse_px(val) {
                 current_style.width = Some(w);
@@ -316,6 +493,7 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
         "height" => {
             if let Some(d) = parse_dimension(val) {
                 taffy_style.size.height = d;
+                current_style.sized.1 = true;
             }
             if let Some(w) = parse_px(val) {
                 current_style.height = Some(w);
@@ -341,6 +519,9 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                 taffy_style.max_size.height = d;
             }
         }
+        "aspect-ratio" => {
+            taffy_style.aspect_ratio = parse_aspect_ratio(val);
+        }
         "inline-size" => {
             if let Some(d) = parse_dimension(val) {
                 current_style.inline_size = Some(d);
@@ -449,31 +630,43 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
             }
         }
          "align-items" => {
-               match val {
-                  "flex-start" => taffy_style.align_items = Some(AlignItems::FlexStart),
-                  "flex-end" => taffy_style.align_items = Some(AlignItems::FlexEnd),
-                  "center" => taffy_style.align_items = Some(AlignItems::Center),
-                  "baseline" => taffy_style.align_items = Some(AlignItems::Baseline),
-                  "stretch" => taffy_style.align_items = Some(AlignItems::Stretch),
-                  "start" | "left" => taffy_style.align_items = Some(AlignItems::Start),
-                  "end" | "right" => taffy_style.align_items = Some(AlignItems::End),
-                  _ => {}
+              if let Some(align) = parse_align_items(val) {
+                  taffy_style.align_items = Some(align);
               }
               current_style.align_items = taffy_style.align_items;
           }
           "align-self" => {
-               match val {
-                  "flex-start" => taffy_style.align_self = Some(AlignSelf::FlexStart),
-                  "flex-end" => taffy_style.align_self = Some(AlignSelf::FlexEnd),
-                  "center" => taffy_style.align_self = Some(AlignSelf::Center),
-                  "baseline" => taffy_style.align_self = Some(AlignSelf::Baseline),
-                  "stretch" => taffy_style.align_self = Some(AlignSelf::Stretch),
-                  "start" | "left" => taffy_style.align_self = Some(AlignSelf::Start),
-                  "end" | "right" => taffy_style.align_self = Some(AlignSelf::End),
-                  _ => {}
+              if val == "auto" {
+                  taffy_style.align_self = None;
+              } else if let Some(align) = parse_align_items(val) {
+                  taffy_style.align_self = Some(align);
               }
               current_style.align_self = taffy_style.align_self;
           }
+          "justify-items" => {
+              if matches!(val, "normal" | "legacy") {
+                  taffy_style.justify_items = None;
+              } else if let Some(justify) = parse_align_items(val) {
+                  taffy_style.justify_items = Some(justify);
+              }
+          }
+          "justify-self" => {
+              if matches!(val, "auto" | "normal") {
+                  taffy_style.justify_self = None;
+              } else if let Some(justify) = parse_align_items(val) {
+                  taffy_style.justify_self = Some(justify);
+              }
+          }
+          "place-items" | "place-self" | "place-content" => {
+              // `<align> <justify>?`: a single value sets both axes.
+              let mut parts = val.split_whitespace();
+              if let Some(align) = parts.next() {
+                  let justify = parts.next().unwrap_or(align);
+                  let axis = prop.trim_start_matches("place-");
+                  apply_declaration(&format!("align-{axis}"), align, current_style, taffy_style);
+                  apply_declaration(&format!("justify-{axis}"), justify, current_style, taffy_style);
+              }
+          }
           "align-content" => {
                match val {
                   "flex-start" => taffy_style.align_content = Some(AlignContent::FlexStart),
@@ -536,6 +729,9 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                           } else if let Some(d) = parse_dimension(parts[1]) {
                               taffy_style.flex_shrink = 1.0;
                               taffy_style.flex_basis = d;
+                          } else if parts[1] == "auto" {
+                              taffy_style.flex_shrink = 1.0;
+                              taffy_style.flex_basis = taffy::style::Dimension::auto();
                           }
                       }
                   }
@@ -558,12 +754,26 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
          "overflow" => {
              match val {
                  "hidden" => current_style.overflow = crate::Overflow::Hidden,
+                 "clip" => current_style.overflow = crate::Overflow::Clip,
                  "scroll" => current_style.overflow = crate::Overflow::Scroll,
                  "auto" => current_style.overflow = crate::Overflow::Scroll,
                  "visible" => current_style.overflow = crate::Overflow::Visible,
                  _ => {}
              }
          }
+         "visibility" => {
+             current_style.hidden = matches!(val.trim(), "hidden" | "collapse");
+         }
+         "text-overflow" => {
+             current_style.text_overflow = val.trim() == "ellipsis";
+         }
+         "scrollbar-width" => {
+             current_style.hide_scrollbar = val == "none";
+         }
+         "z-index" => {
+             // `auto` keeps document order, like 0.
+             current_style.z_index = val.trim().parse::<i32>().unwrap_or(0);
+         }
          "order" => {
              if let Ok(v) = val.trim().parse::<i32>() {
                  current_style.order = v;
@@ -606,6 +816,11 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                  taffy_style.inset.bottom = v;
              }
          }
+         "inset" => {
+             if let Some(v) = parse_margin(val) {
+                 taffy_style.inset = v;
+             }
+         }
          "float" => {
              if val == "left" || val == "right" {
                  current_style.is_floated = true;
@@ -621,17 +836,18 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                  _ => {}
              }
          }
-         "row-gap" => {
+         "row-gap" | "grid-row-gap" => {
              if let Some(lp) = parse_length_percentage(val) {
                  taffy_style.gap.height = lp;
              }
          }
-         "column-gap" => {
+         "column-gap" | "grid-column-gap" => {
              if let Some(lp) = parse_length_percentage(val) {
                  taffy_style.gap.width = lp;
              }
          }
-         "gap" => {
+         // `grid-*-gap` are the legacy names, still common in older stylesheets.
+         "gap" | "grid-gap" => {
              let parts: Vec<&str> = val.split_whitespace().collect();
              match parts.len() {
                  1 => {
@@ -651,6 +867,84 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
                  _ => {}
              }
          }
+         "content" => {
+             // Consumed when `::before`/`::after` elements are inserted; nothing to apply here.
+         }
+         "backdrop-filter" | "-webkit-backdrop-filter" => {
+             // Only `blur(<length>)` is supported; anything else, e.g. `none`, turns it off.
+             current_style.backdrop_blur = val.trim()
+                 .strip_prefix("blur(")
+                 .and_then(|v| v.strip_suffix(')'))
+                 .and_then(|v| parse_px(v.trim()))
+                 .unwrap_or(0.0)
+                 .max(0.0);
+         }
+         "box-shadow" => current_style.box_shadow = parse_box_shadow(val),
+         "opacity" => {
+             let parsed = match val.trim().strip_suffix('%') {
+                 Some(percent) => percent.trim().parse::<f32>().ok().map(|p| p / 100.0),
+                 None => val.trim().parse::<f32>().ok(),
+             };
+             if let Some(opacity) = parsed {
+                 current_style.opacity = opacity.clamp(0.0, 1.0);
+             }
+         }
+         "will-change" => {
+             // Any hint other than `auto` asks for the subtree to get its own layer.
+             current_style.layer = !val.trim().eq_ignore_ascii_case("auto");
+         }
+         "transform" => {
+             if val == "none" {
+                 current_style.transform = None;
+             } else if let Some(matrix) = parse_transform(val) {
+                 current_style.transform = Some(matrix);
+             }
+         }
+         "transform-origin" => {
+             if let Some(origin) = parse_transform_origin(val) {
+                 current_style.transform_origin = origin;
+             }
+         }
+         "line-clamp" | "-webkit-line-clamp" => {
+             current_style.line_clamp = val.trim().parse::<u32>().ok().filter(|&n| n > 0);
+         }
+         "white-space" => {
+             match val {
+                 "nowrap" => current_style.white_space = crate::style::WhiteSpace::NoWrap,
+                 "pre" => current_style.white_space = crate::style::WhiteSpace::Pre,
+                 "normal" | "pre-wrap" | "pre-line" | "break-spaces" => current_style.white_space = crate::style::WhiteSpace::Normal,
+                 _ => {}
+             }
+         }
+         "list-style-type" | "list-style" => {
+             // The shorthand's position and image parts are ignored.
+             for part in val.split_whitespace() {
+                 current_style.list_style = match part {
+                     "none" => crate::style::ListStyle::None,
+                     "disc" => crate::style::ListStyle::Disc,
+                     "circle" => crate::style::ListStyle::Circle,
+                     "square" => crate::style::ListStyle::Square,
+                     "decimal" => crate::style::ListStyle::Decimal,
+                     _ => continue,
+                 };
+             }
+         }
+         "background-image" => {
+             current_style.background_image = parse_url(val).map(str::to_string);
+         }
+         "background-size" => {
+             if let Some(size) = parse_background_size(val) {
+                 current_style.background_size = size;
+             }
+         }
+         "background-position" => {
+             if let Some(position) = parse_transform_origin(val) {
+                 current_style.background_position = position;
+             }
+         }
+         "object-view-box" => {
+             current_style.object_view_box = parse_xywh(val);
+         }
          "direction" => {
              match val {
                  "rtl" => current_style.direction = Direction::Rtl,
@@ -695,6 +989,9 @@ pub fn apply_declaration(prop: &str, val: &str, current_style: &mut ContainerSty
          "animation" => {
              parse_animation_shorthand(val, current_style);
          }
+         "transition" => {
+             current_style.transitions = parse_transition(val).into();
+         }
          _ => {
              log::warn!("Unsupported CSS property: {}", prop);
          }
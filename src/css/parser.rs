@@ -1,5 +1,6 @@
 use taffy::prelude::*;
-use crate::graphics::{Color, LinearGradient};
+use crate::graphics::{BackgroundSize, Color, LinearGradient, Matrix, RadialGradient};
+use crate::style::{BoxShadow, LineHeight, OriginOffset};
 use csscolorparser::parse as parse_color;
 use std::collections::HashMap;
 
@@ -16,10 +17,10 @@ pub fn parse_hex_color(val: &str) -> Option<Color> {
     let color = parse_color_fast(trimmed).or_else(|| {
         parse_color(trimmed).ok().map(|c| {
             Color::from_rgba8(
-                (c.r * 255.0) as u8,
-                (c.g * 255.0) as u8,
-                (c.b * 255.0) as u8,
-                (c.a * 255.0) as u8,
+                (c.r * 255.0).round() as u8,
+                (c.g * 255.0).round() as u8,
+                (c.b * 255.0).round() as u8,
+                (c.a * 255.0).round() as u8,
             )
         })
     });
@@ -59,7 +60,7 @@ fn parse_color_fast(s: &str) -> Option<Color> {
         let g = parts.next()?.trim().parse::<u8>().ok()?;
         let b = parts.next()?.trim().parse::<u8>().ok()?;
         let a_str = parts.next()?.trim();
-        let a = (a_str.parse::<f32>().ok()? * 255.0) as u8;
+        let a = (a_str.parse::<f32>().ok()? * 255.0).round() as u8;
         return Some(Color::from_rgba8(r, g, b, a));
     } else if s.starts_with("rgb(") && s.ends_with(')') {
         let content = &s[4..s.len() - 1];
@@ -73,12 +74,12 @@ fn parse_color_fast(s: &str) -> Option<Color> {
 }
 
 pub(crate) fn parse_linear_gradient(val: &str) -> Option<LinearGradient> {
-    let inner = val.trim_start_matches("linear-gradient(").trim_end_matches(")");
-    let parts: Vec<&str> = inner.split(',').collect();
+    let inner = val.trim().strip_prefix("linear-gradient(")?.strip_suffix(')')?;
+    // Commas inside a stop's `rgba(...)` don't separate stops.
+    let parts = split_top_level(inner, ',');
     if parts.is_empty() { return None; }
 
     let mut angle = 180.0;
-    let mut stops = Vec::new();
 
     let mut start_idx = 0;
     if parts[0].contains("deg") {
@@ -94,30 +95,147 @@ pub(crate) fn parse_linear_gradient(val: &str) -> Option<LinearGradient> {
          start_idx = 1;
     }
 
-    for i in start_idx..parts.len() {
-        let stop_str = parts[i].trim();
-        let stop_parts: Vec<&str> = stop_str.split_whitespace().collect();
-        if stop_parts.is_empty() { continue; }
-        
-        let color_str = stop_parts[0];
-        if let Some(color) = parse_hex_color(color_str) {
-             let pos = if stop_parts.len() > 1 {
-                 if let Some(p) = stop_parts[1].strip_suffix("%") {
-                     p.parse::<f32>().unwrap_or(0.0) / 100.0
-                 } else {
-                     0.0
-                 }
-             } else {
-                 if i == start_idx { 0.0 } else { 1.0 }
-             };
-             
-             stops.push((color, pos));
-         }
-    }
-    
+    let stops = position_color_stops(parts[start_idx..].iter().filter_map(|part| parse_color_stop(part)).collect());
     Some(LinearGradient { angle, stops: stops.into() })
 }
 
+/// A gradient color stop: a color, possibly a function with spaces of its
+/// own such as `rgb(255 0 0)`, then an optional percentage position.
+fn parse_color_stop(part: &str) -> Option<(Color, Option<f32>)> {
+    let (color, position) = match split_top_level(part, ' ')[..] {
+        [color] => (color, None),
+        [color, position] => (color, Some(position)),
+        _ => return None,
+    };
+    let position = position.and_then(|p| p.strip_suffix('%')).and_then(|p| p.parse::<f32>().ok());
+    Some((parse_hex_color(color)?, position.map(|p| p / 100.0)))
+}
+
+/// Gives every color stop its position as in CSS: the ends default to 0% and
+/// 100%, a stop placed before an earlier one moves up to it, and stops
+/// without a position are spread evenly between the positioned stops around
+/// them.
+fn position_color_stops(mut stops: Vec<(Color, Option<f32>)>) -> Vec<(Color, f32)> {
+    if let Some((_, first)) = stops.first_mut() {
+        first.get_or_insert(0.0);
+    }
+    if let Some((_, last)) = stops.last_mut() {
+        last.get_or_insert(1.0);
+    }
+    let mut previous = 0.0f32;
+    for (_, pos) in &mut stops {
+        if let Some(pos) = pos {
+            *pos = pos.max(previous);
+            previous = *pos;
+        }
+    }
+    let mut i = 0;
+    while i < stops.len() {
+        if stops[i].1.is_some() {
+            i += 1;
+            continue;
+        }
+        // The first and last stops have positions, so a run has both neighbours.
+        let end = (i..stops.len()).find(|&j| stops[j].1.is_some()).unwrap_or(stops.len() - 1);
+        let (from, to) = (stops[i - 1].1.unwrap_or(0.0), stops[end].1.unwrap_or(1.0));
+        let steps = (end - i + 1) as f32;
+        for (k, (_, pos)) in stops[i..end].iter_mut().enumerate() {
+            *pos = Some(from + (to - from) * (k + 1) as f32 / steps);
+        }
+        i = end;
+    }
+    stops.into_iter().map(|(color, pos)| (color, pos.unwrap_or(0.0))).collect()
+}
+
+/// Parses `radial-gradient([circle | ellipse], <color> [<percentage>], ...)`.
+/// The gradient is always centered, and its stops are positioned like a
+/// linear gradient's; at least two stops are needed.
+pub(crate) fn parse_radial_gradient(val: &str) -> Option<RadialGradient> {
+    let inner = val.trim().strip_prefix("radial-gradient(")?.strip_suffix(')')?;
+    let mut parts = split_top_level(inner, ',');
+    let mut circle = false;
+    if let Some(first) = parts.first()
+        && parse_hex_color(split_top_level(first, ' ')[0]).is_none()
+    {
+        circle = first.split_whitespace().any(|word| word == "circle");
+        parts.remove(0);
+    }
+
+    let stops: Vec<(Color, Option<f32>)> = parts
+        .iter()
+        .filter_map(|part| parse_color_stop(part))
+        .collect();
+    if stops.len() < 2 {
+        return None;
+    }
+    Some(RadialGradient { circle, stops: position_color_stops(stops).into() })
+}
+
+/// Parses a `grid-template-columns`/`-rows` track list of `fr`, `px`, `%` and
+/// `auto` tracks. `repeat(<count>, <tracks>)` is expanded in place.
+pub(crate) fn parse_grid_tracks(val: &str) -> Option<Vec<TrackSizingFunction>> {
+    let mut tracks = Vec::new();
+    for token in split_top_level(val, ' ') {
+        if let Some(inner) = token.strip_prefix("repeat(").and_then(|t| t.strip_suffix(')')) {
+            let (count, repeated) = inner.split_once(',')?;
+            let repeated = parse_grid_tracks(repeated)?;
+            for _ in 0..count.trim().parse::<usize>().ok()? {
+                tracks.extend_from_slice(&repeated);
+            }
+        } else if token == "auto" {
+            tracks.push(auto());
+        } else if let Some(flex) = token.strip_suffix("fr") {
+            tracks.push(fr(flex.parse::<f32>().ok()?));
+        } else {
+            tracks.push(parse_length_percentage(token)?.into());
+        }
+    }
+    Some(tracks)
+}
+
+/// `width / height` or a single number; `None` for `auto` and anything else.
+pub fn parse_aspect_ratio(val: &str) -> Option<f32> {
+    let ratio = match val.split_once('/') {
+        Some((width, height)) => width.trim().parse::<f32>().ok()? / height.trim().parse::<f32>().ok()?,
+        None => val.trim().parse::<f32>().ok()?,
+    };
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// `normal`, a unitless multiple or percentage, or a length in `px` or `em`
+/// of `font_size`.
+pub fn parse_line_height(val: &str, font_size: f32) -> Option<LineHeight> {
+    if val == "normal" {
+        Some(LineHeight::Multiple(1.0))
+    } else if let Some(percent) = val.strip_suffix('%') {
+        percent.parse::<f32>().ok().map(|p| LineHeight::Multiple(p / 100.0))
+    } else if let Some(em) = val.strip_suffix("em") {
+        em.parse::<f32>().ok().map(|em| LineHeight::Px(em * font_size))
+    } else if let Some(px) = val.strip_suffix("px") {
+        px.parse::<f32>().ok().map(LineHeight::Px)
+    } else {
+        val.parse::<f32>().ok().map(LineHeight::Multiple)
+    }
+    .filter(|line_height| !matches!(line_height, LineHeight::Multiple(v) | LineHeight::Px(v) if *v < 0.0))
+}
+
+/// `val` with every `em` and `rem` length turned into pixels of `em` and
+/// `rem` respectively; other values come back untouched.
+pub fn resolve_font_relative(val: &str, em: f32, rem: f32) -> std::borrow::Cow<'_, str> {
+    let to_px = |token: &str| {
+        let (number, unit) = match token.strip_suffix("rem") {
+            Some(number) => (number, rem),
+            None => (token.strip_suffix("em")?, em),
+        };
+        number.parse::<f32>().ok().map(|n| format!("{}px", n * unit))
+    };
+    if !val.contains("em") || val.split_whitespace().all(|token| to_px(token).is_none()) {
+        return std::borrow::Cow::Borrowed(val);
+    }
+    let tokens: Vec<String> = val.split_whitespace().map(|token| to_px(token).unwrap_or_else(|| token.to_string())).collect();
+    std::borrow::Cow::Owned(tokens.join(" "))
+}
+
 pub fn parse_px(val: &str) -> Option<f32> {
     if let Some(stripped) = val.strip_suffix("px") {
         stripped.parse::<f32>().ok()
@@ -187,6 +305,17 @@ pub(crate) fn parse_padding(val: &str) -> Option<taffy::geometry::Rect<LengthPer
                 bottom: v,
             })
         }
+        3 => {
+            let t = parse_length_percentage(parts[0])?;
+            let h = parse_length_percentage(parts[1])?;
+            let b = parse_length_percentage(parts[2])?;
+            Some(taffy::geometry::Rect {
+                left: h,
+                right: h,
+                top: t,
+                bottom: b,
+            })
+        }
         4 => {
             let t = parse_length_percentage(parts[0])?;
             let r = parse_length_percentage(parts[1])?;
@@ -229,6 +358,17 @@ pub(crate) fn parse_margin(val: &str) -> Option<taffy::geometry::Rect<LengthPerc
                 bottom: v,
             })
         }
+        3 => {
+            let t = parse_length_percentage_auto(parts[0])?;
+            let h = parse_length_percentage_auto(parts[1])?;
+            let b = parse_length_percentage_auto(parts[2])?;
+            Some(taffy::geometry::Rect {
+                left: h,
+                right: h,
+                top: t,
+                bottom: b,
+            })
+        }
         4 => {
             let t = parse_length_percentage_auto(parts[0])?;
             let r = parse_length_percentage_auto(parts[1])?;
@@ -244,3 +384,168 @@ pub(crate) fn parse_margin(val: &str) -> Option<taffy::geometry::Rect<LengthPerc
         _ => None
     }
 }
+
+/// Parses a keyword of `align-items`/`align-self`/`justify-items`/`justify-self`,
+/// which all share Taffy's `AlignItems`.
+pub(crate) fn parse_align_items(val: &str) -> Option<AlignItems> {
+    match val {
+        "flex-start" => Some(AlignItems::FlexStart),
+        "flex-end" => Some(AlignItems::FlexEnd),
+        "center" => Some(AlignItems::Center),
+        "baseline" => Some(AlignItems::Baseline),
+        "stretch" => Some(AlignItems::Stretch),
+        "start" | "left" => Some(AlignItems::Start),
+        "end" | "right" => Some(AlignItems::End),
+        _ => None,
+    }
+}
+
+/// Splits `val` at every `sep` outside parentheses, so that `rgba(0, 0, 0, 0.3)`
+/// stays one part.
+pub(crate) fn split_top_level(val: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in val.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c == sep && depth == 0 => {
+                parts.push(&val[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&val[start..]);
+    parts.into_iter().map(str::trim).filter(|p| !p.is_empty()).collect()
+}
+
+/// Parses the first shadow of `box-shadow`: offset-x, offset-y, then optional
+/// blur and spread lengths, and a color (black if omitted) before or after them.
+/// `none` and `inset` shadows are not drawn.
+pub(crate) fn parse_box_shadow(val: &str) -> Option<BoxShadow> {
+    let first = *split_top_level(val, ',').first()?;
+    let mut lengths = Vec::new();
+    let mut color = Color::BLACK;
+    for token in split_top_level(first, ' ') {
+        if token == "inset" || token == "none" {
+            return None;
+        } else if let Some(px) = parse_px(token) {
+            lengths.push(px);
+        } else {
+            color = parse_hex_color(token)?;
+        }
+    }
+    let (offset_x, offset_y, blur, spread) = match lengths[..] {
+        [x, y] => (x, y, 0.0, 0.0),
+        [x, y, blur] => (x, y, blur, 0.0),
+        [x, y, blur, spread] => (x, y, blur, spread),
+        _ => return None,
+    };
+    Some(BoxShadow { offset_x, offset_y, blur: blur.max(0.0), spread, color })
+}
+
+/// Parses `xywh(x y w h)` in image pixels, as used by `object-view-box`.
+pub(crate) fn parse_xywh(val: &str) -> Option<crate::graphics::Rect> {
+    let inner = val.trim().strip_prefix("xywh(")?.strip_suffix(')')?;
+    let parts: Vec<f32> = inner.split_whitespace().map(parse_px).collect::<Option<_>>()?;
+    match parts[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => Some(crate::graphics::Rect { x, y, width, height }),
+        _ => None,
+    }
+}
+
+/// The path in `url(...)`, quoted or not.
+pub(crate) fn parse_url(val: &str) -> Option<&str> {
+    let inner = val.trim().strip_prefix("url(")?.strip_suffix(')')?.trim();
+    let path = inner.trim_matches(|c| c == '"' || c == '\'');
+    (!path.is_empty()).then_some(path)
+}
+
+/// Parses a `transform` list of `translate`, `translateX`/`Y`, `scale`,
+/// `scaleX`/`Y` and `rotate` into one matrix; the last function applies first.
+pub(crate) fn parse_transform(val: &str) -> Option<Matrix> {
+    let mut matrix = Matrix::IDENTITY;
+    for function in val.split(')').map(str::trim).filter(|f| !f.is_empty()) {
+        let (name, args) = function.split_once('(')?;
+        let args: Vec<&str> = args.split([',', ' ']).filter(|a| !a.is_empty()).collect();
+        let number = |n: &str| n.parse::<f32>().ok();
+        let step = match (name.trim(), &args[..]) {
+            ("translate", [x]) | ("translateX", [x]) => Matrix::translate(parse_px(x)?, 0.0),
+            ("translate", [x, y]) => Matrix::translate(parse_px(x)?, parse_px(y)?),
+            ("translateY", [y]) => Matrix::translate(0.0, parse_px(y)?),
+            ("scale", [s]) => Matrix::scale(number(s)?, number(s)?),
+            ("scale", [x, y]) => Matrix::scale(number(x)?, number(y)?),
+            ("scaleX", [x]) => Matrix::scale(number(x)?, 1.0),
+            ("scaleY", [y]) => Matrix::scale(1.0, number(y)?),
+            ("rotate", [angle]) => Matrix::rotate(parse_angle(angle)?),
+            _ => return None,
+        };
+        matrix = matrix.multiply(step);
+    }
+    Some(matrix)
+}
+
+/// An angle in `deg`, `rad` or `turn`, in degrees.
+fn parse_angle(val: &str) -> Option<f32> {
+    if let Some(deg) = val.strip_suffix("deg") {
+        deg.parse().ok()
+    } else if let Some(rad) = val.strip_suffix("rad") {
+        rad.parse::<f32>().ok().map(f32::to_degrees)
+    } else if let Some(turn) = val.strip_suffix("turn") {
+        turn.parse::<f32>().ok().map(|t| t * 360.0)
+    } else {
+        (val == "0").then_some(0.0)
+    }
+}
+
+/// Parses `background-size`: `cover`, `contain`, or a width and optional
+/// height, each `auto`, a percentage or a pixel length.
+pub(crate) fn parse_background_size(val: &str) -> Option<BackgroundSize> {
+    fn length(token: &str) -> Option<Option<OriginOffset>> {
+        match token {
+            "auto" => Some(None),
+            _ => match token.strip_suffix('%') {
+                Some(p) => p.parse::<f32>().ok().map(|p| Some(OriginOffset::Fraction(p / 100.0))),
+                None => parse_px(token).map(|px| Some(OriginOffset::Px(px))),
+            },
+        }
+    }
+    match val.split_whitespace().collect::<Vec<_>>()[..] {
+        ["cover"] => Some(BackgroundSize::Cover),
+        ["contain"] => Some(BackgroundSize::Contain),
+        [width] => Some(BackgroundSize::Length(length(width)?, None)),
+        [width, height] => Some(BackgroundSize::Length(length(width)?, length(height)?)),
+        _ => None,
+    }
+}
+
+/// Parses `transform-origin`: one or two of `left`/`center`/`right`/`top`/`bottom`,
+/// percentages or pixel lengths, horizontal first unless the keywords say otherwise.
+pub(crate) fn parse_transform_origin(val: &str) -> Option<(OriginOffset, OriginOffset)> {
+    fn offset(token: &str) -> Option<OriginOffset> {
+        match token {
+            "left" | "top" => Some(OriginOffset::Fraction(0.0)),
+            "center" => Some(OriginOffset::Fraction(0.5)),
+            "right" | "bottom" => Some(OriginOffset::Fraction(1.0)),
+            _ => match token.strip_suffix('%') {
+                Some(p) => p.parse::<f32>().ok().map(|p| OriginOffset::Fraction(p / 100.0)),
+                None => parse_px(token).map(OriginOffset::Px),
+            },
+        }
+    }
+    let is_vertical = |t: &str| t == "top" || t == "bottom";
+    let is_horizontal = |t: &str| t == "left" || t == "right";
+
+    let tokens: Vec<&str> = val.split_whitespace().collect();
+    let center = OriginOffset::Fraction(0.5);
+    match tokens[..] {
+        [t] if is_vertical(t) => Some((center, offset(t)?)),
+        [t] => Some((offset(t)?, center)),
+        // `top left` names the vertical position first.
+        [a, b] if is_vertical(a) || is_horizontal(b) => Some((offset(b)?, offset(a)?)),
+        // A third, z value is accepted and ignored.
+        [a, b] | [a, b, _] => Some((offset(a)?, offset(b)?)),
+        _ => None,
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use base64::prelude::*;
+use tiny_skia::Pixmap;
+
+/// How many decoded images, and how many tinted copies, an `ImageCache`
+/// holds before the least recently drawn one makes room, so that a view
+/// cycling through many sources or colors doesn't keep them all in memory.
+pub const IMAGE_CACHE_LIMIT: usize = 128;
+
+/// Decoded images by `src`, and tinted copies of them by `src` and color, in
+/// whatever form a renderer draws them. Owned by the caller, so that it
+/// outlives renderers made for a single frame.
+pub struct ImageCache<T> {
+    /// `None` marks an image that failed to load, so it is drawn as a
+    /// placeholder instead of being read again every frame.
+    decoded: Lru<String, Option<T>>,
+    tinted: Lru<(String, [u8; 4]), T>,
+}
+
+impl<T> Default for ImageCache<T> {
+    fn default() -> Self {
+        Self { decoded: Lru::default(), tinted: Lru::default() }
+    }
+}
+
+impl<T> ImageCache<T> {
+    /// Decodes `src` with `decode` unless it is cached, and marks it used.
+    pub fn load(&mut self, src: &str, decode: impl FnOnce() -> Option<T>) {
+        if !self.decoded.touch(src) {
+            self.decoded.insert(src.to_string(), decode());
+        }
+    }
+
+    /// Makes the copy of the loaded `src` in `tint` with `apply` unless it is
+    /// cached, and marks it used. Does nothing if `src` failed to load.
+    pub fn tint(&mut self, src: &str, tint: [u8; 4], apply: impl FnOnce(&T) -> T) {
+        let key = (src.to_string(), tint);
+        if !self.tinted.touch(&key)
+            && let Some(Some(image)) = self.decoded.get(src)
+        {
+            let tinted = apply(image);
+            self.tinted.insert(key, tinted);
+        }
+    }
+
+    /// The image `load` or `tint` last made for `src` in `tint`, if any.
+    pub fn get(&self, src: &str, tint: Option<[u8; 4]>) -> Option<&T> {
+        match tint {
+            Some(tint) => self.tinted.get(&(src.to_string(), tint)),
+            None => self.decoded.get(src).and_then(Option::as_ref),
+        }
+    }
+
+    /// Whether `src` is cached, whether or not it loaded.
+    pub fn contains(&self, src: &str) -> bool {
+        self.decoded.get(src).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.decoded.entries.clear();
+        self.tinted.entries.clear();
+    }
+}
+
+/// Entries with the tick they were last used at, holding at most
+/// `IMAGE_CACHE_LIMIT`.
+struct Lru<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    tick: u64,
+}
+
+impl<K, V> Default for Lru<K, V> {
+    fn default() -> Self {
+        Self { entries: HashMap::new(), tick: 0 }
+    }
+}
+
+impl<K: Eq + Hash, V> Lru<K, V> {
+    fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+    {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    /// Marks `key` used; false if it isn't cached.
+    fn touch<Q: Eq + Hash + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(_, used)| *used = tick).is_some()
+    }
+
+    /// Inserts `value`, first dropping the least recently used entry if full.
+    fn insert(&mut self, key: K, value: V)
+    where
+        K: Clone,
+    {
+        if self.entries.len() >= IMAGE_CACHE_LIMIT
+            && let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        self.entries.insert(key, (value, self.tick));
+    }
+}
+
+/// Loads `src` from a `data:image/...;base64,` URI or from the file system,
+/// picking the decoder by MIME type or file extension. PNG and JPEG are supported.
+pub fn load_image(src: &str) -> Result<Pixmap, String> {
+    let (format, data) = match src.strip_prefix("data:") {
+        Some(uri) => {
+            let (header, payload) = uri.split_once(',').ok_or("malformed data URI")?;
+            let mime = header.strip_suffix(";base64").ok_or("data URI is not base64")?;
+            let data = BASE64_STANDARD.decode(payload.trim()).map_err(|e| e.to_string())?;
+            (mime.strip_prefix("image/").unwrap_or(mime).to_ascii_lowercase(), data)
+        }
+        None => {
+            let extension = std::path::Path::new(src)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            (extension, std::fs::read(src).map_err(|e| e.to_string())?)
+        }
+    };
+    match format.as_str() {
+        "png" => Pixmap::decode_png(&data).map_err(|e| e.to_string()),
+        "jpeg" | "jpg" => {
+            let rgba = image::load_from_memory_with_format(&data, image::ImageFormat::Jpeg)
+                .map_err(|e| e.to_string())?
+                .to_rgba8();
+            let size = tiny_skia::IntSize::from_wh(rgba.width(), rgba.height()).ok_or("empty image")?;
+            // JPEGs are opaque, so the pixels are already premultiplied.
+            Pixmap::from_vec(rgba.into_raw(), size).ok_or_else(|| "invalid image size".to_string())
+        }
+        _ => Err(format!("unsupported image format '{}'", format)),
+    }
+}
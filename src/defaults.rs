@@ -10,6 +10,7 @@ pub enum ElementType {
     Progress,
     Canvas,
     TextInput,
+    Select,
 }
 
 pub struct StyleBundle {
@@ -31,21 +32,46 @@ impl Default for StyleBundle {
 pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundle {
     let mut bundle = StyleBundle::default();
     bundle.container_style = parent_style.clone();
+    bundle.container_style.inherited_font_size = parent_style.font_size;
 
     // Reset non-inherited CSS properties
     bundle.container_style.background_color = None;
     bundle.container_style.background_gradient = None;
+    bundle.container_style.background_image = None;
+    bundle.container_style.background_size = crate::graphics::BackgroundSize::Stretch;
+    bundle.container_style.background_position = crate::style::BACKGROUND_POSITION_TOP_LEFT;
+    bundle.container_style.object_view_box = None;
+    bundle.container_style.value_label = None;
+    bundle.container_style.text_selection = None;
+    bundle.container_style.backdrop_blur = 0.0;
+    bundle.container_style.box_shadow = None;
+    bundle.container_style.opacity = 1.0;
+    bundle.container_style.layer = false;
+    bundle.container_style.line_clamp = None;
+    bundle.container_style.transform = None;
+    bundle.container_style.transform_origin = crate::style::TRANSFORM_ORIGIN_CENTER;
     bundle.container_style.border_radius = 0.0;
     bundle.container_style.border_width = 0.0;
     bundle.container_style.border_color = None;
+    bundle.container_style.outline_width = 0.0;
+    bundle.container_style.outline_color = None;
+    bundle.container_style.border_side_widths = [None; 4];
+    bundle.container_style.border_side_colors = [None; 4];
+    bundle.container_style.border_style = crate::style::BorderStyle::Solid;
+    bundle.container_style.fill = None;
+    bundle.container_style.is_icon = false;
     bundle.container_style.overflow = crate::Overflow::Visible;
+    bundle.container_style.hide_scrollbar = false;
+    bundle.container_style.text_overflow = false;
     bundle.container_style.order = 0;
+    bundle.container_style.z_index = 0;
     bundle.container_style.flex_direction = FlexDirection::Row;
     bundle.container_style.flex_wrap = FlexWrap::NoWrap;
     bundle.container_style.justify_content = None;
     bundle.container_style.align_items = None;
     bundle.container_style.width = None;
     bundle.container_style.height = None;
+    bundle.container_style.sized = (false, false);
     bundle.container_style.padding_left = 0.0;
     bundle.container_style.padding_right = 0.0;
     bundle.container_style.padding_top = 0.0;
@@ -66,9 +92,10 @@ pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundl
     bundle.container_style.animation_direction = std::sync::Arc::from("normal");
     bundle.container_style.animation_fill_mode = std::sync::Arc::from("none");
     bundle.container_style.animation_play_state = std::sync::Arc::from("running");
+    bundle.container_style.transitions = std::sync::Arc::from([]);
 
     bundle.container_style.display = match tag {
-        "div" | "body" | "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "ul" | "li" | "table" | "tbody" | "thead" | "tfoot" | "tr" => Display::Block,
+        "div" | "body" | "p" | "pre" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "ul" | "ol" | "li" | "table" | "tbody" | "thead" | "tfoot" | "tr" | "hr" => Display::Block,
         _ => Display::InlineBlock,
     };
 
@@ -93,7 +120,14 @@ pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundl
                 top: length(10.0), bottom: length(10.0)
             };
         }
-        "ul" => {
+        "ul" | "ol" => {
+            // Bullets change with each level of nesting, as in browsers.
+            bundle.container_style.list_style = match (tag, parent_style.list_style) {
+                ("ol", _) => crate::style::ListStyle::Decimal,
+                (_, crate::style::ListStyle::Disc) => crate::style::ListStyle::Circle,
+                (_, crate::style::ListStyle::Circle | crate::style::ListStyle::Square) => crate::style::ListStyle::Square,
+                _ => crate::style::ListStyle::Disc,
+            };
             bundle.taffy_style.padding = taffy::geometry::Rect {
                 left: length(20.0), right: length(0.0),
                 top: length(0.0), bottom: length(0.0)
@@ -107,6 +141,17 @@ pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundl
         }
         "div" => {
         }
+        "hr" => {
+            // A zero-height box whose border is the rule, so its thickness,
+            // color and dash style all come from the border properties.
+            bundle.container_style.border_width = 1.0;
+            bundle.container_style.border_color = Some(crate::Color::from_rgba8(136, 136, 136, 255));
+            bundle.taffy_style.size.height = length(0.0);
+            bundle.taffy_style.margin = taffy::geometry::Rect {
+                left: length(0.0), right: length(0.0),
+                top: length(8.0), bottom: length(8.0)
+            };
+        }
         "body" => {
             bundle.taffy_style.margin = taffy::geometry::Rect {
                 left: length(8.0), right: length(8.0),
@@ -204,6 +249,7 @@ pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundl
         }
         "input_text" => {
             bundle.element_type = ElementType::TextInput;
+            bundle.container_style.cursor = crate::style::Cursor::Text;
             bundle.taffy_style.size = Size { width: length(150.0), height: length(30.0) };
             bundle.taffy_style.padding = taffy::geometry::Rect {
                 left: length(8.0), right: length(8.0),
@@ -219,10 +265,40 @@ pub fn get_default_style(tag: &str, parent_style: &ContainerStyle) -> StyleBundl
             bundle.container_style.border_width = 1.0;
             bundle.container_style.border_color = Some(crate::Color::from_rgba8(200, 200, 200, 255));
         }
+        "select" => {
+            bundle.element_type = ElementType::Select;
+            bundle.taffy_style.size = Size { width: length(150.0), height: length(30.0) };
+            bundle.taffy_style.margin = taffy::geometry::Rect {
+                left: length(2.0), right: length(2.0),
+                top: length(2.0), bottom: length(2.0)
+            };
+            bundle.container_style.background_color = Some(crate::Color::WHITE);
+            bundle.container_style.border_radius = 4.0;
+            bundle.container_style.border_width = 1.0;
+            bundle.container_style.border_color = Some(crate::Color::from_rgba8(200, 200, 200, 255));
+        }
+        // Options are listed by their `select`, not laid out in place.
+        "option" => {
+            bundle.taffy_style.display = taffy::style::Display::None;
+        }
+        "pre" => {
+            bundle.container_style.white_space = crate::style::WhiteSpace::Pre;
+            bundle.taffy_style.margin = taffy::geometry::Rect {
+                left: length(0.0), right: length(0.0),
+                top: length(12.0), bottom: length(12.0)
+            };
+        }
+        "a" => {
+            bundle.container_style.color = crate::Color::from_rgba8(0, 122, 255, 255);
+            bundle.container_style.underline = true;
+            bundle.container_style.cursor = crate::style::Cursor::Pointer;
+        }
         "canvas" => {
             bundle.element_type = ElementType::Canvas;
             bundle.taffy_style.size = Size { width: length(200.0), height: length(200.0) };
         }
+        // Synthetic elements generated for `::before`/`::after` content.
+        "xerune-before" | "xerune-after" => {}
         "br" => {
             bundle.taffy_style.size.width = Dimension::percent(1.0);
             bundle.taffy_style.size.height = Dimension::length(0.0);
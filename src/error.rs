@@ -0,0 +1,32 @@
+use std::fmt;
+
+use taffy::TaffyError;
+
+/// Errors from building or laying out a `Ui`.
+#[derive(Debug)]
+pub enum Error {
+    /// The document produced no element to use as the root.
+    EmptyDocument,
+    /// Taffy rejected a layout operation.
+    Layout(TaffyError),
+    /// The HTML source could not be read.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyDocument => write!(f, "document has no root element"),
+            Error::Layout(err) => write!(f, "layout failed: {err}"),
+            Error::Parse(msg) => write!(f, "failed to parse document: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<TaffyError> for Error {
+    fn from(err: TaffyError) -> Self {
+        Error::Layout(err)
+    }
+}
@@ -0,0 +1,41 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use fontdue::Font;
+use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle};
+
+use crate::graphics::{font_index, font_runs};
+
+thread_local! {
+    /// The first font covering a character, by font list and character.
+    static COVERING: RefCell<HashMap<(usize, char), Option<usize>>> = RefCell::new(HashMap::new());
+}
+
+/// Appends `text` to `layout` in the face `font_index` picks, except for runs
+/// of characters that face lacks: those come from the first font in `fonts`
+/// that has them, so emoji or CJK draw from a fallback font instead of as
+/// `.notdef` boxes. Which font covers a character the face lacks is looked
+/// up once per font list.
+pub fn append_text(layout: &mut Layout, fonts: &[Font], text: &str, font_size: f32, font_index: usize) {
+    let key = fonts.iter().fold(fonts.len(), |hash, font| hash.wrapping_mul(31).wrapping_add(font.file_hash()));
+    let covering = |c: char| COVERING.with(|cache| {
+        *cache.borrow_mut().entry((key, c)).or_insert_with(|| fonts.iter().position(|font| font.has_glyph(c)))
+    });
+    for (range, font) in font_runs(text, font_index, |c| fonts[font_index].has_glyph(c), covering) {
+        layout.append(fonts, &TextStyle::new(&text[range], font_size, font));
+    }
+}
+
+/// `TextMeasurer::text_layout` for `fonts`: every character of `text` with
+/// the x of its pen position and its advance.
+pub fn text_layout(fonts: &[Font], text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+    let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+    layout.reset(&LayoutSettings::default());
+    append_text(&mut layout, fonts, text, font_size, font_index(weight, fonts.len()));
+    layout.glyphs().iter()
+        .map(|glyph| {
+            let metrics = fonts[glyph.font_index].metrics_indexed(glyph.key.glyph_index, glyph.key.px);
+            (glyph.parent, glyph.x - metrics.xmin as f32, metrics.advance_width)
+        })
+        .collect()
+}
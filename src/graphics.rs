@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::style::OriginOffset;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
     pub r: u8, 
@@ -19,6 +21,22 @@ impl Color {
      pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
+
+    /// The color with its alpha multiplied by `opacity`.
+    pub fn faded(self, opacity: f32) -> Self {
+        Self { a: (self.a as f32 * opacity).round() as u8, ..self }
+    }
+
+    /// The color `amount` of the way to `other`, alpha included.
+    pub fn mix(self, other: Color, amount: f32) -> Self {
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: channel(self.a, other.a),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +45,61 @@ pub struct LinearGradient {
     pub stops: std::sync::Arc<[(Color, f32)]>, // Color and position (0.0 to 1.0)
 }
 
+impl LinearGradient {
+    /// Start and end points of the gradient line in a `width` x `height` box,
+    /// as CSS defines them: through the center at `angle` (0deg pointing up,
+    /// clockwise), long enough for the corners to get the first and last stops.
+    pub fn line(&self, width: f32, height: f32) -> ((f32, f32), (f32, f32)) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let half_length = (width * sin.abs() + height * cos.abs()) / 2.0;
+        let (dx, dy) = (sin * half_length, -cos * half_length);
+        let (cx, cy) = (width / 2.0, height / 2.0);
+        ((cx - dx, cy - dy), (cx + dx, cy + dy))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadialGradient {
+    /// `circle`; otherwise an ellipse with the box's aspect ratio.
+    pub circle: bool,
+    pub stops: std::sync::Arc<[(Color, f32)]>, // Color and position (0.0 to 1.0)
+}
+
+impl RadialGradient {
+    /// Radii of the gradient's ending shape, centered in a `width` x `height`
+    /// box and sized like CSS `farthest-corner`: the last stop reaches the corners.
+    pub fn radii(&self, width: f32, height: f32) -> (f32, f32) {
+        if self.circle {
+            let r = (width / 2.0).hypot(height / 2.0);
+            (r, r)
+        } else {
+            (width / 2.0 * std::f32::consts::SQRT_2, height / 2.0 * std::f32::consts::SQRT_2)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gradient {
+    Linear(LinearGradient),
+    Radial(RadialGradient),
+}
+
+impl Gradient {
+    pub fn stops(&self) -> &[(Color, f32)] {
+        match self {
+            Gradient::Linear(g) => &g.stops,
+            Gradient::Radial(g) => &g.stops,
+        }
+    }
+
+    fn stops_mut(&mut self) -> &mut std::sync::Arc<[(Color, f32)]> {
+        match self {
+            Gradient::Linear(g) => &mut g.stops,
+            Gradient::Radial(g) => &mut g.stops,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rect {
     pub x: f32,
@@ -53,6 +126,22 @@ impl Rect {
         }
     }
 
+    /// Whether the rect covers no area, as `Runtime::render` returns when
+    /// nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// The rect grown by `amount` on every side.
+    pub fn inflate(&self, amount: f32) -> Rect {
+        Rect {
+            x: self.x - amount,
+            y: self.y - amount,
+            width: self.width + amount * 2.0,
+            height: self.height + amount * 2.0,
+        }
+    }
+
     pub fn intersects(&self, other: &Rect) -> bool {
         !(self.x + self.width <= other.x
             || other.x + other.width <= self.x
@@ -61,6 +150,139 @@ impl Rect {
     }
 }
 
+/// `background-size`: how an image is scaled into its box.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BackgroundSize {
+    /// Stretched to the box, as an `<img>` is.
+    #[default]
+    Stretch,
+    /// Scaled to cover the box, cropping what overflows it.
+    Cover,
+    /// Scaled to fit inside the box, leaving the rest of it empty.
+    Contain,
+    /// Width and height in pixels or as fractions of the box. A `None`
+    /// side keeps the image's aspect ratio; both `None` is its own size.
+    Length(Option<OriginOffset>, Option<OriginOffset>),
+}
+
+impl BackgroundSize {
+    /// Scales the `source` region of an image into `rect` and places it at
+    /// `position`, where a fraction aligns that point of the image with the
+    /// same point of the box. Returns the part of `rect` the image covers and
+    /// the region of `source` drawn there; `None` if nothing of it is visible.
+    pub fn fit(self, rect: Rect, source: Rect, position: (OriginOffset, OriginOffset)) -> Option<(Rect, Rect)> {
+        if source.width <= 0.0 || source.height <= 0.0 {
+            return None;
+        }
+        let (width, height) = match self {
+            BackgroundSize::Stretch => (rect.width, rect.height),
+            BackgroundSize::Cover | BackgroundSize::Contain => {
+                let (scale_x, scale_y) = (rect.width / source.width, rect.height / source.height);
+                let scale = if self == BackgroundSize::Cover { scale_x.max(scale_y) } else { scale_x.min(scale_y) };
+                (source.width * scale, source.height * scale)
+            }
+            BackgroundSize::Length(width, height) => {
+                let width = width.map(|w| w.resolve(rect.width));
+                let height = height.map(|h| h.resolve(rect.height));
+                match (width, height) {
+                    (Some(w), Some(h)) => (w, h),
+                    (Some(w), None) => (w, source.height * w / source.width),
+                    (None, Some(h)) => (source.width * h / source.height, h),
+                    (None, None) => (source.width, source.height),
+                }
+            }
+        };
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+        let x = rect.x + position.0.resolve(rect.width - width);
+        let y = rect.y + position.1.resolve(rect.height - height);
+        let left = x.max(rect.x);
+        let top = y.max(rect.y);
+        let right = (x + width).min(rect.x + rect.width);
+        let bottom = (y + height).min(rect.y + rect.height);
+        if right <= left || bottom <= top {
+            return None;
+        }
+        let (scale_x, scale_y) = (width / source.width, height / source.height);
+        Some((
+            Rect { x: left, y: top, width: right - left, height: bottom - top },
+            Rect {
+                x: source.x + (left - x) / scale_x,
+                y: source.y + (top - y) / scale_y,
+                width: (right - left) / scale_x,
+                height: (bottom - top) / scale_y,
+            },
+        ))
+    }
+}
+
+/// A 2D affine transform, mapping `(x, y)` to
+/// `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Matrix {
+    pub const IDENTITY: Self = Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self { e: x, f: y, ..Self::IDENTITY }
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self { a: x, d: y, ..Self::IDENTITY }
+    }
+
+    /// Clockwise on screen, where y points down.
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    /// `other` followed by `self`, as in a CSS transform list where the
+    /// rightmost function applies first.
+    pub fn multiply(self, other: Matrix) -> Matrix {
+        Matrix {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// The same transform applied around `(x, y)` instead of the origin.
+    pub fn about(self, x: f32, y: f32) -> Matrix {
+        Matrix::translate(x, y).multiply(self).multiply(Matrix::translate(-x, -y))
+    }
+
+    pub fn map_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    /// The bounding box of `rect` once transformed.
+    pub fn map_rect(&self, rect: Rect) -> Rect {
+        let corners = [(rect.x, rect.y), (rect.x + rect.width, rect.y), (rect.x, rect.y + rect.height), (rect.x + rect.width, rect.y + rect.height)]
+            .map(|(x, y)| self.map_point(x, y));
+        let (min_x, max_x) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(x, _)| (lo.min(x), hi.max(x)));
+        let (min_y, max_y) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+        Rect { x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y }
+    }
+
+    /// Whether the transform only moves things, without scaling or rotating them.
+    pub fn is_translation(&self) -> bool {
+        self.a == 1.0 && self.b == 0.0 && self.c == 0.0 && self.d == 1.0
+    }
+}
+
 pub struct Canvas {
     pub width: u32,
     pub height: u32,
@@ -77,6 +299,36 @@ impl Canvas {
             dirty: true,
         }
     }
+
+    /// The RGBA pixel at `(x, y)`, or `None` outside the canvas.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = ((y * self.width + x) * 4) as usize;
+        let p = self.data.get(i..i + 4)?;
+        Some(Color::new(p[0], p[1], p[2], p[3]))
+    }
+
+    /// The RGBA bytes of `rect`, clipped to the canvas, row by row. Returns the
+    /// bytes with the clipped width and height, since rows of a sub-region are
+    /// not contiguous in `data` and have to be copied.
+    pub fn region(&self, rect: Rect) -> (Vec<u8>, u32, u32) {
+        let x0 = (rect.x.max(0.0) as u32).min(self.width);
+        let y0 = (rect.y.max(0.0) as u32).min(self.height);
+        let x1 = ((rect.x + rect.width).max(0.0).ceil() as u32).clamp(x0, self.width);
+        let y1 = ((rect.y + rect.height).max(0.0).ceil() as u32).clamp(y0, self.height);
+
+        let mut bytes = Vec::with_capacity(((x1 - x0) * (y1 - y0) * 4) as usize);
+        for y in y0..y1 {
+            let start = ((y * self.width + x0) * 4) as usize;
+            let end = ((y * self.width + x1) * 4) as usize;
+            if let Some(row) = self.data.get(start..end) {
+                bytes.extend_from_slice(row);
+            }
+        }
+        (bytes, x1 - x0, y1 - y0)
+    }
 }
 
 pub enum ContextCommand {
@@ -98,6 +350,10 @@ impl Context {
         }
     }
     
+    pub fn canvas(&self, id: &str) -> Option<&Canvas> {
+        self.canvases.get(id)
+    }
+
     pub fn canvas_mut(&mut self, id: &str) -> Option<&mut Canvas> {
         self.canvases.get_mut(id)
     }
@@ -129,14 +385,28 @@ impl Context {
     }
 }
 
+const CARET_WIDTH: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum DrawCommand {
-    Clip { rect: Rect },
+    /// Clips everything up to the matching `PopClip` to `rect`, with rounded
+    /// corners of `border_radius`. Nested clips intersect.
+    Clip { rect: Rect, border_radius: f32 },
     PopClip,
+    /// Draws everything up to the matching `PopTransform` through `matrix`.
+    /// `rect` is the box of the transformed element.
+    PushTransform { matrix: Matrix, rect: Rect },
+    PopTransform,
     DrawRect {
         rect: Rect,
         color: Option<Color>,
-        gradient: Option<LinearGradient>,
+        gradient: Option<Gradient>,
         border_radius: f32,
         border_width: f32,
         border_color: Option<Color>,
@@ -147,11 +417,25 @@ pub enum DrawCommand {
         color: Color, 
         font_size: f32,
         weight: u16,
+        word_spacing: f32,
+        /// `text-overflow: ellipsis`: text wider than this is cut short with
+        /// an ellipsis to fit, see `TextMeasurer::truncate_text`.
+        max_width: Option<f32>,
     },
     DrawImage {
         src: String,
         rect: Rect,
         border_radius: f32,
+        /// Region of the source image, in image pixels, scaled into `rect`; `None` draws the whole image.
+        src_rect: Option<Rect>,
+        /// Draws the image's alpha channel in this color instead of its own colors, for icons.
+        tint: Option<Color>,
+        /// Multiplies the image's alpha, from `opacity` on it and its ancestors.
+        opacity: f32,
+        /// How the image is scaled into `rect`, for `background-size`.
+        size: BackgroundSize,
+        /// Where the scaled image sits in `rect`, for `background-position`.
+        position: (OriginOffset, OriginOffset),
     },
     DrawCheckbox {
         rect: Rect,
@@ -162,6 +446,7 @@ pub enum DrawCommand {
         rect: Rect,
         value: f32,
         color: Color,
+        label: Option<String>,
     },
 
     DrawProgress {
@@ -169,14 +454,104 @@ pub enum DrawCommand {
         value: f32,
         max: f32,
         color: Color,
+        label: Option<String>,
     },
     DrawCanvas {
         id: String,
         rect: Rect,
     },
+    /// The caret of a focused text input, after `text` drawn at the start of
+    /// `rect`. Renderers place it with `caret_rect`.
+    DrawCaret {
+        rect: Rect,
+        text: String,
+        color: Color,
+        font_size: f32,
+        weight: u16,
+    },
+    /// The highlight behind the characters `range.0..range.1` of `text`, drawn
+    /// at the start of `rect` right before the text. Renderers place it with
+    /// `selection_rect`.
+    DrawTextSelection {
+        rect: Rect,
+        text: String,
+        range: (usize, usize),
+        color: Color,
+        font_size: f32,
+        weight: u16,
+        word_spacing: f32,
+    },
+    /// The scrollbar of a scroll container along one axis: a track `rect`
+    /// with the thumb at `thumb_rect`. Renderers pick the colors.
+    DrawScrollbar {
+        rect: Rect,
+        thumb_rect: Rect,
+        orientation: Orientation,
+    },
+    /// A `box-shadow`: `rect` (already offset and spread) filled with `color`
+    /// and blurred by the CSS blur radius `blur`, drawn before the box itself.
+    DrawShadow {
+        rect: Rect,
+        border_radius: f32,
+        blur: f32,
+        color: Color,
+    },
+    /// Blurs what has already been drawn inside `rect`, for `backdrop-filter: blur()`.
+    /// `radius` is the blur's standard deviation in pixels.
+    BackdropBlur {
+        rect: Rect,
+        radius: f32,
+    },
+    /// Fills the whole target (within the current clip) before anything else is drawn.
+    Clear {
+        color: Color,
+    },
+    /// Starts the drawing of a `will-change`/`data-layer` subtree whose box is
+    /// `rect`. Everything up to the matching `EndLayer` may be rasterized once
+    /// and reused while only its position changes.
+    BeginLayer {
+        rect: Rect,
+    },
+    EndLayer,
 }
 
 impl DrawCommand {
+    /// The text command drawing the label of a labelled slider or progress bar,
+    /// centered on the bar. Renderers draw it right after the bar itself.
+    pub fn label_command(&self, measurer: &impl TextMeasurer) -> Option<DrawCommand> {
+        // The label contrasts with whatever lies under its center: the filled
+        // part of the bar or the renderers' track color.
+        let (rect, label, under) = match self {
+            DrawCommand::DrawSlider { rect, value, color, label: Some(label) } => {
+                (rect, label, if *value >= 0.5 { *color } else { Color::new(60, 60, 60, 255) })
+            }
+            DrawCommand::DrawProgress { rect, value, max, color, label: Some(label) } => {
+                let fraction = if *max > 0.0 { value / max } else { 0.0 };
+                (rect, label, if fraction >= 0.5 { *color } else { Color::new(200, 200, 200, 255) })
+            }
+            _ => return None,
+        };
+        let luminance = 0.299 * under.r as f32 + 0.587 * under.g as f32 + 0.114 * under.b as f32;
+        let color = if luminance > 140.0 { Color::BLACK } else { Color::WHITE };
+
+        let font_size = (rect.height * 0.75).clamp(8.0, 16.0);
+        let (width, height) = measurer.measure_text(label, font_size, 0);
+        Some(DrawCommand::DrawText {
+            text: label.clone(),
+            rect: Rect {
+                x: rect.x + (rect.width - width) / 2.0,
+                y: rect.y + (rect.height - height) / 2.0,
+                width,
+                height,
+            },
+            color,
+            font_size,
+            weight: 0,
+            word_spacing: 0.0,
+            max_width: None,
+        })
+    }
+
     pub fn bounds(&self) -> Option<Rect> {
         let pad = 10.0; // Pad bounds generously to catch font overhangs and anti-aliasing bleeds
         let apply_pad = |r: Rect| Rect {
@@ -187,8 +562,10 @@ impl DrawCommand {
         };
 
         match self {
-            DrawCommand::Clip { rect } => Some(apply_pad(*rect)),
+            DrawCommand::Clip { rect, .. } => Some(apply_pad(*rect)),
             DrawCommand::PopClip => None,
+            DrawCommand::PushTransform { matrix, rect } => Some(apply_pad(matrix.map_rect(*rect))),
+            DrawCommand::PopTransform => None,
             DrawCommand::DrawRect { rect, .. } => Some(apply_pad(*rect)),
             DrawCommand::DrawText { rect, .. } => Some(apply_pad(*rect)),
             DrawCommand::DrawImage { rect, .. } => Some(apply_pad(*rect)),
@@ -196,14 +573,389 @@ impl DrawCommand {
             DrawCommand::DrawSlider { rect, .. } => Some(apply_pad(*rect)),
             DrawCommand::DrawProgress { rect, .. } => Some(apply_pad(*rect)),
             DrawCommand::DrawCanvas { rect, .. } => Some(apply_pad(*rect)),
+            DrawCommand::DrawCaret { rect, .. } => Some(apply_pad(*rect)),
+            DrawCommand::DrawTextSelection { rect, .. } => Some(apply_pad(*rect)),
+            DrawCommand::DrawScrollbar { rect, .. } => Some(apply_pad(*rect)),
+            DrawCommand::DrawShadow { rect, blur, .. } => Some(apply_pad(rect.inflate(shadow_margin(*blur)))),
+            DrawCommand::BackdropBlur { rect, .. } => Some(apply_pad(*rect)),
+            DrawCommand::Clear { .. } => None,
+            DrawCommand::BeginLayer { .. } => None,
+            DrawCommand::EndLayer => None,
+        }
+    }
+
+    /// The bar a `DrawCaret` fills: right after its text, one line tall.
+    pub fn caret_rect(&self, measurer: &impl TextMeasurer) -> Option<Rect> {
+        let DrawCommand::DrawCaret { rect, text, font_size, weight, .. } = self else {
+            return None;
+        };
+        let (text_width, _) = measurer.measure_text(text, *font_size, *weight);
+        let x = (rect.x + text_width).min(rect.x + rect.width);
+        Some(Rect::new(x, rect.y, CARET_WIDTH, (font_size * 1.2).min(rect.height)))
+    }
+
+    /// The box a `DrawTextSelection` fills: from the left of its first
+    /// selected character to the right of its last, as tall as the text.
+    pub fn selection_rect(&self, measurer: &impl TextMeasurer) -> Option<Rect> {
+        let DrawCommand::DrawTextSelection { rect, text, range, font_size, weight, word_spacing, .. } = self else {
+            return None;
+        };
+        let layout = measurer.text_layout(text, *font_size, *weight);
+        let (start, end) = (range.0.min(layout.len()), range.1.min(layout.len()));
+        if start >= end {
+            return None;
+        }
+        // Renderers add `word_spacing` after every space, shifting what follows.
+        let spaced = |i: usize| layout[..i].iter().filter(|(c, ..)| *c == ' ').count() as f32 * word_spacing;
+        let left = layout[start].1 + spaced(start);
+        let (_, last_x, last_advance) = layout[end - 1];
+        let right = (last_x + last_advance + spaced(end - 1)).min(rect.width);
+        (right > left).then(|| Rect::new(rect.x + left, rect.y, right - left, rect.height))
+    }
+
+    /// Multiplies the alpha of everything the command draws by `opacity`.
+    /// Canvases and scrollbars are drawn as they are.
+    pub fn fade(&mut self, opacity: f32) {
+        match self {
+            DrawCommand::DrawRect { color, gradient, border_color, .. } => {
+                *color = color.map(|c| c.faded(opacity));
+                *border_color = border_color.map(|c| c.faded(opacity));
+                if let Some(gradient) = gradient {
+                    let stops = gradient.stops_mut();
+                    *stops = stops.iter().map(|(c, p)| (c.faded(opacity), *p)).collect();
+                }
+            }
+            DrawCommand::DrawText { color, .. }
+            | DrawCommand::DrawCheckbox { color, .. }
+            | DrawCommand::DrawSlider { color, .. }
+            | DrawCommand::DrawProgress { color, .. }
+            | DrawCommand::DrawCaret { color, .. }
+            | DrawCommand::DrawTextSelection { color, .. }
+            | DrawCommand::DrawShadow { color, .. } => *color = color.faded(opacity),
+            DrawCommand::DrawImage { opacity: image_opacity, .. } => *image_opacity *= opacity,
+            DrawCommand::Clip { .. }
+            | DrawCommand::PopClip
+            | DrawCommand::PushTransform { .. }
+            | DrawCommand::PopTransform
+            | DrawCommand::DrawCanvas { .. }
+            | DrawCommand::DrawScrollbar { .. }
+            | DrawCommand::BackdropBlur { .. }
+            | DrawCommand::Clear { .. }
+            | DrawCommand::BeginLayer { .. }
+            | DrawCommand::EndLayer => {}
+        }
+    }
+
+    /// The same command moved by `(dx, dy)`.
+    pub fn translated(&self, dx: f32, dy: f32) -> DrawCommand {
+        let mut command = self.clone();
+        if let DrawCommand::DrawScrollbar { thumb_rect, .. } = &mut command {
+            thumb_rect.x += dx;
+            thumb_rect.y += dy;
+        }
+        if let DrawCommand::PushTransform { matrix, .. } = &mut command {
+            *matrix = Matrix::translate(dx, dy).multiply(*matrix).multiply(Matrix::translate(-dx, -dy));
+        }
+        let rect = match &mut command {
+            DrawCommand::Clip { rect, .. }
+            | DrawCommand::DrawRect { rect, .. }
+            | DrawCommand::DrawText { rect, .. }
+            | DrawCommand::DrawImage { rect, .. }
+            | DrawCommand::DrawCheckbox { rect, .. }
+            | DrawCommand::DrawSlider { rect, .. }
+            | DrawCommand::DrawProgress { rect, .. }
+            | DrawCommand::DrawCanvas { rect, .. }
+            | DrawCommand::DrawCaret { rect, .. }
+            | DrawCommand::DrawTextSelection { rect, .. }
+            | DrawCommand::DrawScrollbar { rect, .. }
+            | DrawCommand::DrawShadow { rect, .. }
+            | DrawCommand::BackdropBlur { rect, .. }
+            | DrawCommand::PushTransform { rect, .. }
+            | DrawCommand::BeginLayer { rect } => rect,
+            DrawCommand::PopClip | DrawCommand::PopTransform | DrawCommand::Clear { .. } | DrawCommand::EndLayer => return command,
+        };
+        rect.x += dx;
+        rect.y += dy;
+        command
+    }
+}
+
+/// Index into a renderer's fonts for a text `weight` as passed to
+/// `TextMeasurer` and `DrawText`, where bit 0 is bold, bit 1 italic and the
+/// bits above them the font family id, see `FontRegistry`.
+///
+/// Fonts are given in the order regular, bold, italic, bold italic. A face
+/// that is missing falls back to the italic, then the bold, then the regular
+/// one, so a single font serves every variant. Further families follow in
+/// groups of four, as `FontRegistry::fonts` lays them out; a family that
+/// isn't there is drawn in the first one.
+pub fn font_index(weight: u16, font_count: usize) -> usize {
+    let bold = usize::from(weight & 1 != 0);
+    let italic = if weight & 2 != 0 { 2 } else { 0 };
+    let family = usize::from(weight >> 2) * 4;
+    let (first, count) = if family > 0 && family + 4 <= font_count { (family, 4) } else { (0, font_count.min(4)) };
+    first + [italic + bold, italic, bold].into_iter().find(|&index| index < count).unwrap_or(0)
+}
+
+/// Room around a shadow's shape for its blur to fade out: three std-devs,
+/// where the CSS blur radius is two.
+pub fn shadow_margin(blur: f32) -> f32 {
+    (blur * 1.5).ceil()
+}
+
+/// Splits `text` into runs of the font each is drawn in: the face
+/// `font_index`, except for characters `in_face` says it lacks, which take
+/// the font `fallback` gives them, if any. Whitespace stays in the face.
+pub fn font_runs(text: &str, font_index: usize, in_face: impl Fn(char) -> bool, mut fallback: impl FnMut(char) -> Option<usize>) -> Vec<(std::ops::Range<usize>, usize)> {
+    let mut runs = Vec::new();
+    let mut run = (0, font_index);
+    for (i, c) in text.char_indices() {
+        let font = if c.is_whitespace() || in_face(c) { font_index } else { fallback(c).unwrap_or(font_index) };
+        if font != run.1 {
+            if i > run.0 {
+                runs.push((run.0..i, run.1));
+            }
+            run = (i, font);
         }
     }
+    if run.0 < text.len() {
+        runs.push((run.0..text.len(), run.1));
+    }
+    runs
+}
+
+/// The id CSS `font-family` gives the family `name` among `families`, the
+/// lowercase names of those registered after the default one, to carry in a
+/// text `weight` above the bold and italic bits. Matched case-insensitively.
+pub fn font_family_id(families: &[String], name: &str) -> Option<u16> {
+    let name = name.to_lowercase();
+    families.iter().position(|family| *family == name).map(|i| i as u16 + 1)
+}
+
+/// Fonts by family name, for CSS `font-family`. `fonts` lays them out the way
+/// `font_index` reads them, so renderers and measurers take that slice, and
+/// `families` names them for the measurer that resolves `font-family`.
+pub struct FontRegistry<F> {
+    fonts: Vec<F>,
+    families: Vec<String>,
+}
+
+impl<F: Clone> FontRegistry<F> {
+    /// A registry of the default family's faces, given in the order regular,
+    /// bold, italic, bold italic.
+    ///
+    /// # Panics
+    ///
+    /// If `faces` is empty.
+    pub fn new(faces: &[F]) -> Self {
+        Self { fonts: Self::every_variant(faces), families: Vec::new() }
+    }
+
+    /// Adds `family` with `faces` in the same order as `new`, replacing any
+    /// faces registered under that name before. Empty `faces` are ignored.
+    pub fn register(&mut self, family: &str, faces: &[F]) -> &mut Self {
+        if faces.is_empty() {
+            return self;
+        }
+        match font_family_id(&self.families, family).map(usize::from) {
+            Some(id) => {
+                self.fonts.splice(id * 4..(id + 1) * 4, Self::every_variant(faces));
+            }
+            None => {
+                self.families.push(family.to_lowercase());
+                self.fonts.extend(Self::every_variant(faces));
+            }
+        }
+        self
+    }
+
+    /// The id of the registered family `name`, see `font_family_id`.
+    pub fn family_id(&self, name: &str) -> Option<u16> {
+        font_family_id(&self.families, name)
+    }
+
+    /// Lowercase names of the registered families, in id order from 1.
+    pub fn families(&self) -> &[String] {
+        &self.families
+    }
+
+    pub fn fonts(&self) -> &[F] {
+        &self.fonts
+    }
+
+    /// All four faces, the missing ones filled in as `font_index` does.
+    fn every_variant(faces: &[F]) -> Vec<F> {
+        (0..4).map(|variant| faces[font_index(variant, faces.len())].clone()).collect()
+    }
 }
 
 pub trait TextMeasurer {
     fn measure_text(&self, text: &str, font_size: f32, weight: u16) -> (f32, f32);
+
+    /// The id CSS `font-family` gives the family `name`, or `None` if this
+    /// measurer has no fonts by that name; see `FontRegistry`.
+    fn font_family_id(&self, _name: &str) -> Option<u16> {
+        None
+    }
+
+    /// Measures `text` with `word_spacing` extra advance after every space,
+    /// matching how renderers lay out `DrawText` with the same spacing.
+    fn measure_text_spaced(&self, text: &str, font_size: f32, weight: u16, word_spacing: f32) -> (f32, f32) {
+        let (width, height) = self.measure_text(text, font_size, weight);
+        if word_spacing == 0.0 {
+            return (width, height);
+        }
+        let spaces = text.chars().filter(|c| *c == ' ').count() as f32;
+        (width + spaces * word_spacing, height)
+    }
+
+    /// Every character of `text` with the x of its pen position and its
+    /// advance, to place a caret or find the character under a point. The
+    /// default measures each prefix; font-backed measurers read their glyph
+    /// layout instead.
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        let mut x = 0.0;
+        text.char_indices()
+            .map(|(i, c)| {
+                let end = self.measure_text(&text[..i + c.len_utf8()], font_size, weight).0.max(x);
+                let glyph = (c, x, end - x);
+                x = end;
+                glyph
+            })
+            .collect()
+    }
+
+    /// `text` cut short with an ellipsis so that it fits in `max_width`, or
+    /// as it is if it fits already.
+    fn truncate_text<'t>(&self, text: &'t str, font_size: f32, weight: u16, word_spacing: f32, max_width: f32) -> std::borrow::Cow<'t, str> {
+        if self.measure_text_spaced(text, font_size, weight, word_spacing).0 <= max_width {
+            return std::borrow::Cow::Borrowed(text);
+        }
+        let ends: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        let fits = |end: usize| {
+            let cut = format!("{}{}", text[..end].trim_end(), crate::ui::text_wrap::ELLIPSIS);
+            self.measure_text_spaced(&cut, font_size, weight, word_spacing).0 <= max_width
+        };
+        // The longest prefix that still fits with the ellipsis, by bisection.
+        let (mut lo, mut hi) = (0, ends.len().saturating_sub(1));
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if fits(ends[mid]) {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let end = ends.get(lo).copied().unwrap_or(0);
+        std::borrow::Cow::Owned(format!("{}{}", text[..end].trim_end(), crate::ui::text_wrap::ELLIPSIS))
+    }
+
+    /// Measures `text` wrapped at spaces into lines no wider than `max_width`,
+    /// the way layout wraps text nodes: the widest line by the total height.
+    fn measure_text_wrapped(&self, text: &str, font_size: f32, weight: u16, max_width: f32) -> (f32, f32)
+    where
+        Self: Sized,
+    {
+        let mut wrap = crate::ui::TextWrap::new(text, None, font_size, weight, 0.0, self);
+        wrap.break_lines(max_width);
+        (wrap.max_line_width(), wrap.line_height * wrap.lines.len() as f32)
+    }
+}
+
+/// Measures through a reference, e.g. a `&dyn TextMeasurer` chosen at run
+/// time, so layout can be computed with any measurer and no renderer.
+impl<M: TextMeasurer + ?Sized> TextMeasurer for &M {
+    fn measure_text(&self, text: &str, font_size: f32, weight: u16) -> (f32, f32) {
+        (**self).measure_text(text, font_size, weight)
+    }
+
+    fn font_family_id(&self, name: &str) -> Option<u16> {
+        (**self).font_family_id(name)
+    }
+
+    fn measure_text_spaced(&self, text: &str, font_size: f32, weight: u16, word_spacing: f32) -> (f32, f32) {
+        (**self).measure_text_spaced(text, font_size, weight, word_spacing)
+    }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        (**self).text_layout(text, font_size, weight)
+    }
+
+    fn truncate_text<'t>(&self, text: &'t str, font_size: f32, weight: u16, word_spacing: f32, max_width: f32) -> std::borrow::Cow<'t, str> {
+        (**self).truncate_text(text, font_size, weight, word_spacing, max_width)
+    }
+}
+
+impl<M: TextMeasurer + ?Sized> TextMeasurer for Box<M> {
+    fn measure_text(&self, text: &str, font_size: f32, weight: u16) -> (f32, f32) {
+        (**self).measure_text(text, font_size, weight)
+    }
+
+    fn font_family_id(&self, name: &str) -> Option<u16> {
+        (**self).font_family_id(name)
+    }
+
+    fn measure_text_spaced(&self, text: &str, font_size: f32, weight: u16, word_spacing: f32) -> (f32, f32) {
+        (**self).measure_text_spaced(text, font_size, weight, word_spacing)
+    }
+
+    fn text_layout(&self, text: &str, font_size: f32, weight: u16) -> Vec<(char, f32, f32)> {
+        (**self).text_layout(text, font_size, weight)
+    }
+
+    fn truncate_text<'t>(&self, text: &'t str, font_size: f32, weight: u16, word_spacing: f32, max_width: f32) -> std::borrow::Cow<'t, str> {
+        (**self).truncate_text(text, font_size, weight, word_spacing, max_width)
+    }
 }
 
 pub trait Renderer: TextMeasurer {
     fn render(&mut self, commands: &[DrawCommand], canvases: &HashMap<String, Canvas>, dirty_rect: Option<Rect>);
 }
+
+/// Blurs a `width` x `height` block of 4 x 8-bit pixels in place with three
+/// box-blur passes per axis, approximating a Gaussian with std-dev `radius`.
+/// Channel order does not matter; edges are clamped. Shared by the renderers
+/// for `DrawCommand::BackdropBlur`.
+pub fn box_blur(pixels: &mut [u32], width: usize, height: usize, radius: f32) {
+    let r = radius.round() as usize;
+    if r == 0 || width == 0 || height == 0 || pixels.len() < width * height {
+        return;
+    }
+    let pixels = &mut pixels[..width * height];
+    let mut tmp = vec![0u32; width * height];
+    for _ in 0..3 {
+        blur_rows(pixels, &mut tmp, width, r);
+        transpose(&tmp, pixels, width, height);
+        blur_rows(pixels, &mut tmp, height, r);
+        transpose(&tmp, pixels, height, width);
+    }
+}
+
+fn blur_rows(src: &[u32], dst: &mut [u32], width: usize, r: usize) {
+    let window = (2 * r + 1) as u32;
+    for (row, out) in src.chunks_exact(width).zip(dst.chunks_exact_mut(width)) {
+        let at = |i: isize| row[i.clamp(0, width as isize - 1) as usize];
+        let mut sum = [0u32; 4];
+        for i in -(r as isize)..=(r as isize) {
+            let p = at(i);
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s += (p >> (c * 8)) & 0xff;
+            }
+        }
+        for (x, o) in out.iter_mut().enumerate() {
+            *o = sum.iter().enumerate().fold(0, |acc, (c, s)| acc | ((s / window) << (c * 8)));
+            let (leaving, entering) = (at(x as isize - r as isize), at((x + r + 1) as isize));
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s = *s + ((entering >> (c * 8)) & 0xff) - ((leaving >> (c * 8)) & 0xff);
+            }
+        }
+    }
+}
+
+fn transpose(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    for y in 0..height {
+        for x in 0..width {
+            dst[x * height + y] = src[y * width + x];
+        }
+    }
+}
@@ -1,15 +1,27 @@
+//! A CPU-rendered HTML/CSS UI runtime. Each public type is defined once, in
+//! the module that owns it, and re-exported here; `xerune::Color` and
+//! `xerune::graphics::Color` name the same type.
+
 pub mod graphics;
 pub mod style;
 pub mod model;
 pub mod ui;
 pub mod runtime;
+pub mod error;
 
 pub mod css;
 pub mod defaults;
+#[cfg(feature = "fontdue")]
+pub mod fonts;
+#[cfg(feature = "image-decode")]
+pub mod decode;
 
-pub use graphics::{Color, LinearGradient, Rect, Canvas, Context, DrawCommand, TextMeasurer, Renderer};
-pub use style::{Overflow, ContainerStyle, RenderData, Display, TextAlign, Direction, WritingMode, FlexDirection, FlexWrap, AlignContent, AlignItems, MyJustifyContent, Position, BoxSizing};
-pub use model::{Model, InputEvent};
-pub use ui::{Interaction, Ui, TemplateLayout, UiBuilder};
-pub use runtime::Runtime;
+pub use graphics::{BackgroundSize, Color, Gradient, LinearGradient, Matrix, RadialGradient, Rect, Canvas, Context, DrawCommand, Orientation, TextMeasurer, Renderer, box_blur, font_family_id, font_index, font_runs, FontRegistry};
+pub use style::{Overflow, ContainerStyle, RenderData, Display, TextAlign, Direction, WritingMode, FlexDirection, FlexWrap, AlignContent, AlignItems, MyJustifyContent, Position, BoxSizing, OriginOffset, BorderStyle, BoxShadow, WhiteSpace, ListStyle, Cursor, LineHeight};
+pub use model::{Model, InputEvent, Subscription};
+pub use ui::{Interaction, Ui, TemplateLayout, UiBuilder, AccessNode, NodeKey};
+#[cfg(feature = "dynamic-parser")]
+pub use ui::measure_fragment;
+pub use runtime::{Command, Runtime};
+pub use error::Error;
 pub use xerune_derive::XeruneTemplate;
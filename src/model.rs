@@ -1,19 +1,53 @@
 use crate::graphics::Context;
+use crate::runtime::Command;
 
 pub trait Model {
     type Message: std::str::FromStr + Send + Sync + 'static;
     fn view(&self) -> String {
         String::new()
     }
-    fn update(&mut self, msg: Self::Message, context: &mut Context);
+    /// Applies `msg` to the model. Work that should not block the UI, such as
+    /// loading files, is returned as a `Command`; most updates return
+    /// `Command::none()`.
+    fn update(&mut self, msg: Self::Message, context: &mut Context) -> Command<Self::Message>;
+    /// Whether a click that sent `msg` goes on to the `data-on-click` of the
+    /// enclosing element, up to one marked `data-stop-propagation`. By
+    /// default the innermost handler handles the click.
+    fn bubbles(&self, _msg: &Self::Message) -> bool {
+        false
+    }
+    /// Timers the runtime should keep running, checked again after every
+    /// update. An empty list, the default, leaves the app idle.
+    fn subscriptions(&self) -> Vec<Subscription> {
+        Vec::new()
+    }
+}
+
+/// A recurring message the runtime delivers for as long as `Model::subscriptions`
+/// lists it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Subscription {
+    /// Delivers the message string, parsed like any interaction, every interval.
+    Interval(std::time::Duration, String),
 }
 
 pub enum InputEvent {
     Click { x: f32, y: f32 },
     Hover { x: f32, y: f32 },
+    /// Primary button or touch pressed/released. A press on an interactive node
+    /// captures the pointer until the release. If the node has
+    /// `data-on-drag="<message>"`, `Hover` moves are reported to it as
+    /// `drag:<message>:<fx>:<fy>`, even outside its bounds; a slider with an
+    /// `id` reports its new value as `slider:<id>:<value>`.
+    PointerDown { x: f32, y: f32 },
+    PointerUp { x: f32, y: f32 },
     Scroll { x: f32, y: f32, delta_x: f32, delta_y: f32 },
     KeyDown(String),
     KeyUp(String),
     Message(String),
     TextInput { id: String, text: String },
+    /// Touch points are identified by `id` from `TouchStart` until `TouchEnd`.
+    TouchStart { id: u64, x: f32, y: f32 },
+    TouchMove { id: u64, x: f32, y: f32 },
+    TouchEnd { id: u64, x: f32, y: f32 },
 }
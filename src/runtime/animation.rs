@@ -1,4 +1,5 @@
 use taffy::prelude::*;
+use crate::graphics::{Color, Matrix};
 use crate::style::{ContainerStyle, AnimationIterationCount};
 
 #[derive(Clone, Debug)]
@@ -16,6 +17,91 @@ pub struct ActiveAnimation {
     pub is_finished: bool,
 }
 
+/// The properties `transition` can ease, and that `transition: all` covers.
+pub(crate) const TRANSITIONABLE: [&str; 5] = ["color", "background-color", "border-color", "opacity", "transform"];
+
+/// A value of one of the `TRANSITIONABLE` properties.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionValue {
+    Color(Option<Color>),
+    Opacity(f32),
+    Transform(Option<Matrix>),
+}
+
+impl TransitionValue {
+    pub(crate) fn read(property: &str, style: &ContainerStyle) -> Option<Self> {
+        match property {
+            "color" => Some(Self::Color(Some(style.color))),
+            "background-color" => Some(Self::Color(style.background_color)),
+            "border-color" => Some(Self::Color(style.border_color)),
+            "opacity" => Some(Self::Opacity(style.opacity)),
+            "transform" => Some(Self::Transform(style.transform)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write(self, property: &str, style: &mut ContainerStyle) {
+        match (property, self) {
+            ("color", Self::Color(Some(color))) => style.color = color,
+            ("background-color", Self::Color(color)) => style.background_color = color,
+            ("border-color", Self::Color(color)) => style.border_color = color,
+            ("opacity", Self::Opacity(opacity)) => style.opacity = opacity,
+            ("transform", Self::Transform(matrix)) => style.transform = matrix,
+            _ => {}
+        }
+    }
+
+    /// The value `t` of the way to `to`. A missing color fades from or to its
+    /// counterpart made transparent, and a missing transform is the identity;
+    /// matrices are interpolated entry by entry.
+    fn lerp(self, to: Self, t: f32) -> Self {
+        let clear = |color: Color| Color { a: 0, ..color };
+        match (self, to) {
+            (Self::Color(Some(c1)), Self::Color(Some(c2))) => Self::Color(Some(interpolate_color(c1, c2, t))),
+            (Self::Color(Some(c1)), Self::Color(None)) => Self::Color(Some(interpolate_color(c1, clear(c1), t))),
+            (Self::Color(None), Self::Color(Some(c2))) => Self::Color(Some(interpolate_color(clear(c2), c2, t))),
+            (Self::Opacity(o1), Self::Opacity(o2)) => Self::Opacity(interpolate_f32(o1, o2, t)),
+            (Self::Transform(m1), Self::Transform(m2)) => {
+                let (m1, m2) = (m1.unwrap_or(Matrix::IDENTITY), m2.unwrap_or(Matrix::IDENTITY));
+                Self::Transform(Some(Matrix {
+                    a: interpolate_f32(m1.a, m2.a, t),
+                    b: interpolate_f32(m1.b, m2.b, t),
+                    c: interpolate_f32(m1.c, m2.c, t),
+                    d: interpolate_f32(m1.d, m2.d, t),
+                    e: interpolate_f32(m1.e, m2.e, t),
+                    f: interpolate_f32(m1.f, m2.f, t),
+                }))
+            }
+            _ => to,
+        }
+    }
+}
+
+/// A transitioned property easing from the value shown when it changed to
+/// the value of the latest build.
+#[derive(Clone, Debug)]
+pub struct ActiveTransition {
+    pub property: std::sync::Arc<str>,
+    pub from: TransitionValue,
+    pub to: TransitionValue,
+    pub duration: f32,
+    pub timing_function: std::sync::Arc<str>,
+    pub delay: f32,
+    pub started: std::time::Instant,
+}
+
+impl ActiveTransition {
+    /// The value to show at `now`, and whether the transition has finished.
+    pub(crate) fn value_at(&self, now: std::time::Instant) -> (TransitionValue, bool) {
+        let elapsed_sec = now.saturating_duration_since(self.started).as_secs_f32() - self.delay;
+        let progress = (elapsed_sec / self.duration.max(0.001)).clamp(0.0, 1.0);
+        if progress >= 1.0 {
+            return (self.to, true);
+        }
+        (self.from.lerp(self.to, ease(progress, &self.timing_function)), false)
+    }
+}
+
 pub(crate) fn ease(t: f32, func: &str) -> f32 {
     match func {
         "linear" => t,
@@ -72,8 +158,8 @@ fn sample_curve_derivative_x(x1: f32, x2: f32, t: f32) -> f32 {
     3.0 * tm * tm * x1 + 6.0 * tm * t * (x2 - x1) + 3.0 * t * t * (1.0 - x2)
 }
 
-fn interpolate_color(c1: crate::graphics::Color, c2: crate::graphics::Color, t: f32) -> crate::graphics::Color {
-    crate::graphics::Color {
+fn interpolate_color(c1: Color, c2: Color, t: f32) -> Color {
+    Color {
         r: ((1.0 - t) * c1.r as f32 + t * c2.r as f32).round() as u8,
         g: ((1.0 - t) * c1.g as f32 + t * c2.g as f32).round() as u8,
         b: ((1.0 - t) * c1.b as f32 + t * c2.b as f32).round() as u8,
@@ -135,13 +221,17 @@ pub(crate) fn interpolate_property(
     style: &mut ContainerStyle,
     taffy_style: &mut Style,
 ) {
-    if ["color", "background-color", "border-color"].contains(&prop) {
+    if ["color", "background-color", "border-color", "border-top-color", "border-right-color", "border-bottom-color", "border-left-color"].contains(&prop) {
         if let (Some(c1), Some(c2)) = (crate::css::parse_hex_color(val1), crate::css::parse_hex_color(val2)) {
             let interpolated = interpolate_color(c1, c2, t);
             match prop {
                 "color" => style.color = interpolated,
                 "background-color" => style.background_color = Some(interpolated),
                 "border-color" => style.border_color = Some(interpolated),
+                "border-top-color" => style.border_side_colors[0] = Some(interpolated),
+                "border-right-color" => style.border_side_colors[1] = Some(interpolated),
+                "border-bottom-color" => style.border_side_colors[2] = Some(interpolated),
+                "border-left-color" => style.border_side_colors[3] = Some(interpolated),
                 _ => {}
             }
         }
@@ -0,0 +1,57 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Poll, Wake, Waker};
+use std::thread::{self, JoinHandle, Thread};
+
+type Task<M> = Box<dyn FnOnce() -> M + Send>;
+
+/// Work returned from `Model::update` for the runtime to carry out off the UI
+/// thread. Each task's message is delivered to `update` once it is done.
+pub struct Command<M> {
+    tasks: Vec<Task<M>>,
+}
+
+impl<M: Send + 'static> Command<M> {
+    pub fn none() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Runs `future` to completion on its own thread and turns its output
+    /// into a message with `map`.
+    pub fn perform<T>(future: impl Future<Output = T> + Send + 'static, map: impl FnOnce(T) -> M + Send + 'static) -> Self {
+        Self { tasks: vec![Box::new(move || map(block_on(future)))] }
+    }
+
+    pub fn batch(commands: impl IntoIterator<Item = Self>) -> Self {
+        Self { tasks: commands.into_iter().flat_map(|command| command.tasks).collect() }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub(crate) fn spawn(self) -> impl Iterator<Item = JoinHandle<M>> {
+        self.tasks.into_iter().map(thread::spawn)
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Polls `future` on the current thread, parking it while the future waits.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+/// Tracks active touch points and recognizes a two-finger pinch.
+#[derive(Clone, Debug, Default)]
+pub struct PinchRecognizer {
+    touches: Vec<(u64, f32, f32)>,
+    start_distance: Option<f32>,
+}
+
+impl PinchRecognizer {
+    pub fn touch_start(&mut self, id: u64, x: f32, y: f32) {
+        self.touches.retain(|&(t, _, _)| t != id);
+        self.touches.push((id, x, y));
+        self.start_distance = self.distance();
+    }
+
+    /// Updates a touch point and, while exactly two fingers are down, returns
+    /// `(scale, center_x, center_y)` where `scale` is relative to the distance
+    /// between the fingers when the second one touched down.
+    pub fn touch_move(&mut self, id: u64, x: f32, y: f32) -> Option<(f32, f32, f32)> {
+        let touch = self.touches.iter_mut().find(|(t, _, _)| *t == id)?;
+        touch.1 = x;
+        touch.2 = y;
+
+        let start = self.start_distance?;
+        let current = self.distance()?;
+        let [(_, x1, y1), (_, x2, y2)] = self.touches[..] else {
+            return None;
+        };
+        Some((current / start, (x1 + x2) / 2.0, (y1 + y2) / 2.0))
+    }
+
+    pub fn touch_end(&mut self, id: u64) {
+        self.touches.retain(|&(t, _, _)| t != id);
+        self.start_distance = self.distance();
+    }
+
+    fn distance(&self) -> Option<f32> {
+        let [(_, x1, y1), (_, x2, y2)] = self.touches[..] else {
+            return None;
+        };
+        let d = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+        (d > 0.0).then_some(d)
+    }
+}
@@ -1,9 +1,14 @@
 pub mod timer;
 pub mod animation;
+pub mod gesture;
+pub mod command;
 
 pub use timer::{Timer, TickResult};
-pub use animation::ActiveAnimation;
+pub use command::Command;
+pub use animation::{ActiveAnimation, ActiveTransition, TransitionValue};
+pub use gesture::PinchRecognizer;
 
+use std::borrow::Cow;
 use std::str::FromStr;
 use taffy::prelude::*;
 use std::collections::HashMap;
@@ -16,12 +21,19 @@ macro_rules! profile {
     ($($tt:tt)*) => {};
 }
 
-use crate::graphics::{Context, DrawCommand, Rect, TextMeasurer, Renderer};
+use crate::graphics::{Color, Context, DrawCommand, Matrix, Rect, TextMeasurer, Renderer};
 use crate::style::{ContainerStyle, RenderData, AnimationIterationCount};
-use crate::model::{InputEvent, Model};
+use crate::model::{InputEvent, Model, Subscription};
 use crate::ui::{Ui, NodeMap};
+use crate::error::Error;
 
-pub struct Runtime<M, R> {
+type InteractionHook = Box<dyn FnMut(&mut String) -> bool + Send>;
+
+const CARET_BLINK: std::time::Duration = std::time::Duration::from_millis(530);
+/// How often `tick` checks on running commands.
+const COMMAND_POLL: std::time::Duration = std::time::Duration::from_millis(16);
+
+pub struct Runtime<M: Model, R> {
     model: M,
     measurer: R,
     pub ui: Ui,
@@ -30,24 +42,72 @@ pub struct Runtime<M, R> {
     cached_size: Size<AvailableSpace>,
     context: Context,
     last_commands: Vec<DrawCommand>,
+    /// `Ui::focus_key` of the focused node, to focus it again after a rebuild.
     pub focused_id: Option<String>,
+    /// Whether a Shift key is down, which makes Tab move focus backwards.
+    shift_held: bool,
+    /// When the caret of the focused input started blinking: on focus and on
+    /// every edit, so that it stays visible while typing.
+    caret_since: std::time::Instant,
     pub target_fps: u32,
+    /// When set, clicks pass through text and unpainted containers that have no
+    /// interaction of their own (see `Ui::hit_test_opaque`).
+    pub transparent_hit_test: bool,
+    /// Color `render` clears the target to when the document sets no
+    /// background of its own; `None` leaves the target as the host left it.
+    pub clear_color: Option<Color>,
+    /// Outline and label every node's box; see `set_debug`.
+    debug: bool,
+    redraw_pending: bool,
+    pinch: PinchRecognizer,
+    captured: Option<NodeId>,
+    /// What finds the captured node again once the tree is rebuilt.
+    captured_key: Option<crate::ui::NodeKey>,
+    /// Last `Hover` position, to hover the same node again after a rebuild.
+    pointer: Option<(f32, f32)>,
+    /// Inside `handle_events`: whether a message has changed the model since the last rebuild.
+    deferred_sync: Option<bool>,
+    interaction_hook: Option<InteractionHook>,
     pub(crate) timers: Vec<Timer>,
     next_timer_id: usize,
+    /// The model's current subscriptions and the ids of the timers running them.
+    subscriptions: Vec<(Subscription, usize)>,
+    /// Threads running the commands returned from `update`.
+    commands: Vec<std::thread::JoinHandle<M::Message>>,
     pub(crate) active_animations: HashMap<NodeId, ActiveAnimation>,
     last_tick_time: std::time::Instant,
+    /// Running transitions by element `id`, which stays the same across rebuilds.
+    transitions: HashMap<String, Vec<ActiveTransition>>,
+    /// The last built style of every element with a `transition`, by `id`.
+    transition_targets: HashMap<String, ContainerStyle>,
 }
 
 impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
+    /// Builds the runtime's first view of `model`.
+    ///
+    /// # Panics
+    ///
+    /// If the view can't be built; use [`Runtime::try_new`] to handle that.
     pub fn new(model: M, measurer: R) -> Self {
+        Self::try_new(model, measurer).unwrap()
+    }
+
+    /// Builds the runtime's first view of `model`, returning an error instead
+    /// of panicking if the view can't be built.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::EmptyDocument`] if the document has no root element, and
+    /// [`Error::Layout`] if Taffy rejects the tree the template builds.
+    pub fn try_new(model: M, measurer: R) -> Result<Self, Error> {
          let default_style = ContainerStyle::default();
          let validator = |s: &str| M::Message::from_str(s).is_ok();
-         let ui = Ui::new_compiled(&model, &measurer, default_style.clone(), &validator).unwrap();
+         let ui = Ui::new_compiled(&model, &measurer, default_style.clone(), &validator)?;
          
          let mut context = Context::new();
          Runtime::<M, R>::sync_canvases(&ui, &mut context);
 
-         Self {
+         let mut runtime = Self {
              model,
              measurer,
              ui,
@@ -57,12 +117,31 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
              context,
              last_commands: Vec::new(),
              focused_id: None,
+             shift_held: false,
+             caret_since: std::time::Instant::now(),
              target_fps: 60,
+             transparent_hit_test: false,
+             clear_color: None,
+             debug: false,
+             redraw_pending: true,
+             pinch: PinchRecognizer::default(),
+             captured: None,
+             captured_key: None,
+             pointer: None,
+             deferred_sync: None,
+             interaction_hook: None,
              timers: Vec::new(),
              next_timer_id: 1,
+             subscriptions: Vec::new(),
+             commands: Vec::new(),
              active_animations: HashMap::new(),
              last_tick_time: std::time::Instant::now(),
-         }
+             transitions: HashMap::new(),
+             transition_targets: HashMap::new(),
+         };
+         runtime.sync_subscriptions();
+         runtime.sync_transitions();
+         Ok(runtime)
     }
 
     fn sync_canvases(ui: &Ui, context: &mut Context) {
@@ -88,6 +167,15 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
                                   height = val as u32;
                              }
                          }
+
+                         // `aspect-ratio` derives the side left `auto` from the other.
+                         if let Some(ratio) = style.aspect_ratio {
+                             if h_dim.is_auto() && !w_dim.is_auto() {
+                                 height = (width as f32 / ratio).round() as u32;
+                             } else if w_dim.is_auto() && !h_dim.is_auto() {
+                                 width = (height as f32 * ratio).round() as u32;
+                             }
+                         }
                      }
 
                      context.canvases.insert(id.clone(), crate::graphics::Canvas::new(width, height));
@@ -100,66 +188,365 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
         self.ui.scroll_offsets = self.scroll_offsets.clone();
     }
 
+    /// Returns whether this event changed anything on screen. The same state
+    /// is accumulated for `needs_redraw`, so hosts may ignore the return value.
     pub fn handle_event(&mut self, event: InputEvent) -> bool {
+        let dirty = self.dispatch_event(event);
+        self.redraw_pending |= dirty;
+        dirty
+    }
+
+    /// Handles a batch of events, rebuilding the view once at the end instead
+    /// of after every message. A click, hover or scroll in the batch first
+    /// brings the view up to date so that it hits what is on screen.
+    pub fn handle_events(&mut self, events: impl IntoIterator<Item = InputEvent>) -> bool {
+        let mut dirty = false;
+        self.deferred_sync = Some(false);
+        for event in events {
+            let hit_tests = matches!(event, InputEvent::Click { .. } | InputEvent::Hover { .. } | InputEvent::Scroll { .. } | InputEvent::PointerDown { .. });
+            if hit_tests && self.deferred_sync == Some(true) {
+                self.deferred_sync = Some(false);
+                dirty |= self.sync_view();
+            }
+            dirty |= self.dispatch_event(event);
+        }
+        if self.deferred_sync.take() == Some(true) {
+            dirty |= self.sync_view();
+        }
+        self.redraw_pending |= dirty;
+        dirty
+    }
+
+    /// True when anything has changed since the last `render`: an event or
+    /// message, a timer or animation step from `tick`, a new available space,
+    /// or a dirty canvas.
+    pub fn needs_redraw(&self) -> bool {
+        self.redraw_pending || self.context.canvases.values().any(|c| c.dirty)
+    }
+
+    fn dispatch_event(&mut self, event: InputEvent) -> bool {
         match event {
             InputEvent::Click { x, y } => {
-                if let Some((msg_str, clicked_node)) = self.ui.hit_test(x, y) {
-                    if let Some(RenderData::TextInput(id, _, _)) = self.ui.render_data.get(&clicked_node) {
-                        if !id.is_empty() {
-                            self.focused_id = Some(id.clone());
-                        } else {
-                            self.focused_id = None;
-                        }
-                    } else {
-                        self.focused_id = None;
+                let hit = if self.transparent_hit_test {
+                    self.ui.hit_test_opaque(x, y)
+                } else {
+                    self.ui.hit_test(x, y)
+                };
+                // While a select is open, a click picks one of its options or closes it.
+                if let Some((node, _)) = self.ui.open_select {
+                    let option = self.ui.select_option_at(x, y);
+                    self.ui.open_select = None;
+                    return match option {
+                        Some(index) => self.pick_option(node, index),
+                        None => true,
+                    };
+                }
+                let old_focus = self.ui.focused;
+                if let Some((msg_str, clicked_node)) = hit {
+                    // Clicking inside a focusable element, e.g. on a button's label, focuses it.
+                    let focus = std::iter::successors(Some(clicked_node), |&node| self.ui.taffy.parent(node))
+                        .find(|node| self.ui.focusable.contains(node));
+                    self.set_focus(focus);
+
+                    if let Some(select) = std::iter::successors(Some(clicked_node), |&node| self.ui.taffy.parent(node))
+                        .find(|&node| matches!(self.ui.render_data.get(node), Some(RenderData::Select(..))))
+                    {
+                        self.ui.open_select(select);
+                        return true;
                     }
-                    
+
                     if !msg_str.is_empty() {
-                        return self.process_message_str(&msg_str) || self.focused_id.is_some();
+                        let path = self.ui.interaction_path(clicked_node);
+                        return self.bubble_click(&path) || focus.is_some() || old_focus.is_some();
                     }
-                    return self.focused_id.is_some();
+                    return focus.is_some() || old_focus.is_some();
                 }
                 
-                let old_focus = self.focused_id.take();
+                self.set_focus(None);
                 return old_focus.is_some();
             }
             InputEvent::Message(msg_str) => {
                 self.process_message_str(&msg_str)
             }
             InputEvent::Scroll { x, y, delta_x, delta_y } => {
-                if self.ui.handle_scroll(x, y, delta_x, delta_y) {
-                    self.scroll_offsets = self.ui.scroll_offsets.clone();
-                    return true;
+                let Some(node) = self.ui.scroll_at(x, y, delta_x, delta_y) else {
+                    return false;
+                };
+                let previous = self.scroll_offsets.get(node).copied().unwrap_or((0.0, 0.0));
+                self.scroll_offsets = self.ui.scroll_offsets.clone();
+                // Containers with `data-on-scroll="<id>"` report each new offset as `scroll:<id>:<y>`.
+                let current = self.scroll_offsets.get(node).copied().unwrap_or((0.0, 0.0));
+                if current != previous
+                    && let Some(id) = self.ui.scroll_listeners.get(node).cloned()
+                {
+                    self.process_message_str(&format!("scroll:{}:{}", id, current.1));
                 }
-                false
+                true
             }
             InputEvent::KeyDown(key) => {
+                if key == "ShiftLeft" || key == "ShiftRight" {
+                    self.shift_held = true;
+                }
+                if key == "Tab" && !self.ui.focusable.is_empty() {
+                    let focus = self.ui.focus_next(self.shift_held);
+                    self.set_focus(focus);
+                    return true;
+                }
+                if let Some(handled) = self.select_key(&key) {
+                    return handled;
+                }
+                if let Some(handled) = self.row_key(&key) {
+                    return handled;
+                }
+                if key == "Backspace"
+                    && let Some(updated) = self.edit_focused_input(|value| {
+                        value.pop();
+                    })
+                {
+                    return updated;
+                }
                 let msg_str = format!("keydown:{}", key);
                 self.process_message_str(&msg_str)
             }
             InputEvent::KeyUp(key) => {
+                if key == "ShiftLeft" || key == "ShiftRight" {
+                    self.shift_held = false;
+                }
                 let msg_str = format!("keyup:{}", key);
                 self.process_message_str(&msg_str)
             }
             InputEvent::TextInput { id: event_id, text } => {
-                if let Some(ref focused) = self.focused_id {
-                    if event_id.is_empty() || &event_id == focused {
-                        let msg_str = format!("{}:text:{}", focused, text);
-                        return self.process_message_str(&msg_str);
-                    }
+                let Some(focused) = self.focused_input_id() else {
+                    return false;
+                };
+                if !event_id.is_empty() && event_id != focused {
+                    return false;
+                }
+                let msg_str = format!("{}:text:{}", focused, text);
+                let typed: String = text.chars().filter(|c| !c.is_control()).collect();
+                if let Some(updated) = self.edit_focused_input(|value| value.push_str(&typed)) {
+                    return updated;
                 }
+                self.process_message_str(&msg_str)
+            }
+            InputEvent::TouchStart { id, x, y } => {
+                self.pinch.touch_start(id, x, y);
                 false
             }
-            _ => false
+            InputEvent::TouchMove { id, x, y } => {
+                // Two-finger moves are reported to the model as `pinch:<scale>:<cx>:<cy>`.
+                match self.pinch.touch_move(id, x, y) {
+                    Some((scale, cx, cy)) => self.process_message_str(&format!("pinch:{}:{}:{}", scale, cx, cy)),
+                    None => false,
+                }
+            }
+            InputEvent::TouchEnd { id, .. } => {
+                self.pinch.touch_end(id);
+                false
+            }
+            InputEvent::PointerDown { x, y } => {
+                // Nothing inside a disabled element takes the pointer.
+                self.captured = self.ui.hit_test_stack(x, y).into_iter().rev()
+                    .take_while(|node| !self.ui.disabled.contains_key(*node))
+                    .find(|node| {
+                        self.ui.interactions.contains_key(node)
+                            || self.ui.drag_listeners.contains_key(node)
+                            || (self.ui.ids.contains_key(node) && matches!(self.ui.render_data.get(*node), Some(RenderData::Slider(..))))
+                    });
+                self.captured_key = self.captured.and_then(|node| self.ui.node_key(node));
+                let pressed = self.ui.set_pressed(self.captured);
+                self.drag_captured(x, y) || pressed
+            }
+            InputEvent::Hover { x, y } => {
+                self.pointer = Some((x, y));
+                let dragged = self.drag_captured(x, y);
+                self.ui.update_hover(x, y) || dragged
+            }
+            InputEvent::PointerUp { .. } => {
+                self.captured = None;
+                self.captured_key = None;
+                self.ui.set_pressed(None)
+            }
+        }
+    }
+
+    /// Focuses the focusable node whose `Ui::focus_key` is `interaction_id`:
+    /// the id of a text input or the interaction of e.g. a button. Returns
+    /// false if there is no such node.
+    pub fn focus(&mut self, interaction_id: &str) -> bool {
+        if !self.ui.focus(interaction_id) {
+            return false;
+        }
+        self.focused_id = Some(interaction_id.to_string());
+        self.restart_caret();
+        self.redraw_pending = true;
+        true
+    }
+
+    fn set_focus(&mut self, node: Option<NodeId>) {
+        self.ui.focused = node;
+        self.focused_id = node.and_then(|node| self.ui.focus_key(node)).map(str::to_string);
+        self.restart_caret();
+    }
+
+    fn focused_input_id(&self) -> Option<String> {
+        match self.ui.render_data.get(self.ui.focused?) {
+            Some(RenderData::TextInput(id, _, _)) => Some(id.clone()),
+            _ => None,
+        }
+    }
+
+    /// Applies `edit` to the value of the focused text input and, if it has
+    /// `data-on-input="<id>"`, reports the new value as `input:<id>:<value>`.
+    /// Returns `None` when the focused input (if any) has no `data-on-input`.
+    fn edit_focused_input(&mut self, edit: impl FnOnce(&mut String)) -> Option<bool> {
+        let node = self.ui.focused?;
+        let Some(RenderData::TextInput(_, text, _)) = self.ui.render_data.get(node) else {
+            return None;
+        };
+        let listener = self.ui.input_listeners.get(node)?.clone();
+        let mut value = text.clone().unwrap_or_default();
+        edit(&mut value);
+        self.restart_caret();
+        Some(self.process_message_str(&format!("input:{}:{}", listener, value)))
+    }
+
+    /// Arrow keys move through the options of the open select, Enter picks
+    /// one and Escape closes the list; Enter on a focused select opens it.
+    /// Returns `None` for keys a select does not take.
+    fn select_key(&mut self, key: &str) -> Option<bool> {
+        let Some((node, highlighted)) = self.ui.open_select else {
+            let node = self.ui.focused.filter(|&node| matches!(self.ui.render_data.get(node), Some(RenderData::Select(..))))?;
+            if key != "Enter" {
+                return None;
+            }
+            self.ui.open_select(node);
+            return Some(true);
+        };
+        let Some(RenderData::Select(_, options, _, _)) = self.ui.render_data.get(node) else {
+            return None;
+        };
+        let last = options.len().saturating_sub(1);
+        match key {
+            "ArrowDown" => self.ui.open_select = Some((node, (highlighted + 1).min(last))),
+            "ArrowUp" => self.ui.open_select = Some((node, highlighted.saturating_sub(1))),
+            "Enter" => {
+                self.ui.open_select = None;
+                return Some(self.pick_option(node, highlighted));
+            }
+            "Escape" => self.ui.open_select = None,
+            _ => return None,
+        }
+        Some(true)
+    }
+
+    /// Once a row of a list has focus, arrow keys move it between the rows,
+    /// marking the new row active and scrolling it into view, and Enter
+    /// clicks the focused row. Returns `None` for other keys, or when no row
+    /// is focused.
+    fn row_key(&mut self, key: &str) -> Option<bool> {
+        match key {
+            "ArrowDown" | "ArrowUp" => {
+                let row = self.ui.step_row(key == "ArrowUp")?;
+                self.set_focus(Some(row));
+                if let Some(interaction) = self.ui.interactions.get(row).cloned() {
+                    self.scroll_into_view(&interaction);
+                }
+                Some(true)
+            }
+            "Enter" => {
+                let row = self.ui.focused.filter(|&node| self.ui.is_row(node))?;
+                let path = self.ui.interaction_path(row);
+                self.bubble_click(&path);
+                Some(true)
+            }
+            _ => None,
         }
     }
 
+    /// Selects option `index` of the select `node` and reports it to the model
+    /// as `select:<id>:<value>`. The selection is shown right away, before the
+    /// model rebuilds the view.
+    fn pick_option(&mut self, node: NodeId, index: usize) -> bool {
+        let Some(RenderData::Select(id, options, selected, _)) = self.ui.render_data.get_mut(node) else {
+            return true;
+        };
+        let Some((value, _)) = options.get(index) else {
+            return true;
+        };
+        selected.clone_from(value);
+        let msg_str = format!("select:{}:{}", id, value);
+        self.process_message_str(&msg_str);
+        true
+    }
+
+    fn restart_caret(&mut self) {
+        self.caret_since = std::time::Instant::now();
+        self.ui.caret_visible = true;
+    }
+
+    /// The cursor for the window to show at the last hovered position, from
+    /// the `cursor` style of the node under it.
+    pub fn cursor(&self) -> crate::style::Cursor {
+        self.pointer.map(|(x, y)| self.ui.cursor_at(x, y)).unwrap_or_default()
+    }
+
+    /// The node holding pointer capture between `PointerDown` and `PointerUp`.
+    pub fn captured_node(&self) -> Option<NodeId> {
+        self.captured
+    }
+
+    /// Reports the pointer position to a captured node with `data-on-drag`
+    /// as fractions of its bounds, e.g. `drag:volume:0.25:0.5`. Fractions
+    /// fall outside 0..1 once the pointer leaves the node.
+    ///
+    /// A captured slider instead takes the value under the pointer, clamped
+    /// to 0..1, and reports it as `slider:<id>:<value>` with its `id`
+    /// attribute, as a text input reports edits. Its thumb follows the
+    /// pointer right away, before the model answers.
+    fn drag_captured(&mut self, x: f32, y: f32) -> bool {
+        let Some((node, bounds)) = self.captured.and_then(|node| Some((node, self.ui.node_bounds(node)?))) else {
+            return false;
+        };
+        let fx = if bounds.width > 0.0 { (x - bounds.x) / bounds.width } else { 0.0 };
+        let fy = if bounds.height > 0.0 { (y - bounds.y) / bounds.height } else { 0.0 };
+        if let Some(id) = self.ui.ids.get(node).cloned()
+            && let Some(RenderData::Slider(value, _)) = self.ui.render_data.get_mut(node)
+        {
+            let new_value = fx.clamp(0.0, 1.0);
+            let moved = *value != new_value;
+            *value = new_value;
+            return self.process_message_str(&format!("slider:{}:{}", id, new_value)) || moved;
+        }
+        match self.ui.drag_listeners.get(node).cloned() {
+            Some(message) => self.process_message_str(&format!("drag:{}:{}:{}", message, fx, fy)),
+            None => false,
+        }
+    }
+
+    /// Calls `hook` with every interaction string before it is parsed into a
+    /// message, e.g. to log it. The hook may rewrite the string in place;
+    /// returning false swallows the interaction.
+    pub fn on_interaction(&mut self, hook: impl FnMut(&mut String) -> bool + Send + 'static) {
+        self.interaction_hook = Some(Box::new(hook));
+    }
+
+    fn intercept<'s>(&mut self, msg_str: &'s str) -> Option<Cow<'s, str>> {
+        let Some(hook) = self.interaction_hook.as_mut() else {
+            return Some(Cow::Borrowed(msg_str));
+        };
+        let mut msg_str = msg_str.to_string();
+        hook(&mut msg_str).then_some(Cow::Owned(msg_str))
+    }
+
     pub fn handle_messages(&mut self, messages: impl IntoIterator<Item = String>) -> bool {
         let mut any_update = false;
         for msg_str in messages {
+            let Some(msg_str) = self.intercept(&msg_str) else {
+                continue;
+            };
             if let Ok(msg) = M::Message::from_str(&msg_str) {
-                profile!("update");
-                self.model.update(msg, &mut self.context);
+                self.update_model(msg);
                 any_update = true;
             }
         }
@@ -170,10 +557,57 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
         }
     }
 
+    /// Passes `msg` to the model and starts the command it returns.
+    fn update_model(&mut self, msg: M::Message) {
+        profile!("update");
+        let command = self.model.update(msg, &mut self.context);
+        self.commands.extend(command.spawn());
+    }
+
+    /// Delivers the messages of finished commands to the model.
+    fn finish_commands(&mut self) -> bool {
+        if !self.commands.iter().any(|handle| handle.is_finished()) {
+            return false;
+        }
+        let (finished, running) = std::mem::take(&mut self.commands).into_iter().partition(|handle| handle.is_finished());
+        self.commands = running;
+        let mut any_update = false;
+        for handle in finished {
+            match handle.join() {
+                Ok(msg) => {
+                    self.update_model(msg);
+                    any_update = true;
+                }
+                Err(_) => log::error!("A command panicked; its message is dropped"),
+            }
+        }
+        any_update && self.sync_view()
+    }
+
+    /// Sends a click's messages innermost first, moving on to the next only
+    /// while `Model::bubbles` says the last one left the click unhandled.
+    fn bubble_click(&mut self, path: &[String]) -> bool {
+        let mut changed = false;
+        for msg_str in path {
+            let bubbles = M::Message::from_str(msg_str).is_ok_and(|msg| self.model.bubbles(&msg));
+            changed |= self.process_message_str(msg_str);
+            if !bubbles {
+                break;
+            }
+        }
+        changed
+    }
+
     fn process_message_str(&mut self, msg_str: &str) -> bool {
-        if let Ok(msg) = M::Message::from_str(msg_str) {
-            profile!("update");
-            self.model.update(msg, &mut self.context);
+        let Some(msg_str) = self.intercept(msg_str) else {
+            return false;
+        };
+        if let Ok(msg) = M::Message::from_str(&msg_str) {
+            self.update_model(msg);
+            if let Some(pending) = self.deferred_sync.as_mut() {
+                *pending = true;
+                return true;
+            }
             self.sync_view()
         } else {
             log::debug!("Unhandled or failed to parse message: {}", msg_str);
@@ -182,6 +616,11 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
     }
 
     pub fn sync_view(&mut self) -> bool {
+        self.sync_subscriptions();
+        let open_select = self.ui.open_select.and_then(|(node, highlighted)| match self.ui.render_data.get(node) {
+            Some(RenderData::Select(id, ..)) => Some((id.clone(), highlighted)),
+            _ => None,
+        });
         self.ui = {
             profile!("ui_new_compiled");
             let validator = |s: &str| M::Message::from_str(s).is_ok();
@@ -192,7 +631,23 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
             let _ = self.ui.compute_layout(self.cached_size);
         }
         Runtime::<M, R>::sync_canvases(&self.ui, &mut self.context);
+        self.sync_transitions();
         self.restore_scroll();
+        if let Some((x, y)) = self.pointer {
+            self.ui.update_hover(x, y);
+        }
+        self.captured = self.captured_key.as_ref().and_then(|key| self.ui.find_node(key));
+        self.ui.set_pressed(self.captured);
+        if let Some(key) = &self.focused_id
+            && !self.ui.focus(key)
+        {
+            self.focused_id = None;
+        }
+        if let Some((id, highlighted)) = open_select
+            && let Some(node) = self.ui.select_by_id(&id)
+        {
+            self.ui.open_select = Some((node, highlighted));
+        }
         let mut dirty = true;
 
         let commands: Vec<_> = self.context.commands.drain(..).collect();
@@ -218,47 +673,117 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
             }
         }
 
+        self.redraw_pending |= dirty;
         dirty
     }
 
+    /// Starts a transition for every transitioned property the rebuild changed,
+    /// easing from the value shown until now, and shows where each running
+    /// transition is.
+    fn sync_transitions(&mut self) {
+        let now = std::time::Instant::now();
+        let mut targets = HashMap::new();
+        for (node_id, id) in &self.ui.ids {
+            let Some(style) = self.ui.render_data.get(node_id).map(RenderData::style) else { continue };
+            if style.transitions.is_empty() {
+                continue;
+            }
+            if let Some(previous) = self.transition_targets.get(id) {
+                let running = self.transitions.entry(id.clone()).or_default();
+                for property in animation::TRANSITIONABLE {
+                    // A later entry for the same property wins, as in CSS.
+                    let Some(transition) = style.transitions.iter().rev().find(|t| &*t.property == property || &*t.property == "all") else { continue };
+                    let (Some(to), Some(before)) = (TransitionValue::read(property, style), TransitionValue::read(property, previous)) else { continue };
+                    if to == before {
+                        continue;
+                    }
+                    let from = match running.iter().position(|t| &*t.property == property) {
+                        Some(i) => running.remove(i).value_at(now).0,
+                        None => before,
+                    };
+                    running.push(ActiveTransition {
+                        property: std::sync::Arc::from(property),
+                        from,
+                        to,
+                        duration: transition.duration,
+                        timing_function: transition.timing_function.clone(),
+                        delay: transition.delay,
+                        started: now,
+                    });
+                }
+            }
+            targets.insert(id.clone(), style.clone());
+        }
+        self.transitions.retain(|id, running| !running.is_empty() && targets.contains_key(id));
+        self.transition_targets = targets;
+        self.apply_transitions(now);
+    }
+
+    /// Writes each running transition's value at `now` into its element's
+    /// style and drops the finished ones. Returns whether any were running.
+    fn apply_transitions(&mut self, now: std::time::Instant) -> bool {
+        if self.transitions.is_empty() {
+            return false;
+        }
+        for (node_id, id) in &self.ui.ids {
+            let (Some(running), Some(render_data)) = (self.transitions.get_mut(id), self.ui.render_data.get_mut(node_id)) else { continue };
+            running.retain(|transition| {
+                let (value, finished) = transition.value_at(now);
+                value.write(&transition.property, render_data.style_mut());
+                !finished
+            });
+        }
+        self.transitions.retain(|_, running| !running.is_empty());
+        true
+    }
+
+    /// Repaints what changed since the last `render` and returns that region:
+    /// `None` after a full repaint, an empty rect if nothing changed. The
+    /// first frame and the first after `invalidate` are full repaints.
+    ///
+    /// With nothing changed the renderer isn't called at all, so a host that
+    /// flips between buffers must not present the one it passed in.
     pub fn render(&mut self, renderer: &mut impl Renderer) -> Option<Rect> {
         profile!("render");
-        let commands = self.ui.build_commands(&self.context.canvases, self.focused_id.as_deref());
+        let mut commands = self.ui.build_commands(&self.context.canvases);
+        if self.debug {
+            commands.extend(self.ui.build_debug_commands());
+        }
+        if let Some(color) = self.ui.document_background().or(self.clear_color) {
+            commands.insert(0, DrawCommand::Clear { color });
+        }
         
         let mut dirty_region: Option<Rect> = None;
+        // A new background covers everything, including what no command bounds.
+        let background = |commands: &[DrawCommand]| match commands.first() {
+            Some(DrawCommand::Clear { color }) => Some(*color),
+            _ => None,
+        };
+        let full_repaint = self.last_commands.is_empty() || background(&commands) != background(&self.last_commands);
 
+        let bounds = screen_bounds(&commands);
+        let last_bounds = screen_bounds(&self.last_commands);
         let max_len = commands.len().max(self.last_commands.len());
         for i in 0..max_len {
-            let cmd1 = commands.get(i);
-            let cmd2 = self.last_commands.get(i);
-
-            if cmd1 != cmd2 {
-                if let Some(cmd) = cmd1 {
-                    if let Some(b) = cmd.bounds() {
-                        dirty_region = match dirty_region {
-                            Some(dr) => Some(dr.expand(b)),
-                            None => Some(b),
-                        };
-                    }
-                }
-                if let Some(cmd) = cmd2 {
-                    if let Some(b) = cmd.bounds() {
-                        dirty_region = match dirty_region {
-                            Some(dr) => Some(dr.expand(b)),
-                            None => Some(b),
-                        };
-                    }
+            let (now, before) = (bounds.get(i), last_bounds.get(i));
+            // A command drawn unchanged under a changed transform still moves.
+            if commands.get(i) != self.last_commands.get(i) || now.map(|b| b.1) != before.map(|b| b.1) {
+                for b in [now, before].into_iter().flatten().filter_map(|b| b.0) {
+                    dirty_region = match dirty_region {
+                        Some(dr) => Some(dr.expand(b)),
+                        None => Some(b),
+                    };
                 }
             }
         }
 
-        for cmd in &commands {
-            if let DrawCommand::DrawCanvas { id, rect } = cmd {
+        for (cmd, (b, _)) in commands.iter().zip(&bounds) {
+            if let (DrawCommand::DrawCanvas { id, .. }, Some(b)) = (cmd, b) {
                 if let Some(canvas) = self.context.canvases.get(id) {
                     if canvas.dirty {
                         dirty_region = match dirty_region {
-                            Some(dr) => Some(dr.expand(*rect)),
-                            None => Some(*rect),
+                            Some(dr) => Some(dr.expand(*b)),
+                            None => Some(*b),
                         };
                     }
                 }
@@ -269,52 +794,125 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
             canvas.dirty = false;
         }
 
+        self.redraw_pending = false;
+        if full_repaint {
+            dirty_region = None;
+        } else if dirty_region.is_none() {
+            return Some(Rect::new(0.0, 0.0, 0.0, 0.0));
+        }
         renderer.render(&commands, &self.context.canvases, dirty_region);
         self.last_commands = commands;
         dirty_region
     }
 
+    /// Draws every node's box outlined and labelled with its tag and size
+    /// over the view, see `Ui::build_debug_commands`.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.redraw_pending |= self.debug != debug;
+        self.debug = debug;
+    }
+
+    /// Makes the next `render` repaint everything, e.g. after the host
+    /// replaced the target it draws into.
+    pub fn invalidate(&mut self) {
+        self.last_commands.clear();
+        self.redraw_pending = true;
+    }
+
     pub fn set_size(&mut self, width: f32, height: f32) {
-         let _ = self.ui.compute_layout(Size {
+        self.set_available_space(Size {
             width: length(width),
-             height: length(height),
-          });
+            height: length(height),
+        });
+    }
+
+    /// Lays out against a definite width while letting the height grow to fit
+    /// the content, e.g. a scrolling document in a fixed-width column.
+    pub fn set_fixed_width(&mut self, width: f32) {
+        self.set_available_space(Size {
+            width: length(width),
+            height: AvailableSpace::MaxContent,
+        });
+    }
+
+    /// Sets the available space per axis. The value is kept and reused by
+    /// every relayout triggered from `sync_view` and `tick`.
+    pub fn set_available_space(&mut self, space: Size<AvailableSpace>) {
+        self.redraw_pending |= self.cached_size != space;
+        self.cached_size = space;
+        let _ = self.ui.compute_layout(space);
+    }
+
+    pub fn available_space(&self) -> Size<AvailableSpace> {
+        self.cached_size
     }
 
     pub fn compute_layout(&mut self, size: Size<AvailableSpace>) {
-        self.cached_size = size;
-        let _ = self.ui.compute_layout(size);
+        self.set_available_space(size);
     }
     
+    pub fn accessibility_tree(&self) -> crate::ui::AccessNode {
+        self.ui.accessibility_tree()
+    }
+
+    /// Scroll offset of the node with this `data-key` or interaction id; see `Ui::scroll_offset_of`.
+    pub fn scroll_offset_of(&self, key: &str) -> Option<(f32, f32)> {
+        self.ui.scroll_offset_of(key)
+    }
+
+    /// Bounds in window coordinates of the element with this `id`; see `Ui::rect_for_id`.
+    pub fn rect_for_id(&self, id: &str) -> Option<Rect> {
+        self.ui.rect_for_id(id)
+    }
+
     pub fn scroll_into_view(&mut self, interaction_id: &str) {
         self.ui.scroll_into_view(interaction_id);
         self.scroll_offsets = self.ui.scroll_offsets.clone();
+        self.redraw_pending = true;
     }
 
     pub fn set_interval(&mut self, message: String, millis: u32) {
-        let duration = std::time::Duration::from_millis(millis as u64);
-        let id = self.next_timer_id;
-        self.next_timer_id += 1;
-        self.timers.push(Timer {
-            id,
-            message,
-            interval: duration,
-            next_trigger: std::time::Instant::now() + duration,
-            is_recurring: true,
-        });
+        self.add_timer(message, std::time::Duration::from_millis(millis as u64), true);
     }
 
     pub fn set_timeout(&mut self, message: String, millis: u32) {
-        let duration = std::time::Duration::from_millis(millis as u64);
+        self.add_timer(message, std::time::Duration::from_millis(millis as u64), false);
+    }
+
+    fn add_timer(&mut self, message: String, interval: std::time::Duration, is_recurring: bool) -> usize {
         let id = self.next_timer_id;
         self.next_timer_id += 1;
         self.timers.push(Timer {
             id,
             message,
-            interval: duration,
-            next_trigger: std::time::Instant::now() + duration,
-            is_recurring: false,
+            interval,
+            next_trigger: std::time::Instant::now() + interval,
+            is_recurring,
         });
+        id
+    }
+
+    /// Starts timers for new `Model::subscriptions` and stops those of the
+    /// ones no longer listed. Unchanged subscriptions keep their phase.
+    fn sync_subscriptions(&mut self) {
+        let wanted = self.model.subscriptions();
+        let timers = &mut self.timers;
+        self.subscriptions.retain(|(subscription, id)| {
+            let keep = wanted.contains(subscription);
+            if !keep {
+                timers.retain(|timer| timer.id != *id);
+            }
+            keep
+        });
+        for subscription in wanted {
+            if self.subscriptions.iter().any(|(running, _)| *running == subscription) {
+                continue;
+            }
+            let id = match &subscription {
+                Subscription::Interval(interval, message) => self.add_timer(message.clone(), *interval, true),
+            };
+            self.subscriptions.push((subscription, id));
+        }
     }
 
     pub fn tick(&mut self) -> TickResult {
@@ -336,6 +934,7 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
         if !triggered_messages.is_empty() {
             needs_redraw |= self.handle_messages(triggered_messages);
         }
+        needs_redraw |= self.finish_commands();
 
         let new_timers = std::mem::take(&mut self.context.pending_timers);
         for mut timer in new_timers {
@@ -421,6 +1020,7 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
                             RenderData::Progress(_, _, style) => *style = base_container.clone(),
                             RenderData::Canvas(_, style) => *style = base_container.clone(),
                             RenderData::TextInput(_, _, style) => *style = base_container.clone(),
+                            RenderData::Select(_, _, _, style) => *style = base_container.clone(),
                         }
                     }
                 }
@@ -554,6 +1154,7 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
                             RenderData::Progress(_, _, style) => *style = current_container,
                             RenderData::Canvas(_, style) => *style = current_container,
                             RenderData::TextInput(_, _, style) => *style = current_container,
+                            RenderData::Select(_, _, _, style) => *style = current_container,
                         }
                     }
                     needs_redraw = true;
@@ -564,10 +1165,11 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
                 let _ = self.ui.compute_layout(self.cached_size);
             }
         }
+        needs_redraw |= self.apply_transitions(now);
 
         let target_frame_duration = std::time::Duration::from_nanos((1_000_000_000.0 / self.target_fps as f64) as u64);
         
-        let mut min_sleep = if self.active_animations.values().any(|a| !a.is_finished && &*a.play_state != "paused") {
+        let mut min_sleep = if !self.transitions.is_empty() || self.active_animations.values().any(|a| !a.is_finished && &*a.play_state != "paused") {
             target_frame_duration
         } else {
             std::time::Duration::from_secs(3600 * 24)
@@ -580,9 +1182,43 @@ impl<M: Model + crate::ui::TemplateLayout, R: TextMeasurer> Runtime<M, R> {
             }
         }
 
+        if !self.commands.is_empty() {
+            min_sleep = min_sleep.min(COMMAND_POLL);
+        }
+
+        // The caret of a focused input blinks, on for one interval, then off.
+        if self.focused_input_id().is_some() {
+            let elapsed = now.duration_since(self.caret_since).as_millis();
+            let visible = (elapsed / CARET_BLINK.as_millis()).is_multiple_of(2);
+            if visible != self.ui.caret_visible {
+                self.ui.caret_visible = visible;
+                needs_redraw = true;
+            }
+            let until_toggle = CARET_BLINK.as_millis() - elapsed % CARET_BLINK.as_millis();
+            min_sleep = min_sleep.min(std::time::Duration::from_millis(until_toggle as u64));
+        }
+
+        self.redraw_pending |= needs_redraw;
         TickResult {
             needs_redraw,
             next_tick_in: min_sleep,
         }
     }
 }
+
+/// Where each command lands on screen, through the transforms it is drawn
+/// under, along with the combined transform itself.
+fn screen_bounds(commands: &[DrawCommand]) -> Vec<(Option<Rect>, Matrix)> {
+    let mut stack = vec![Matrix::IDENTITY];
+    commands.iter().map(|cmd| {
+        let current = *stack.last().unwrap_or(&Matrix::IDENTITY);
+        match cmd {
+            DrawCommand::PushTransform { matrix, .. } => stack.push(current.multiply(*matrix)),
+            DrawCommand::PopTransform if stack.len() > 1 => {
+                stack.pop();
+            }
+            _ => {}
+        }
+        (cmd.bounds().map(|b| current.map_rect(b)), current)
+    }).collect()
+}
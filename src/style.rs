@@ -1,10 +1,11 @@
-use crate::graphics::{Color, LinearGradient};
+use crate::graphics::{BackgroundSize, Color, Gradient, Matrix, Rect};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Display {
     Block,
     InlineBlock,
     Flex,
+    Grid,
     None,
 }
 
@@ -13,19 +14,99 @@ pub enum TextAlign {
     Left,
     Center,
     Right,
+    Justify,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     Ltr,
     Rtl,
 }
 
+/// `line-height`: the height of a line box, as a multiple of the glyph box of
+/// the font or in pixels. Inherited.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineHeight {
+    Multiple(f32),
+    Px(f32),
+}
+
+impl LineHeight {
+    /// The line box for text whose glyph box is `glyph_height` tall.
+    pub fn resolve(self, glyph_height: f32) -> f32 {
+        match self {
+            LineHeight::Multiple(multiple) => glyph_height * multiple,
+            LineHeight::Px(px) => px,
+        }
+    }
+}
+
+/// How text handles white space and line breaks. Inherited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WhiteSpace {
+    /// Runs of white space collapse to one space and lines wrap to the
+    /// container, up to `line-clamp` lines.
+    Normal,
+    /// White space collapses, but the text stays on one line however narrow
+    /// the container; it may end in an ellipsis with `text-overflow`.
+    NoWrap,
+    /// White space is kept as written and lines break only at newlines,
+    /// never to fit the container, so `line-clamp` does not apply.
+    Pre,
+}
+
+/// The marker drawn beside each list item. Inherited, so items take it from
+/// their list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ListStyle {
+    None,
+    Disc,
+    Circle,
+    Square,
+    /// The item's position among the items of its list: `1.`, `2.`, ...
+    Decimal,
+}
+
+/// The mouse cursor shown over an element, for the window to set.
+/// Inherited, so text shows the cursor of the element it is in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Cursor {
+    #[default]
+    Default,
+    Pointer,
+    Text,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Overflow {
     Visible,
+    /// Clips the content. Only `Scroll` makes a scroll container, so unlike in
+    /// CSS a hidden box can't be scrolled, not even by `scroll_into_view`.
     Hidden,
     Scroll,
+    /// `overflow: clip`, an alias of `Hidden` here: both clip and neither
+    /// scrolls. Kept apart so the declared value can be told.
+    Clip,
+}
+
+/// `border-style`, shared by all four sides. `Solid` is the default so that a
+/// border width alone draws a border.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderStyle {
+    Solid,
+    Dashed,
+    Dotted,
+    None,
+}
+
+/// An outer `box-shadow`. `blur` is the CSS blur radius, twice the std-dev.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoxShadow {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -65,20 +146,125 @@ pub enum AnimationIterationCount {
     Count(f32),
 }
 
+/// One entry of `transition`: how a change to `property` (or `all`) eases in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub property: std::sync::Arc<str>,
+    pub duration: f32, // in seconds
+    pub timing_function: std::sync::Arc<str>,
+    pub delay: f32, // in seconds
+}
+
+/// One coordinate of `transform-origin` or `background-position`, measured
+/// from the box's top-left corner, or one side of a `background-size`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OriginOffset {
+    Px(f32),
+    /// A fraction of the box's width or height, e.g. `0.5` for `center`.
+    Fraction(f32),
+}
+
+/// The initial `transform-origin`, `center center`.
+pub const TRANSFORM_ORIGIN_CENTER: (OriginOffset, OriginOffset) = (OriginOffset::Fraction(0.5), OriginOffset::Fraction(0.5));
+
+/// The initial `background-position`, `left top`.
+pub const BACKGROUND_POSITION_TOP_LEFT: (OriginOffset, OriginOffset) = (OriginOffset::Fraction(0.0), OriginOffset::Fraction(0.0));
+
+impl OriginOffset {
+    pub fn resolve(self, size: f32) -> f32 {
+        match self {
+            OriginOffset::Px(px) => px,
+            OriginOffset::Fraction(f) => f * size,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ContainerStyle {
     pub color: Color,
     pub font_size: f32,
+    /// The parent's font size, what `em` in `font-size` itself refers to.
+    pub inherited_font_size: f32,
+    /// Font size of the root element, the unit of `rem`. Inherited unchanged.
+    pub root_font_size: f32,
     pub weight: u16, // 0 = Regular, 1 = Bold
+    pub font_style: u16, // 0 = Normal, 1 = Italic
+    /// `font-family`: the id of a registered family, 0 for the default one.
+    pub font_family: u16,
+    /// `font-family` as written, until style resolution asks the text
+    /// measurer for the first family it knows and sets `font_family`.
+    pub font_family_names: Option<std::sync::Arc<str>>,
+    /// `text-decoration: underline`: text is drawn with a line under it.
+    /// Passed down to the text of descendants.
+    pub underline: bool,
+    /// `cursor`: `pointer` for links and `text` for text fields by default.
+    pub cursor: Cursor,
+    pub word_spacing: f32,
     pub background_color: Option<Color>,
     pub border_radius: f32,
     pub border_width: f32,
     pub border_color: Option<Color>,
-    pub background_gradient: Option<LinearGradient>,
+    // Per-side overrides in top, right, bottom, left order; `None` falls back to `border_width`/`border_color`.
+    pub border_side_widths: [Option<f32>; 4],
+    pub border_side_colors: [Option<Color>; 4],
+    pub border_style: BorderStyle,
+    /// `outline`: a ring drawn just outside the border box. Unlike a border
+    /// it takes no space, so it overlaps neighbours instead of moving them.
+    pub outline_width: f32,
+    /// `None` (`currentColor`) follows `color`.
+    pub outline_color: Option<Color>,
+    /// `fill` for monochrome images; `None` (`currentColor`) follows `color` on icons.
+    pub fill: Option<Color>,
+    /// Set by `data-icon` on an `<img>`: only the image's alpha is drawn, in the fill color.
+    pub is_icon: bool,
+    pub background_gradient: Option<Gradient>,
+    /// `background-image: url(...)`, drawn over the background color.
+    pub background_image: Option<String>,
+    pub background_size: BackgroundSize,
+    pub background_position: (OriginOffset, OriginOffset),
+    // Sub-rectangle of an image's source pixels to draw, e.g. one frame of a sprite sheet.
+    pub object_view_box: Option<Rect>,
+    // Format of the on-bar label of a progress bar or slider (`data-label`).
+    pub value_label: Option<std::sync::Arc<str>>,
+    /// `data-selection="start,end"`: the characters of the element's own text
+    /// drawn highlighted. Its text children carry it. Left-to-right text only:
+    /// the offsets aren't mapped to reordered right-to-left runs.
+    pub text_selection: Option<(usize, usize)>,
+    pub backdrop_blur: f32,
+    pub box_shadow: Option<BoxShadow>,
+    /// The element's own `opacity`; ancestors' opacities are multiplied in when drawing.
+    pub opacity: f32,
+    /// `will-change` or `data-layer`: draw the subtree as its own cacheable layer.
+    pub layer: bool,
+    /// `line-clamp`: wrap text to the container and keep at most this many lines.
+    pub line_clamp: Option<u32>,
+    /// `white-space`: whether text collapses its white space and wraps to the
+    /// container, stays on one line, or keeps its lines as written.
+    pub white_space: WhiteSpace,
+    /// `list-style-type`, set on `ul` and `ol` by default.
+    pub list_style: ListStyle,
+    pub line_height: LineHeight,
+    /// `transform`, applied around `transform_origin`. It moves the element's
+    /// own box and text but not its child elements.
+    pub transform: Option<Matrix>,
+    /// Pivot for transforms, `(x, y)`; defaults to the center of the box.
+    pub transform_origin: (OriginOffset, OriginOffset),
     pub overflow: Overflow,
+    /// `scrollbar-width: none`: scroll without drawing scrollbars.
+    pub hide_scrollbar: bool,
+    /// `text-overflow: ellipsis`: a single line of text overflowing this clipped
+    /// box ends in an ellipsis.
+    pub text_overflow: bool,
+    /// `visibility: hidden`: keep the box in layout but draw nothing of it
+    /// and let clicks through. Inherited, so children may set `visible` again.
+    pub hidden: bool,
     pub display: Display,
     pub text_align: Option<TextAlign>,
     pub order: i32,
+    /// `z-index`: siblings with a higher value paint over those with a lower one.
+    /// It only reorders siblings, as if every element formed a stacking
+    /// context of its own, so a child never rises above its parent's siblings.
+    pub z_index: i32,
     pub direction: Direction,
     pub writing_mode: WritingMode,
     pub flex_direction: FlexDirection,
@@ -87,6 +273,9 @@ pub struct ContainerStyle {
     pub align_items: Option<AlignItems>,
     pub width: Option<f32>,
     pub height: Option<f32>,
+    /// Whether CSS or an attribute set the `(width, height)`, so that
+    /// `aspect-ratio` derives the other side only.
+    pub sized: (bool, bool),
     pub padding_left: f32,
     pub padding_right: f32,
     pub padding_top: f32,
@@ -110,6 +299,9 @@ pub struct ContainerStyle {
     pub animation_direction: std::sync::Arc<str>,
     pub animation_fill_mode: std::sync::Arc<str>,
     pub animation_play_state: std::sync::Arc<str>,
+    /// `transition`: properties whose changes the runtime eases between
+    /// rebuilds instead of showing at once.
+    pub transitions: std::sync::Arc<[Transition]>,
 }
 
 impl Default for ContainerStyle {
@@ -117,17 +309,52 @@ impl Default for ContainerStyle {
         Self {
             color: Color::from_rgba8(0, 0, 0, 255),
             font_size: 16.0,
+            inherited_font_size: 16.0,
+            root_font_size: 16.0,
             weight: 0,
+            font_style: 0,
+            font_family: 0,
+            font_family_names: None,
+            underline: false,
+            cursor: Cursor::Default,
+            word_spacing: 0.0,
             background_color: None,
             border_radius: 0.0,
             border_width: 0.0,
 
             border_color: None,
+            outline_width: 0.0,
+            outline_color: None,
+            border_side_widths: [None; 4],
+            border_side_colors: [None; 4],
+            border_style: BorderStyle::Solid,
+            fill: None,
+            is_icon: false,
             background_gradient: None,
+            background_image: None,
+            background_size: BackgroundSize::Stretch,
+            background_position: BACKGROUND_POSITION_TOP_LEFT,
+            object_view_box: None,
+            value_label: None,
+            text_selection: None,
+            backdrop_blur: 0.0,
+            box_shadow: None,
+            opacity: 1.0,
+            layer: false,
+            line_clamp: None,
+            white_space: WhiteSpace::Normal,
+            list_style: ListStyle::None,
+            line_height: LineHeight::Multiple(1.0),
+            transform: None,
+            transform_origin: TRANSFORM_ORIGIN_CENTER,
             overflow: Overflow::Visible,
+            hide_scrollbar: false,
+            text_overflow: false,
+            hidden: false,
             display: Display::Block,
             text_align: None,
             order: 0,
+            z_index: 0,
             direction: Direction::Ltr,
             writing_mode: WritingMode::HorizontalTb,
             flex_direction: FlexDirection::Row,
@@ -136,6 +363,7 @@ impl Default for ContainerStyle {
             align_items: None,
             width: None,
             height: None,
+            sized: (false, false),
             padding_left: 0.0,
             padding_right: 0.0,
             padding_top: 0.0,
@@ -158,10 +386,40 @@ impl Default for ContainerStyle {
             animation_direction: std::sync::Arc::from("normal"),
             animation_fill_mode: std::sync::Arc::from("none"),
             animation_play_state: std::sync::Arc::from("running"),
+            transitions: std::sync::Arc::from([]),
         }
     }
 }
 
+impl ContainerStyle {
+    /// The `weight` passed to `TextMeasurer` and `DrawText`: bit 0 is bold,
+    /// bit 1 italic and the rest the font family, see `graphics::font_index`.
+    pub fn font_variant(&self) -> u16 {
+        self.weight | self.font_style << 1 | self.font_family << 2
+    }
+
+    /// Effective border widths in top, right, bottom, left order.
+    pub fn border_widths(&self) -> [f32; 4] {
+        self.border_side_widths.map(|w| w.unwrap_or(self.border_width))
+    }
+
+    pub fn has_side_borders(&self) -> bool {
+        self.border_side_widths.iter().any(Option::is_some) || self.border_side_colors.iter().any(Option::is_some)
+    }
+
+    /// The `transform-origin` point in the same coordinates as `rect`.
+    pub fn transform_origin_in(&self, rect: Rect) -> (f32, f32) {
+        let (x, y) = self.transform_origin;
+        (rect.x + x.resolve(rect.width), rect.y + y.resolve(rect.height))
+    }
+
+    /// `transform` for an element drawn at `rect`, around its origin.
+    pub fn transform_in(&self, rect: Rect) -> Option<Matrix> {
+        let (x, y) = self.transform_origin_in(rect);
+        self.transform.map(|matrix| matrix.about(x, y))
+    }
+}
+
 pub enum RenderData {
     Container(ContainerStyle),
     Text(String, ContainerStyle),
@@ -171,6 +429,7 @@ pub enum RenderData {
     Progress(f32, f32, ContainerStyle), // value, max, style
     Canvas(String, ContainerStyle),
     TextInput(String, Option<String>, ContainerStyle), // id, text value, style
+    Select(String, Vec<(String, String)>, String, ContainerStyle), // id, (value, label) options, selected value, style
 }
 
 impl RenderData {
@@ -184,6 +443,21 @@ impl RenderData {
             RenderData::Progress(_, _, style) => style,
             RenderData::Canvas(_, style) => style,
             RenderData::TextInput(_, _, style) => style,
+            RenderData::Select(_, _, _, style) => style,
+        }
+    }
+
+    pub fn style_mut(&mut self) -> &mut ContainerStyle {
+        match self {
+            RenderData::Container(style)
+            | RenderData::Text(_, style)
+            | RenderData::Image(_, style)
+            | RenderData::Checkbox(_, style)
+            | RenderData::Slider(_, style)
+            | RenderData::Progress(_, _, style)
+            | RenderData::Canvas(_, style)
+            | RenderData::TextInput(_, _, style)
+            | RenderData::Select(_, _, _, style) => style,
         }
     }
 }
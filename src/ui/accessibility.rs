@@ -0,0 +1,218 @@
+use taffy::prelude::*;
+
+use super::{NodeMap, NodeMetadata, Ui};
+use crate::graphics::Rect;
+use crate::style::{Display, Overflow, RenderData};
+
+/// Semantic attributes (`role`, `aria-label`, `alt`) captured per node while the tree is built.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessInfo {
+    pub role: Option<String>,
+    pub label: Option<String>,
+}
+
+impl AccessInfo {
+    pub(crate) fn from_attrs<'a>(tag: &str, attrs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Option<Self> {
+        let mut info = AccessInfo {
+            role: implicit_role(tag).map(str::to_string),
+            label: None,
+        };
+        for (key, val) in attrs {
+            match key {
+                "role" => info.role = Some(val.to_string()),
+                "aria-label" => info.label = Some(val.to_string()),
+                "alt" if info.label.is_none() => info.label = Some(val.to_string()),
+                _ => {}
+            }
+        }
+        if info.role.is_none() && info.label.is_none() {
+            None
+        } else {
+            Some(info)
+        }
+    }
+}
+
+/// A node of the accessibility tree returned by `Ui::accessibility_tree`.
+/// Purely presentational containers are flattened away, so `children` only
+/// holds nodes that carry a role, a label, text or an interaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessNode {
+    pub node: NodeId,
+    pub role: String,
+    pub label: Option<String>,
+    pub action: Option<String>,
+    pub bounds: Rect,
+    pub focused: bool,
+    pub checked: Option<bool>,
+    pub value: Option<f32>,
+    pub children: Vec<AccessNode>,
+}
+
+fn implicit_role(tag: &str) -> Option<&'static str> {
+    match tag {
+        "button" => Some("button"),
+        "a" => Some("link"),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => Some("heading"),
+        "ul" | "ol" => Some("list"),
+        "li" => Some("listitem"),
+        "nav" => Some("navigation"),
+        "main" => Some("main"),
+        _ => None,
+    }
+}
+
+pub(crate) fn collect_compiled(node_metadata: &NodeMap<NodeMetadata>) -> NodeMap<AccessInfo> {
+    let mut access_info = NodeMap::new();
+    for (node, meta) in node_metadata {
+        if meta.tag == "#text" {
+            continue;
+        }
+        let attrs = meta.other_attrs.iter().flatten().map(|(k, v)| (k.as_str(), v.as_str()));
+        if let Some(info) = AccessInfo::from_attrs(&meta.tag, attrs) {
+            access_info.insert(node, info);
+        }
+    }
+    access_info
+}
+
+#[cfg(feature = "dynamic-parser")]
+pub(crate) fn collect_dynamic(node_to_handle: &NodeMap<super::Handle>) -> NodeMap<AccessInfo> {
+    let mut access_info = NodeMap::new();
+    for (node, handle) in node_to_handle {
+        if let markup5ever_rcdom::NodeData::Element { ref name, ref attrs, .. } = handle.data {
+            let attrs = attrs.borrow();
+            let pairs = attrs.iter().map(|a| (a.name.local.as_ref(), a.value.as_ref()));
+            if let Some(info) = AccessInfo::from_attrs(name.local.as_ref(), pairs) {
+                access_info.insert(node, info);
+            }
+        }
+    }
+    access_info
+}
+
+impl Ui {
+    /// Builds the accessibility tree from the current layout.
+    pub fn accessibility_tree(&self) -> AccessNode {
+        let bounds = self.taffy.layout(self.root)
+            .map(|l| Rect { x: l.location.x, y: l.location.y, width: l.size.width, height: l.size.height })
+            .unwrap_or(Rect { x: 0.0, y: 0.0, width: 0.0, height: 0.0 });
+        let mut document = AccessNode {
+            node: self.root,
+            role: "document".to_string(),
+            label: None,
+            action: None,
+            bounds,
+            focused: false,
+            checked: None,
+            value: None,
+            children: Vec::new(),
+        };
+        self.collect_access_nodes(self.root, 0.0, 0.0, &mut document.children);
+        document
+    }
+
+    fn collect_access_nodes(&self, node: NodeId, offset_x: f32, offset_y: f32, out: &mut Vec<AccessNode>) {
+        let layout = match self.taffy.layout(node) {
+            Ok(l) => l,
+            Err(_) => return,
+        };
+        let style = self.render_data.get(node).map(|d| d.style());
+        if style.is_some_and(|s| s.display == Display::None) {
+            return;
+        }
+
+        let x = offset_x + layout.location.x;
+        let y = offset_y + layout.location.y;
+        let bounds = Rect { x, y, width: layout.size.width, height: layout.size.height };
+
+        let (mut child_x, mut child_y) = (x, y);
+        if style.is_some_and(|s| s.overflow == Overflow::Scroll)
+            && let Some((sx, sy)) = self.scroll_offsets.get(node)
+        {
+            child_x -= sx;
+            child_y -= sy;
+        }
+
+        let children = self.taffy.children(node).unwrap_or_default();
+        match self.describe_node(node, bounds) {
+            Some(mut entry) => {
+                for child in children {
+                    self.collect_access_nodes(child, child_x, child_y, &mut entry.children);
+                }
+                out.push(entry);
+            }
+            None => {
+                for child in children {
+                    self.collect_access_nodes(child, child_x, child_y, out);
+                }
+            }
+        }
+    }
+
+    fn describe_node(&self, node: NodeId, bounds: Rect) -> Option<AccessNode> {
+        let info = self.access_info.get(node);
+        let action = self.interactions.get(node).cloned();
+
+        let mut entry = AccessNode {
+            node,
+            role: "generic".to_string(),
+            label: None,
+            action,
+            bounds,
+            focused: self.focused == Some(node),
+            checked: None,
+            value: None,
+            children: Vec::new(),
+        };
+
+        match self.render_data.get(node) {
+            Some(RenderData::Text(text, _)) => {
+                entry.role = "text".to_string();
+                entry.label = Some(text.clone());
+            }
+            Some(RenderData::Checkbox(checked, _)) => {
+                entry.role = "checkbox".to_string();
+                entry.checked = Some(*checked);
+            }
+            Some(RenderData::Slider(value, _)) => {
+                entry.role = "slider".to_string();
+                entry.value = Some(*value);
+            }
+            Some(RenderData::Progress(value, _, _)) => {
+                entry.role = "progressbar".to_string();
+                entry.value = Some(*value);
+            }
+            Some(RenderData::TextInput(_, text, _)) => {
+                entry.role = "textbox".to_string();
+                entry.label = text.clone().filter(|t| !t.is_empty());
+            }
+            Some(RenderData::Select(_, options, selected, _)) => {
+                entry.role = "combobox".to_string();
+                entry.label = options.iter().find(|(value, _)| value == selected).map(|(_, label)| label.clone());
+            }
+            Some(RenderData::Image(..)) | Some(RenderData::Canvas(..)) => {
+                entry.role = "img".to_string();
+            }
+            Some(RenderData::Container(_)) | None => {}
+        }
+
+        if let Some(info) = info {
+            if let Some(ref role) = info.role {
+                entry.role = role.clone();
+            }
+            if info.label.is_some() {
+                entry.label = info.label.clone();
+            }
+        }
+
+        if entry.role == "generic" {
+            if entry.action.is_some() {
+                entry.role = "button".to_string();
+            } else if entry.label.is_none() {
+                return None;
+            }
+        }
+        Some(entry)
+    }
+}
@@ -40,7 +40,7 @@ impl simplecss::Element for ElementWrapper {
         if index > 0 {
             for i in (0..index).rev() {
                 let sibling = &siblings[i];
-                if matches!(sibling.data, NodeData::Element { .. }) {
+                if matches!(sibling.data, NodeData::Element { .. }) && !is_pseudo_element(sibling) {
                     return Some(ElementWrapper(sibling.clone()));
                 }
             }
@@ -73,7 +73,7 @@ impl simplecss::Element for ElementWrapper {
                 if let Some(parent) = self.parent_element() {
                     let siblings = parent.0.children.borrow();
                     for child in siblings.iter() {
-                        if matches!(child.data, NodeData::Element { .. }) {
+                        if matches!(child.data, NodeData::Element { .. }) && !is_pseudo_element(child) {
                             return Rc::ptr_eq(child, &self.0);
                         }
                     }
@@ -161,6 +161,128 @@ pub(crate) fn preprocess_dom(handle: &DomHandle) {
     }
 }
 
+/// Tags of the synthetic elements standing in for `::before` and `::after`.
+/// The stylesheet is rewritten so `X::before` reads `X > xerune-before`.
+pub(crate) const PSEUDO_BEFORE_TAG: &str = "xerune-before";
+pub(crate) const PSEUDO_AFTER_TAG: &str = "xerune-after";
+
+pub(crate) fn rewrite_pseudo_elements(css_str: &str) -> String {
+    let re_pseudo = regex::Regex::new(r"::?(before|after)\b").unwrap();
+    re_pseudo.replace_all(css_str, " > xerune-$1").into_owned()
+}
+
+#[cfg(feature = "dynamic-parser")]
+fn is_pseudo_element(handle: &DomHandle) -> bool {
+    matches!(handle.data, NodeData::Element { ref name, .. }
+        if name.local.as_ref() == PSEUDO_BEFORE_TAG || name.local.as_ref() == PSEUDO_AFTER_TAG)
+}
+
+/// Whether `handle` is the document element, `<html>`.
+#[cfg(feature = "dynamic-parser")]
+fn is_root_element(handle: &DomHandle) -> bool {
+    let parent_weak = handle.parent.take();
+    let is_root = parent_weak.as_ref().and_then(|weak| weak.upgrade()).is_some_and(|parent| matches!(parent.data, NodeData::Document));
+    handle.parent.set(parent_weak);
+    is_root
+}
+
+#[cfg(feature = "dynamic-parser")]
+fn new_element(tag: &str) -> DomHandle {
+    markup5ever_rcdom::Node::new(NodeData::Element {
+        name: markup5ever::QualName::new(None, markup5ever::ns!(html), markup5ever::LocalName::from(tag)),
+        attrs: std::cell::RefCell::new(Vec::new()),
+        template_contents: std::cell::RefCell::new(None),
+        mathml_annotation_xml_integration_point: false,
+    })
+}
+
+/// Parses a `content` value into the text to insert; `none`, `normal` and
+/// anything other than a quoted string generate no pseudo-element.
+pub(crate) fn parse_content(val: &str) -> Option<String> {
+    let val = val.trim();
+    let inner = val.strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+        .or_else(|| val.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))?;
+
+    let mut text = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        let mut hex = String::new();
+        while hex.len() < 6 && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            hex.push(chars.next().unwrap());
+        }
+        if hex.is_empty() {
+            text.extend(chars.next());
+        } else {
+            text.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+            if chars.peek() == Some(&' ') {
+                chars.next();
+            }
+        }
+    }
+    Some(text)
+}
+
+/// Inserts a `xerune-before`/`xerune-after` element holding the `content`
+/// text as the first/last child of every element a pseudo-element rule
+/// matches. The inserted nodes are then styled like any other element.
+#[cfg(feature = "dynamic-parser")]
+pub(crate) fn insert_pseudo_elements(handle: &DomHandle, stylesheet: &simplecss::StyleSheet<'_>) {
+    if let NodeData::Element { ref name, .. } = handle.data
+        && matches!(name.local.as_ref(), "style" | "script" | "head")
+    {
+        return;
+    }
+
+    let children: Vec<DomHandle> = handle.children.borrow().clone();
+    for child in &children {
+        insert_pseudo_elements(child, stylesheet);
+    }
+
+    if !matches!(handle.data, NodeData::Element { .. }) {
+        return;
+    }
+
+    for tag in [PSEUDO_BEFORE_TAG, PSEUDO_AFTER_TAG] {
+        let pseudo = new_element(tag);
+        pseudo.parent.set(Some(Rc::downgrade(handle)));
+
+        let wrapper = ElementWrapper(pseudo.clone());
+        let mut content = None;
+        for rule in &stylesheet.rules {
+            if rule.selector.matches(&wrapper) {
+                for decl in rule.declarations.iter().filter(|d| d.name.eq_ignore_ascii_case("content")) {
+                    content = parse_content(decl.value);
+                }
+            }
+        }
+
+        let Some(text) = content else {
+            pseudo.parent.set(None);
+            continue;
+        };
+
+        let text_node = markup5ever_rcdom::Node::new(NodeData::Text {
+            contents: std::cell::RefCell::new(text.into()),
+        });
+        text_node.parent.set(Some(Rc::downgrade(&pseudo)));
+        pseudo.children.borrow_mut().push(text_node);
+
+        let mut siblings = handle.children.borrow_mut();
+        if tag == PSEUDO_BEFORE_TAG {
+            siblings.insert(0, pseudo);
+        } else {
+            siblings.push(pseudo);
+        }
+    }
+}
+
+/// Text color of elements with `data-disabled`.
+const DISABLED_COLOR: crate::Color = crate::Color { r: 160, g: 160, b: 160, a: 255 };
+
 pub(crate) struct ParsedAttributes {
     pub(crate) element_type: defaults::ElementType,
     pub(crate) slider_value: f32,
@@ -172,6 +294,7 @@ pub(crate) struct ParsedAttributes {
     pub(crate) canvas_id: String,
     pub(crate) element_id: Option<String>,
     pub(crate) text_input_text: Option<String>,
+    pub(crate) disabled: bool,
 }
 
 impl ParsedAttributes {
@@ -187,6 +310,7 @@ impl ParsedAttributes {
             canvas_id: String::new(),
             element_id: None,
             text_input_text: None,
+            disabled: false,
         }
     }
 }
@@ -243,25 +367,86 @@ pub(crate) fn parse_attributes_generic<'a>(
             "width" => {
                  if let Ok(w) = value.parse::<f32>() {
                      layout_style.size.width = length(w);
+                     current_style.sized.0 = true;
                  }
              },
              "height" => {
                  if let Ok(h) = value.parse::<f32>() {
                      layout_style.size.height = length(h);
+                     current_style.sized.1 = true;
                  }
              },
              "src" => parsed.image_src = value.to_string(),
+             "aria-orientation" if tag == "hr" && value == "vertical" => {
+                 // A vertical rule for flex rows: as tall as the row, zero width.
+                 layout_style.size = Size { width: length(0.0), height: Dimension::auto() };
+                 layout_style.align_self = Some(AlignSelf::Stretch);
+                 layout_style.margin = taffy::geometry::Rect { left: length(8.0), right: length(8.0), top: length(0.0), bottom: length(0.0) };
+             }
+             "data-icon" => current_style.is_icon = true,
+             "data-layer" => current_style.layer = true,
+             "data-label" => current_style.value_label = Some(std::sync::Arc::from(value)),
+             "data-selection" => {
+                 current_style.text_selection = value.split_once(',').and_then(|(start, end)| {
+                     let (start, end) = (start.trim().parse::<usize>().ok()?, end.trim().parse::<usize>().ok()?);
+                     Some((start.min(end), start.max(end)))
+                 });
+             }
+             "show-label" => {
+                 if current_style.value_label.is_none() {
+                     current_style.value_label = Some(std::sync::Arc::from("{percent}%"));
+                 }
+             }
+             // A link without a click handler of its own navigates.
+             "href" if tag == "a" => {
+                 if parsed.interaction_id.is_none() {
+                     let message = format!("navigate:{}", value);
+                     if !message_validator(&message) {
+                         log::warn!("Invalid message for href: {}", message);
+                     }
+                     parsed.interaction_id = Some(message);
+                 }
+             }
              "data-on-click" => {
                  if !message_validator(value) {
                      log::warn!("Invalid message in data-on-click: {}", value);
                  }
                  parsed.interaction_id = Some(value.to_string());
              }
+             "data-disabled" => parsed.disabled = true,
              _ => {
                  log::debug!("Ignoring attribute: {} on tag: {}", name, tag);
              }
         }
     }
+
+    // Disabled wins over any style given: greyed out, and not clickable.
+    if parsed.disabled {
+        parsed.interaction_id = None;
+        current_style.color = DISABLED_COLOR;
+        current_style.background_color = current_style.background_color.map(|color| color.mix(crate::Color::WHITE, 0.5));
+        current_style.border_color = current_style.border_color.map(|color| color.mix(crate::Color::WHITE, 0.5));
+        current_style.cursor = crate::style::Cursor::Default;
+    }
+}
+
+/// With `aspect-ratio`, the default size of e.g. an image or canvas gives way
+/// on the side that neither CSS nor an attribute set, even to the default
+/// value, so that the ratio derives it from the other. If neither side was
+/// set, the height follows the default width.
+pub(crate) fn fit_aspect_ratio(tag: &str, current_style: &ContainerStyle, layout_style: &mut Style) {
+    if layout_style.aspect_ratio.is_none() {
+        return;
+    }
+    let default = defaults::get_default_style(tag, &ContainerStyle::default()).taffy_style.size;
+    if default.width.is_auto() || default.height.is_auto() {
+        return;
+    }
+    match current_style.sized {
+        (_, false) => layout_style.size.height = Dimension::auto(),
+        (false, true) => layout_style.size.width = Dimension::auto(),
+        (true, true) => {}
+    }
 }
 
 #[cfg(feature = "dynamic-parser")]
@@ -308,12 +493,31 @@ pub(crate) fn process_element_type(
         defaults::ElementType::TextInput => {
             render_data.insert(id, RenderData::TextInput(parsed.element_id.clone().unwrap_or_default(), parsed.text_input_text.clone(), current_style));
         },
+        defaults::ElementType::Select => {
+            // Options are filled in once the children are built, see `select::collect`.
+            render_data.insert(id, RenderData::Select(parsed.element_id.clone().unwrap_or_default(), Vec::new(), parsed.text_input_text.clone().unwrap_or_default(), current_style));
+        },
         _ => {
             render_data.insert(id, RenderData::Container(current_style));
         }
     }
 }
 
+/// Whether a sibling right before or after `handle` is an inline element.
+#[cfg(feature = "dynamic-parser")]
+fn next_to_inline_element(handle: &DomHandle) -> bool {
+    let Some(parent) = simplecss::Element::parent_element(&ElementWrapper(handle.clone())) else {
+        return false;
+    };
+    let siblings = parent.0.children.borrow();
+    let Some(index) = siblings.iter().position(|child| Rc::ptr_eq(child, handle)) else {
+        return false;
+    };
+    [index.checked_sub(1), Some(index + 1)].into_iter()
+        .filter_map(|i| siblings.get(i?))
+        .any(|sibling| matches!(sibling.data, NodeData::Element { ref name, .. } if super::inline::is_inline(&name.local)))
+}
+
 #[cfg(feature = "dynamic-parser")]
 pub(crate) fn dom_to_taffy(
     taffy: &mut TaffyTree,
@@ -331,10 +535,33 @@ pub(crate) fn dom_to_taffy(
     let mut current_style = parent_style.clone();
     current_style.background_color = None;
     current_style.background_gradient = None;
+    current_style.background_image = None;
+    current_style.background_size = crate::graphics::BackgroundSize::Stretch;
+    current_style.background_position = crate::style::BACKGROUND_POSITION_TOP_LEFT;
+    current_style.object_view_box = None;
+    current_style.value_label = None;
+    current_style.backdrop_blur = 0.0;
+    current_style.box_shadow = None;
+    current_style.opacity = 1.0;
+    current_style.layer = false;
+    current_style.transform = None;
+    current_style.transform_origin = crate::style::TRANSFORM_ORIGIN_CENTER;
+    current_style.transitions = std::sync::Arc::from([]);
     current_style.border_width = 0.0;
     current_style.border_radius = 0.0;
     current_style.border_color = None;
+    current_style.outline_width = 0.0;
+    current_style.outline_color = None;
+    current_style.border_side_widths = [None; 4];
+    current_style.border_side_colors = [None; 4];
+    current_style.border_style = crate::style::BorderStyle::Solid;
+    current_style.fill = None;
+    current_style.is_icon = false;
     current_style.overflow = Overflow::Visible;
+    current_style.hide_scrollbar = false;
+    current_style.text_overflow = false;
+    current_style.sized = (false, false);
+    current_style.z_index = 0;
 
     match &handle.data {
         NodeData::Document => {
@@ -379,6 +606,18 @@ pub(crate) fn dom_to_taffy(
                 }
             }
             parse_attributes(tag, &attrs.borrow(), &mut current_style, &mut layout_style, &mut parsed, message_validator);
+            fit_aspect_ratio(tag, &current_style, &mut layout_style);
+            if let Some(names) = current_style.font_family_names.take() {
+                current_style.font_family = css::pick_font_family(&names, text_measurer);
+            }
+            // The root element's own font size is what `rem` means below it.
+            if is_root_element(handle) {
+                current_style.root_font_size = current_style.font_size;
+            }
+            // `display: none` leaves the whole subtree out of the tree.
+            if current_style.display == Display::None {
+                return None;
+            }
 
             if let Some(d) = current_style.inline_size {
                 layout_style.size.width = d;
@@ -405,8 +644,9 @@ pub(crate) fn dom_to_taffy(
                 layout_style.max_size.height = d;
             }
 
-            let add_h = current_style.padding_left + current_style.padding_right + current_style.border_width * 2.0;
-            let add_v = current_style.padding_top + current_style.padding_bottom + current_style.border_width * 2.0;
+            let [border_top, border_right, border_bottom, border_left] = current_style.border_widths();
+            let add_h = current_style.padding_left + current_style.padding_right + border_left + border_right;
+            let add_v = current_style.padding_top + current_style.padding_bottom + border_top + border_bottom;
 
             if current_style.box_sizing == BoxSizing::ContentBox {
                 layout_style.size.width = super::style_resolution::to_border_box(layout_style.size.width, add_h);
@@ -623,15 +863,13 @@ pub(crate) fn dom_to_taffy(
                 }
             }
 
-            if current_style.display == Display::Flex {
+            if matches!(current_style.display, Display::Flex | Display::Grid) {
                 children.sort_by_key(|child_id| {
                     render_data.get(child_id).map(|data| data.style().order).unwrap_or(0)
                 });
             }
 
-            if current_style.display == Display::None {
-                layout_style.display = taffy::style::Display::None;
-            } else if current_style.display != Display::Flex {
+            if !matches!(current_style.display, Display::Flex | Display::Grid) {
                 let mut has_inline_child = false;
                 for child_id in &children {
                     if let Some(child_data) = render_data.get(child_id) {
@@ -653,29 +891,34 @@ pub(crate) fn dom_to_taffy(
                 if has_inline_child || tag == "tr" {
                     layout_style.flex_direction = FlexDirection::Row;
                     layout_style.flex_wrap = FlexWrap::Wrap;
-                    if let Some(align) = current_style.text_align {
-                        match align {
-                            TextAlign::Right => layout_style.justify_content = Some(JustifyContent::FlexEnd),
-                            TextAlign::Center => layout_style.justify_content = Some(JustifyContent::Center),
-                            TextAlign::Left => layout_style.justify_content = Some(JustifyContent::FlexStart),
-                        }
+                    let rtl = current_style.direction == Direction::Rtl;
+                    match current_style.text_align {
+                        Some(TextAlign::Right) => layout_style.justify_content = Some(JustifyContent::FlexEnd),
+                        Some(TextAlign::Center) => layout_style.justify_content = Some(JustifyContent::Center),
+                        // Justified text is only spread by `line_alignment`, over the wrapped lines of a
+                        // single text node. A node on one line is a final line, and the words of mixed
+                        // inline runs aren't spread, so both start like unaligned text: on the right in
+                        // a right-to-left block.
+                        None | Some(TextAlign::Justify) if rtl => layout_style.justify_content = Some(JustifyContent::FlexEnd),
+                        Some(TextAlign::Left | TextAlign::Justify) => layout_style.justify_content = Some(JustifyContent::FlexStart),
+                        None => {}
                     }
                 } else {
                     layout_style.flex_direction = FlexDirection::Column;
                 }
             }
 
-            if parent_style.display != Display::Flex && current_style.display == Display::Block {
+            if !matches!(parent_style.display, Display::Flex | Display::Grid) && current_style.display == Display::Block {
                 if layout_style.size.width.is_auto() {
                     layout_style.size.width = Dimension::percent(1.0);
                 }
             }
 
             layout_style.border = taffy::geometry::Rect {
-                left: length(current_style.border_width),
-                right: length(current_style.border_width),
-                top: length(current_style.border_width),
-                bottom: length(current_style.border_width),
+                left: length(border_left),
+                right: length(border_right),
+                top: length(border_top),
+                bottom: length(border_bottom),
             };
 
             if current_style.direction == Direction::Rtl {
@@ -710,25 +953,25 @@ pub(crate) fn dom_to_taffy(
             if !layout_style.max_size.height.is_auto() {
                 let val = layout_style.max_size.height.value();
                 if layout_style.max_size.height == Dimension::length(val) {
-                    layout_style.max_size.height = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_top + current_style.padding_bottom);
+                    layout_style.max_size.height = Dimension::length(val + border_top + border_bottom + current_style.padding_top + current_style.padding_bottom);
                 }
             }
             if !layout_style.max_size.width.is_auto() {
                 let val = layout_style.max_size.width.value();
                 if layout_style.max_size.width == Dimension::length(val) {
-                    layout_style.max_size.width = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_left + current_style.padding_right);
+                    layout_style.max_size.width = Dimension::length(val + border_left + border_right + current_style.padding_left + current_style.padding_right);
                 }
             }
             if !layout_style.min_size.height.is_auto() {
                 let val = layout_style.min_size.height.value();
                 if layout_style.min_size.height == Dimension::length(val) {
-                    layout_style.min_size.height = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_top + current_style.padding_bottom);
+                    layout_style.min_size.height = Dimension::length(val + border_top + border_bottom + current_style.padding_top + current_style.padding_bottom);
                 }
             }
             if !layout_style.min_size.width.is_auto() {
                 let val = layout_style.min_size.width.value();
                 if layout_style.min_size.width == Dimension::length(val) {
-                    layout_style.min_size.width = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_left + current_style.padding_right);
+                    layout_style.min_size.width = Dimension::length(val + border_left + border_right + current_style.padding_left + current_style.padding_right);
                 }
             }
 
@@ -768,14 +1011,24 @@ pub(crate) fn dom_to_taffy(
         
         NodeData::Text { contents } => {
             let text = contents.borrow();
-            let normalized = super::normalize_text(&text);
+            let normalized = super::normalize_text(&text, current_style.white_space);
             
             if normalized.is_empty() {
+                // White space beside an inline element still separates its words
+                // from the text around it once `inline::flow` joins them.
+                if !matches!(current_style.display, Display::Flex | Display::Grid) && next_to_inline_element(handle) {
+                    let id = taffy.new_leaf(Style::default()).ok()?;
+                    node_to_handle.insert(id, handle.clone());
+                    return Some(id);
+                }
                 None
             } else {
-                let (width, height) = text_measurer.measure_text(&normalized, current_style.font_size, current_style.weight);
+                let (width, glyph_height) = text_measurer.measure_text_spaced(&normalized, current_style.font_size, current_style.font_variant(), current_style.word_spacing);
+                let height = current_style.line_height.resolve(glyph_height);
                 let text_layout_style = Style {
                     size: Size { width: length(width), height: length(height) },
+                    // Text that can't wrap overflows its container rather than shrinking.
+                    flex_shrink: if current_style.white_space == crate::style::WhiteSpace::Normal { 1.0 } else { 0.0 },
                     ..Style::default()
                 };
                 let id = taffy.new_leaf(text_layout_style.clone()).ok()?;
@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+/// Which way a character runs once its neighbours are taken into account.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Left,
+    Right,
+    Number,
+    Neutral,
+}
+
+fn class(c: char) -> Class {
+    match c as u32 {
+        // Hebrew, Arabic, Syriac, Thaana, NKo and the other right-to-left
+        // blocks, with their presentation forms.
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF => Class::Right,
+        _ if c.is_ascii_digit() => Class::Number,
+        _ if c.is_alphabetic() => Class::Left,
+        _ => Class::Neutral,
+    }
+}
+
+/// The same character facing the other way, for brackets inside a
+/// right-to-left run.
+fn mirrored(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
+
+/// One line of `text` in the order its characters are drawn, left to right,
+/// for a paragraph running right-to-left if `rtl`.
+///
+/// A reduced form of the Unicode bidirectional algorithm without explicit
+/// embeddings: right-to-left letters take level 1, left-to-right letters the
+/// even level above the paragraph's, and numbers stay left-to-right inside a
+/// right-to-left run. Bracket pairs go the way of what they enclose, and
+/// other neutrals between two runs going the same way join them; otherwise,
+/// and at the end of the line, they follow the paragraph. Runs are then
+/// reversed from the highest level down.
+pub(crate) fn visual_order(text: &str, rtl: bool) -> Cow<'_, str> {
+    let classes: Vec<Class> = text.chars().map(class).collect();
+    if !rtl && !classes.contains(&Class::Right) {
+        return Cow::Borrowed(text);
+    }
+    let base = u8::from(rtl);
+    let left = if rtl { 2 } else { 0 };
+
+    // Numbers take the direction of the letters before them.
+    let mut previous = if rtl { Class::Right } else { Class::Left };
+    let mut resolved: Vec<Class> = classes.iter()
+        .map(|&class| match class {
+            Class::Left | Class::Right => {
+                previous = class;
+                class
+            }
+            Class::Number if previous == Class::Right => Class::Right,
+            Class::Number => Class::Left,
+            Class::Neutral => Class::Neutral,
+        })
+        .collect();
+
+    // A bracket pair goes the paragraph's way if it holds a letter going that
+    // way, else the way of the letters it holds if those before it agree.
+    let paragraph = if rtl { Class::Right } else { Class::Left };
+    let chars: Vec<char> = text.chars().collect();
+    let mut open = Vec::new();
+    for i in 0..chars.len() {
+        match chars[i] {
+            '(' | '[' | '{' => open.push(i),
+            ')' | ']' | '}' => {
+                let Some(start) = open.iter().rposition(|&j| mirrored(chars[j]) == chars[i]) else { continue };
+                let first = open[start];
+                open.truncate(start);
+                let inside = &resolved[first + 1..i];
+                let class = if inside.contains(&paragraph) {
+                    paragraph
+                } else if let Some(&other) = inside.iter().find(|&&class| class != Class::Neutral) {
+                    let context = resolved[..first].iter().rev().copied().find(|&class| class != Class::Neutral).unwrap_or(paragraph);
+                    if context == other { other } else { paragraph }
+                } else {
+                    continue;
+                };
+                resolved[first] = class;
+                resolved[i] = class;
+            }
+            _ => {}
+        }
+    }
+
+    let mut levels: Vec<u8> = resolved.iter()
+        .map(|class| match class {
+            Class::Right => 1,
+            Class::Left => left,
+            _ => base,
+        })
+        .collect();
+    // Numbers in a right-to-left run still read left to right.
+    for (level, (&class, &resolved)) in levels.iter_mut().zip(classes.iter().zip(&resolved)) {
+        if class == Class::Number && resolved == Class::Right {
+            *level = 2;
+        }
+    }
+
+    let mut i = 0;
+    while i < resolved.len() {
+        if resolved[i] != Class::Neutral {
+            i += 1;
+            continue;
+        }
+        let end = (i..resolved.len()).find(|&j| resolved[j] != Class::Neutral).unwrap_or(resolved.len());
+        let before = if i == 0 { paragraph } else { resolved[i - 1] };
+        let after = resolved.get(end).copied();
+        let level = match after {
+            Some(after) if after == before => if before == Class::Right { 1 } else { left },
+            _ => base,
+        };
+        levels[i..end].fill(level);
+        i = end;
+    }
+
+    let mut chars: Vec<char> = chars.into_iter().zip(&levels).map(|(c, &level)| if level % 2 == 1 { mirrored(c) } else { c }).collect();
+    let max_level = levels.iter().copied().max().unwrap_or(0);
+    for level in (1..=max_level).rev() {
+        let mut i = 0;
+        while i < chars.len() {
+            if levels[i] < level {
+                i += 1;
+                continue;
+            }
+            let end = (i..chars.len()).find(|&j| levels[j] < level).unwrap_or(chars.len());
+            chars[i..end].reverse();
+            levels[i..end].reverse();
+            i = end;
+        }
+    }
+    Cow::Owned(chars.into_iter().collect())
+}
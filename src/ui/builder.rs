@@ -3,6 +3,7 @@ use crate::style::RenderData;
 use super::node_map::NodeMap;
 use super::metadata::NodeMetadata;
 use super::{Interaction, Handle};
+use super::attributes::{PSEUDO_AFTER_TAG, PSEUDO_BEFORE_TAG};
 
 pub struct UiBuilder {
     pub taffy: TaffyTree,
@@ -63,6 +64,15 @@ impl UiBuilder {
         (class, id, style, other_attrs)
     }
 
+    /// Widgets built directly through the builder API may omit the `type` attribute
+    /// that templates always carry; add it so style resolution picks the right widget.
+    fn ensure_input_type(other_attrs: &mut Option<Vec<(String, String)>>, input_type: &str) {
+        let attrs = other_attrs.get_or_insert_with(Vec::new);
+        if !attrs.iter().any(|(k, _)| k == "type") {
+            attrs.push(("type".to_string(), input_type.to_string()));
+        }
+    }
+
     pub fn create_element_cow(&mut self, tag: std::borrow::Cow<'static, str>, attrs: &mut [(std::borrow::Cow<'static, str>, std::borrow::Cow<'static, str>)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
         let (class, id_val, style, other_attrs) = Self::parse_attrs_cow(attrs);
@@ -81,7 +91,8 @@ impl UiBuilder {
 
     pub fn create_checkbox_cow(&mut self, checked: bool, attrs: &mut [(std::borrow::Cow<'static, str>, std::borrow::Cow<'static, str>)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs_cow(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs_cow(attrs);
+        Self::ensure_input_type(&mut other_attrs, "checkbox");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.checked = Some(checked);
         self.node_metadata.insert(id, metadata);
@@ -90,7 +101,8 @@ impl UiBuilder {
 
     pub fn create_slider_cow(&mut self, value: f32, attrs: &mut [(std::borrow::Cow<'static, str>, std::borrow::Cow<'static, str>)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs_cow(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs_cow(attrs);
+        Self::ensure_input_type(&mut other_attrs, "range");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.slider_value = Some(value);
         self.node_metadata.insert(id, metadata);
@@ -99,7 +111,8 @@ impl UiBuilder {
 
     pub fn create_input_text_cow(&mut self, value: std::borrow::Cow<'static, str>, attrs: &mut [(std::borrow::Cow<'static, str>, std::borrow::Cow<'static, str>)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs_cow(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs_cow(attrs);
+        Self::ensure_input_type(&mut other_attrs, "text");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.input_text = Some(value.into_owned());
         self.node_metadata.insert(id, metadata);
@@ -172,7 +185,8 @@ impl UiBuilder {
 
     pub fn create_checkbox(&mut self, checked: bool, attrs: &[(&str, &str)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs(attrs);
+        Self::ensure_input_type(&mut other_attrs, "checkbox");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.checked = Some(checked);
         self.node_metadata.insert(id, metadata);
@@ -181,7 +195,8 @@ impl UiBuilder {
 
     pub fn create_slider(&mut self, value: f32, attrs: &[(&str, &str)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs(attrs);
+        Self::ensure_input_type(&mut other_attrs, "range");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.slider_value = Some(value);
         self.node_metadata.insert(id, metadata);
@@ -190,7 +205,8 @@ impl UiBuilder {
 
     pub fn create_input_text(&mut self, value: &str, attrs: &[(&str, &str)]) -> NodeId {
         let id = self.taffy.new_leaf(Style::default()).unwrap();
-        let (class, id_val, style, other_attrs) = Self::parse_attrs(attrs);
+        let (class, id_val, style, mut other_attrs) = Self::parse_attrs(attrs);
+        Self::ensure_input_type(&mut other_attrs, "text");
         let mut metadata = NodeMetadata::new(std::borrow::Cow::Borrowed("input"), class, id_val, style, other_attrs);
         metadata.input_text = Some(value.to_string());
         self.node_metadata.insert(id, metadata);
@@ -233,6 +249,11 @@ impl UiBuilder {
     }
 }
 
+/// Whether `meta` is a synthetic element standing in for `::before` or `::after`.
+pub(crate) fn is_pseudo_element(meta: &NodeMetadata) -> bool {
+    meta.tag == PSEUDO_BEFORE_TAG || meta.tag == PSEUDO_AFTER_TAG
+}
+
 pub(crate) struct TaffyElementWrapper<'a> {
     pub node: NodeId,
     pub taffy: &'a TaffyTree,
@@ -263,7 +284,7 @@ impl<'a> simplecss::Element for TaffyElementWrapper<'a> {
         let idx = siblings.iter().position(|&x| x == self.node)?;
         if idx > 0 {
             for &sibling in siblings[..idx].iter().rev() {
-                if let Some(sibling_meta) = self.metadata.get(&sibling) {
+                if let Some(sibling_meta) = self.metadata.get(&sibling).filter(|meta| !is_pseudo_element(meta)) {
                     return Some(TaffyElementWrapper {
                         node: sibling,
                         taffy: self.taffy,
@@ -311,7 +332,7 @@ impl<'a> simplecss::Element for TaffyElementWrapper<'a> {
                 if let Some(parent) = self.taffy.parent(self.node) {
                     if let Ok(siblings) = self.taffy.children(parent) {
                         for &sibling in &siblings {
-                            if self.metadata.contains_key(&sibling) {
+                            if self.metadata.get(&sibling).is_some_and(|meta| !is_pseudo_element(meta)) {
                                 return sibling == self.node;
                             }
                         }
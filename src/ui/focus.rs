@@ -0,0 +1,68 @@
+use taffy::prelude::*;
+
+use super::{Interaction, NodeMap};
+use crate::graphics::{Color, DrawCommand, Rect};
+use crate::style::RenderData;
+
+const RING_WIDTH: f32 = 2.0;
+
+/// The focused node as drawn.
+#[derive(Clone, Copy)]
+pub(crate) struct Focus {
+    pub(crate) node: NodeId,
+    /// Whether a focused text input shows its caret.
+    pub(crate) caret_visible: bool,
+    /// Whether the node is a list row, filled to mark it active.
+    pub(crate) row: bool,
+}
+
+/// Nodes that take keyboard focus, in document order: text inputs and selects
+/// with an id and every element with an interaction, such as a button, unless
+/// `visibility: hidden` hides them.
+pub(crate) fn collect(taffy: &TaffyTree, root: NodeId, render_data: &NodeMap<RenderData>, interactions: &NodeMap<Interaction>) -> Vec<NodeId> {
+    let mut focusable = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let hidden = render_data.get(node).is_some_and(|data| data.style().hidden);
+        if !hidden && key(render_data, interactions, node).is_some() {
+            focusable.push(node);
+        }
+        let children = taffy.children(node).unwrap_or_default();
+        stack.extend(children.into_iter().rev());
+    }
+    focusable
+}
+
+/// What identifies a focusable node across rebuilds: the id of a text input
+/// or select, otherwise its interaction.
+pub(crate) fn key<'a>(render_data: &'a NodeMap<RenderData>, interactions: &'a NodeMap<Interaction>, node: NodeId) -> Option<&'a str> {
+    match render_data.get(node) {
+        Some(RenderData::TextInput(id, _, _)) if !id.is_empty() => Some(id),
+        Some(RenderData::Select(id, ..)) if !id.is_empty() => Some(id),
+        _ => interactions.get(node).map(String::as_str).filter(|i| !i.is_empty()),
+    }
+}
+
+/// The fill behind the content of the focused list row.
+pub(crate) fn row_highlight(rect: Rect, border_radius: f32) -> DrawCommand {
+    DrawCommand::DrawRect {
+        rect,
+        color: Some(Color::from_rgba8(0, 122, 255, 40)),
+        gradient: None,
+        border_radius,
+        border_width: 0.0,
+        border_color: None,
+    }
+}
+
+/// The outline drawn over the border of the focused element.
+pub(crate) fn ring(rect: Rect, border_radius: f32) -> DrawCommand {
+    DrawCommand::DrawRect {
+        rect,
+        color: None,
+        gradient: None,
+        border_radius,
+        border_width: RING_WIDTH,
+        border_color: Some(Color::from_rgba8(0, 122, 255, 255)),
+    }
+}
@@ -0,0 +1,47 @@
+use taffy::prelude::*;
+
+use super::NodeMap;
+use crate::css;
+use crate::style::{ContainerStyle, RenderData};
+
+/// Styles to show while a node with `data-hover-style` is hovered, for the node
+/// itself and the text directly inside it, which inherits its color.
+pub type HoverStyles = NodeMap<Vec<(NodeId, ContainerStyle)>>;
+
+/// Applies every node's `data-hover-style` declarations over its built style.
+/// Only drawing changes: properties that affect layout are not re-laid out.
+pub(crate) fn collect(taffy: &TaffyTree, render_data: &NodeMap<RenderData>, declarations: &NodeMap<String>) -> HoverStyles {
+    let mut hover_styles = NodeMap::new();
+    for (node, decls) in declarations {
+        let Some(base) = render_data.get(node).map(RenderData::style) else {
+            continue;
+        };
+        let mut hovered = base.clone();
+        let mut layout = Style::default();
+        css::parse_inline_style(decls, &mut hovered, &mut layout);
+
+        let mut styles = Vec::new();
+        for child in taffy.children(node).unwrap_or_default() {
+            if let Some(RenderData::Text(_, text_style)) = render_data.get(child) {
+                let mut text_hovered = text_style.clone();
+                if text_style.color == base.color {
+                    text_hovered.color = hovered.color;
+                }
+                styles.push((child, text_hovered));
+            }
+        }
+        styles.push((node, hovered));
+        hover_styles.insert(node, styles);
+    }
+    hover_styles
+}
+
+/// Swaps the hover styles of `node` with the ones in `render_data`; calling
+/// it again swaps the normal styles back.
+pub(crate) fn swap(render_data: &mut NodeMap<RenderData>, hover_styles: &mut HoverStyles, node: NodeId) {
+    for (target, style) in hover_styles.get_mut(node).into_iter().flatten() {
+        if let Some(data) = render_data.get_mut(*target) {
+            std::mem::swap(data.style_mut(), style);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::borrow::Cow;
+
+use taffy::prelude::*;
+
+use super::{Interaction, NodeMap};
+use crate::graphics::TextMeasurer;
+use crate::style::{ContainerStyle, Display, Position, RenderData, WhiteSpace};
+
+/// Elements whose text flows into the lines of the block around them.
+const INLINE_TAGS: &[&str] = &["span", "strong", "b", "em", "i", "a", "code", "small", "u"];
+
+/// One word of a paragraph, in the style of the text it came from.
+struct Word {
+    text: String,
+    style: ContainerStyle,
+    interaction: Option<Interaction>,
+    space_after: bool,
+}
+
+/// The words of a paragraph, and each node merged into them with the
+/// index of its first word.
+#[derive(Default)]
+struct Run {
+    words: Vec<Word>,
+    merged: Vec<(NodeId, usize)>,
+}
+
+/// The nodes `flow` merged into words, each with the leaf of its first word,
+/// if it had any words.
+pub(crate) struct Merged(Vec<(NodeId, Option<NodeId>)>);
+
+impl Merged {
+    /// Moves entries of merged nodes in `map` onto their first word, so that
+    /// e.g. the id of a link still finds where it starts.
+    pub(crate) fn rekey<T>(&self, map: &mut NodeMap<T>) {
+        for &(node, first_word) in &self.0 {
+            if let Some(value) = map.remove(node)
+                && let Some(leaf) = first_word
+            {
+                map.insert(leaf, value);
+            }
+        }
+    }
+
+    /// Removes the merged nodes from the tree. Called once the build makes
+    /// no more nodes, so that none reuses their slots under ids the maps
+    /// were keyed by.
+    pub(crate) fn remove(self, taffy: &mut TaffyTree) {
+        for (node, _) in self.0 {
+            let _ = taffy.remove(node);
+        }
+    }
+}
+
+/// Lays out every paragraph that mixes its own text with inline elements as
+/// one run of words. Each word becomes its own text leaf in the paragraph's wrapping
+/// row, so a `<strong>` inside a sentence continues the line the text before
+/// it ended on and wraps with it, instead of moving to a line of its own.
+///
+/// An inline element is only merged away if its box draws nothing itself:
+/// no background, border, padding or effects. Its words keep its
+/// interaction, so a click on any of them still reaches it. Elements in any
+/// of `kept`, e.g. those that stop propagation or are disabled, hold
+/// behaviour a word can't carry and keep their box. Paragraphs with
+/// anything else among their children, or `white-space: pre`, keep their
+/// boxes as built.
+///
+/// The merged nodes are detached and dropped from the maps given; the rest
+/// is left to the returned `Merged`.
+pub(crate) fn flow(
+    taffy: &mut TaffyTree,
+    render_data: &mut NodeMap<RenderData>,
+    base_styles: &mut NodeMap<(Style, ContainerStyle)>,
+    interactions: &mut NodeMap<Interaction>,
+    tags: &mut NodeMap<Cow<'static, str>>,
+    texts: &NodeMap<String>,
+    kept: &[&NodeMap<String>],
+    measurer: &impl TextMeasurer,
+) -> Merged {
+    let mut all_merged = Vec::new();
+    let paragraphs: Vec<NodeId> = render_data.iter()
+        .filter(|(node, data)| match data {
+            RenderData::Container(style) => {
+                !matches!(style.display, Display::Flex | Display::Grid)
+                    && style.white_space == WhiteSpace::Normal
+                    && !is_inline_tag(tags, *node)
+            }
+            _ => false,
+        })
+        .map(|(node, _)| node)
+        .collect();
+
+    for paragraph in paragraphs {
+        let children = taffy.children(paragraph).unwrap_or_default();
+        let mixed = children.iter().any(|&child| is_inline_tag(tags, child))
+            && children.iter().any(|&child| matches!(render_data.get(child), Some(RenderData::Text(..))));
+        if !mixed || !children.iter().all(|&child| is_inline_content(taffy, render_data, base_styles, tags, texts, kept, child)) {
+            continue;
+        }
+
+        let mut run = Run::default();
+        for &child in &children {
+            collect_words(taffy, render_data, interactions, texts, child, None, &mut run);
+        }
+        let Run { words, merged } = run;
+        for &(node, _) in &merged {
+            render_data.remove(node);
+            base_styles.remove(node);
+            interactions.remove(node);
+            tags.remove(node);
+        }
+
+        let count = words.len();
+        let mut leaves = Vec::with_capacity(count);
+        let mut word_leaves = Vec::with_capacity(count);
+        for (i, word) in words.into_iter().enumerate() {
+            let style = word.style;
+            let text = if word.space_after && i + 1 < count { word.text + " " } else { word.text };
+            let (width, glyph_height) = measurer.measure_text_spaced(&text, style.font_size, style.font_variant(), style.word_spacing);
+            let height = style.line_height.resolve(glyph_height);
+            let layout_style = Style {
+                size: Size { width: length(width), height: length(height) },
+                ..Style::default()
+            };
+            let Ok(leaf) = taffy.new_leaf(layout_style.clone()) else {
+                word_leaves.push(None);
+                continue;
+            };
+            word_leaves.push(Some(leaf));
+            if let Some(interaction) = word.interaction {
+                interactions.insert(leaf, interaction);
+            }
+            render_data.insert(leaf, RenderData::Text(text, style.clone()));
+            base_styles.insert(leaf, (layout_style, style));
+            tags.insert(leaf, Cow::Borrowed("#text"));
+            leaves.push(leaf);
+        }
+        let _ = taffy.set_children(paragraph, &leaves);
+        all_merged.extend(merged.into_iter().map(|(node, first)| (node, word_leaves.get(first).copied().flatten())));
+    }
+    Merged(all_merged)
+}
+
+/// Whether elements with this tag flow with the text around them.
+pub(crate) fn is_inline(tag: &str) -> bool {
+    INLINE_TAGS.contains(&tag)
+}
+
+fn is_inline_tag(tags: &NodeMap<Cow<'static, str>>, node: NodeId) -> bool {
+    tags.get(node).is_some_and(|tag| is_inline(tag))
+}
+
+/// Whether `node` is text, or an inline element drawing nothing of its own,
+/// in none of `kept`, and holding only such content.
+fn is_inline_content(
+    taffy: &TaffyTree,
+    render_data: &NodeMap<RenderData>,
+    base_styles: &NodeMap<(Style, ContainerStyle)>,
+    tags: &NodeMap<Cow<'static, str>>,
+    texts: &NodeMap<String>,
+    kept: &[&NodeMap<String>],
+    node: NodeId,
+) -> bool {
+    match render_data.get(node) {
+        Some(RenderData::Text(..)) => true,
+        None => texts.contains_key(node),
+        Some(RenderData::Container(style)) if is_inline_tag(tags, node) => {
+            let boxless = base_styles.get(node).is_some_and(|(layout, _)| {
+                layout.display != taffy::style::Display::None
+                    && layout.margin == Rect::zero()
+                    && layout.padding == Rect::zero()
+                    && layout.border == Rect::zero()
+                    && layout.size.width.is_auto()
+                    && layout.size.height.is_auto()
+            });
+            boxless
+                && !kept.iter().any(|map| map.contains_key(node))
+                && style.background_color.is_none()
+                && style.background_gradient.is_none()
+                && style.background_image.is_none()
+                && style.border_width == 0.0
+                && !style.has_side_borders()
+                && style.box_shadow.is_none()
+                && style.opacity == 1.0
+                && style.transform.is_none()
+                && !style.layer
+                && style.animation_name.is_none()
+                && style.position == Position::Static
+                && !style.is_floated
+                && taffy.children(node).unwrap_or_default().into_iter()
+                    .all(|child| is_inline_content(taffy, render_data, base_styles, tags, texts, kept, child))
+        }
+        _ => false,
+    }
+}
+
+/// Appends the words of `node` to `run`, and `node` and its descendants to
+/// its merged nodes. The interaction of the innermost element with one goes
+/// with each word.
+fn collect_words(
+    taffy: &TaffyTree,
+    render_data: &NodeMap<RenderData>,
+    interactions: &NodeMap<Interaction>,
+    texts: &NodeMap<String>,
+    node: NodeId,
+    interaction: Option<&Interaction>,
+    run: &mut Run,
+) {
+    run.merged.push((node, run.words.len()));
+    let words = &mut run.words;
+    match (render_data.get(node), texts.get(node)) {
+        // Text as written, for the white space around it; text left empty
+        // by trimming has no render data but still separates words.
+        (Some(RenderData::Text(..)) | None, Some(source)) => {
+            if source.starts_with(char::is_whitespace)
+                && let Some(previous) = words.last_mut()
+            {
+                previous.space_after = true;
+            }
+            let Some(RenderData::Text(_, style)) = render_data.get(node) else { return };
+            let first = words.len();
+            words.extend(source.split_whitespace().map(|word| Word {
+                text: word.to_string(),
+                style: style.clone(),
+                interaction: interaction.cloned(),
+                space_after: true,
+            }));
+            if words.len() > first
+                && !source.ends_with(char::is_whitespace)
+                && let Some(last) = words.last_mut()
+            {
+                last.space_after = false;
+            }
+        }
+        _ => {
+            let interaction = interactions.get(node).filter(|i| !i.is_empty()).or(interaction);
+            for child in taffy.children(node).unwrap_or_default() {
+                collect_words(taffy, render_data, interactions, texts, child, interaction, run);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::borrow::Cow;
+
+use taffy::prelude::*;
+
+use super::NodeMap;
+use crate::graphics::TextMeasurer;
+use crate::style::{ContainerStyle, ListStyle, RenderData};
+
+/// Adds the marker of every `li` as a text leaf positioned out of flow to
+/// its left, ending half an em before the item's content, so markers of any
+/// width line up on the content edge and hang in the list's padding.
+/// Decimal markers count the `li` siblings up to and including the item.
+pub(crate) fn add_markers(
+    taffy: &mut TaffyTree,
+    render_data: &mut NodeMap<RenderData>,
+    base_styles: &mut NodeMap<(Style, ContainerStyle)>,
+    tags: &mut NodeMap<Cow<'static, str>>,
+    measurer: &impl TextMeasurer,
+) {
+    let is_item = |node: NodeId| tags.get(node).is_some_and(|tag| tag == "li");
+    let mut items = Vec::new();
+    for (list, _) in tags.iter() {
+        let children = taffy.children(list).unwrap_or_default();
+        for (position, item) in children.into_iter().filter(|&child| is_item(child)).enumerate() {
+            let Some(RenderData::Container(style)) = render_data.get(item) else { continue };
+            let marker = match style.list_style {
+                ListStyle::None => continue,
+                ListStyle::Disc => "\u{2022}".to_string(),
+                ListStyle::Circle => "\u{25e6}".to_string(),
+                ListStyle::Square => "\u{25aa}".to_string(),
+                ListStyle::Decimal => format!("{}.", position + 1),
+            };
+            // Only what the item's text would inherit, none of its box.
+            items.push((item, marker, crate::defaults::get_default_style("div", style).container_style));
+        }
+    }
+
+    for (item, marker, style) in items {
+        let (width, glyph_height) = measurer.measure_text(&marker, style.font_size, style.font_variant());
+        let height = style.line_height.resolve(glyph_height);
+        let layout_style = Style {
+            position: taffy::style::Position::Absolute,
+            inset: taffy::geometry::Rect { left: length(-(width + style.font_size / 2.0)), right: auto(), top: length(0.0), bottom: auto() },
+            size: Size { width: length(width), height: length(height) },
+            ..Style::default()
+        };
+        let Ok(leaf) = taffy.new_leaf(layout_style.clone()) else { continue };
+        let _ = taffy.add_child(item, leaf);
+        render_data.insert(leaf, RenderData::Text(marker, style.clone()));
+        base_styles.insert(leaf, (layout_style, style));
+        tags.insert(leaf, Cow::Borrowed("#text"));
+    }
+}
@@ -3,10 +3,20 @@ pub mod metadata;
 pub mod builder;
 pub mod attributes;
 pub mod style_resolution;
+pub mod accessibility;
+pub mod text_wrap;
+pub mod hover;
+pub mod focus;
+pub mod select;
+pub mod bidi;
+pub mod inline;
+pub mod list;
 
 pub use node_map::{NodeMap, NodeMapIter, NodeMapValues};
 pub use metadata::NodeMetadata;
 pub use builder::UiBuilder;
+pub use accessibility::{AccessInfo, AccessNode};
+pub use text_wrap::TextWrap;
 
 use taffy::prelude::*;
 use taffy::TaffyError;
@@ -33,9 +43,10 @@ macro_rules! profile {
     ($($tt:tt)*) => {};
 }
 
-use crate::graphics::{Canvas, DrawCommand, Rect, TextMeasurer};
-use crate::style::{ContainerStyle, Overflow, RenderData};
+use crate::graphics::{BackgroundSize, Canvas, Color, DrawCommand, Orientation, Rect, Renderer, TextMeasurer};
+use crate::style::{BorderStyle, ContainerStyle, Direction, Overflow, RenderData, TextAlign, WhiteSpace};
 use crate::css;
+use crate::error::Error;
 
 pub type Interaction = String;
 
@@ -44,15 +55,62 @@ pub trait TemplateLayout {
     fn build_ui(&self, builder: &mut UiBuilder) -> NodeId;
 }
 
+/// A node as `Ui::node_key` names it, to find it again after a rebuild.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeKey {
+    Id(String),
+    Key(String),
+    /// A drag or click message, and which of the nodes with it.
+    Handler(String, usize),
+}
+
 pub struct Ui {
     pub taffy: TaffyTree,
     pub render_data: NodeMap<RenderData>,
     pub interactions: NodeMap<Interaction>,
+    /// Nodes by interaction, in document order; see `node_for_interaction`.
+    interaction_nodes: HashMap<Interaction, Vec<NodeId>>,
     pub scroll_offsets: NodeMap<(f32, f32)>,
     pub root: NodeId,
     pub node_to_handle: NodeMap<Handle>,
     pub base_styles: NodeMap<(Style, ContainerStyle)>,
     pub keyframes: HashMap<String, css::KeyframesAnimation>,
+    pub access_info: NodeMap<AccessInfo>,
+    /// `data-key` attribute values, for addressing nodes without knowing their `NodeId`.
+    pub keys: NodeMap<String>,
+    /// `id` attribute values; see `rect_for_id`.
+    pub ids: NodeMap<String>,
+    /// Tag names, `#text` for text nodes; see `build_debug_commands`.
+    pub tags: NodeMap<std::borrow::Cow<'static, str>>,
+    /// `data-on-scroll` ids of scroll containers that report their offset to the model.
+    pub scroll_listeners: NodeMap<String>,
+    /// `data-on-input` ids of text inputs that report every edit to the model.
+    pub input_listeners: NodeMap<String>,
+    /// `data-on-drag` messages of nodes that report pointer moves while
+    /// pressed; see `Runtime::captured_node`.
+    pub drag_listeners: NodeMap<String>,
+    /// Elements with `data-stop-propagation`, where a click stops bubbling; see `hit_path`.
+    pub stop_propagation: NodeMap<String>,
+    /// Lines of every text node that can wrap, re-broken by `compute_layout`.
+    pub text_wraps: NodeMap<TextWrap>,
+    /// While a node is hovered its hover styles sit in `render_data` and the
+    /// normal ones here, see `set_hovered`.
+    pub hover_styles: hover::HoverStyles,
+    pub hovered: Option<NodeId>,
+    /// Elements with `data-disabled`. They have no interaction, don't
+    /// show hover styles, and swallow clicks on anything inside them.
+    pub disabled: NodeMap<String>,
+    /// The node held down by the pointer, drawn pressed; see `set_pressed`.
+    pub pressed: Option<NodeId>,
+    /// The pressed node's style from before it was pressed.
+    pressed_style: Option<ContainerStyle>,
+    /// Nodes that take keyboard focus, in document order; see `focus::collect`.
+    pub focusable: Vec<NodeId>,
+    pub focused: Option<NodeId>,
+    /// Whether the focused text input shows its caret; toggled to blink it.
+    pub caret_visible: bool,
+    /// The select whose option list is showing, and the highlighted option.
+    pub open_select: Option<(NodeId, usize)>,
 }
 
 impl Ui {
@@ -62,7 +120,7 @@ impl Ui {
         measurer: &impl TextMeasurer,
         default_style: ContainerStyle,
         message_validator: &impl Fn(&str) -> bool,
-    ) -> Result<Self, TaffyError> {
+    ) -> Result<Self, Error> {
         profile!("ui_new_internal");
         let mut taffy = TaffyTree::new();
         let mut render_data = NodeMap::new();
@@ -73,7 +131,7 @@ impl Ui {
         let dom = parse_document(RcDom::default(), Default::default())
             .from_utf8()
             .read_from(&mut html.as_bytes())
-            .unwrap();
+            .map_err(|err| Error::Parse(err.to_string()))?;
 
         attributes::preprocess_dom(&dom.document);
 
@@ -84,11 +142,14 @@ impl Ui {
         
         let re_nth = regex::Regex::new(r":nth-child\(\s*(\d+)\s*\)").unwrap();
         let css_str = re_nth.replace_all(&css_str, ".nth-child-$1").into_owned();
-        let css_str = css_str.replace(":last-child", ".last-child");
+        let css_str = css_str.replace(":last-child", ".last-child").replace(":root", "html");
         let re_slash = regex::Regex::new(r"/[\d\.]+").unwrap();
         let css_str = re_slash.replace_all(&css_str, "").into_owned();
+        let css_str = attributes::rewrite_pseudo_elements(&css_str);
         
-        let stylesheet = simplecss::StyleSheet::parse(&css_str);
+        let mut stylesheet = simplecss::StyleSheet::parse(&css_str);
+        css::sort_by_specificity(&mut stylesheet);
+        attributes::insert_pseudo_elements(&dom.document, &stylesheet);
 
         let root = attributes::dom_to_taffy(
             &mut taffy, 
@@ -101,20 +162,81 @@ impl Ui {
             &stylesheet,
             &mut node_to_handle,
             &mut base_styles,
-        ).ok_or(TaffyError::ChildIndexOutOfBounds { parent: NodeId::new(0), child_index: 0, child_count: 0 })?;  
+        ).ok_or(Error::EmptyDocument)?;
+
+        let mut access_info = accessibility::collect_dynamic(&node_to_handle);
+        let mut keys = collect_attribute_dynamic(&node_to_handle, "data-key");
+        let mut ids = collect_attribute_dynamic(&node_to_handle, "id");
+        let mut tags = NodeMap::new();
+        let mut texts = NodeMap::new();
+        for (node, handle) in &node_to_handle {
+            match handle.data {
+                NodeData::Element { ref name, .. } => tags.insert(node, std::borrow::Cow::Owned(name.local.to_string())),
+                NodeData::Text { ref contents } => {
+                    texts.insert(node, contents.borrow().to_string());
+                    tags.insert(node, std::borrow::Cow::Borrowed("#text"))
+                }
+                _ => None,
+            };
+        }
+        let scroll_listeners = collect_attribute_dynamic(&node_to_handle, "data-on-scroll");
+        let input_listeners = collect_attribute_dynamic(&node_to_handle, "data-on-input");
+        let drag_listeners = collect_attribute_dynamic(&node_to_handle, "data-on-drag");
+        let stop_propagation = collect_attribute_dynamic(&node_to_handle, "data-stop-propagation");
+        let disabled = collect_attribute_dynamic(&node_to_handle, "data-disabled");
+        let hover_attrs = collect_attribute_dynamic(&node_to_handle, "data-hover-style");
+        let kept = [&scroll_listeners, &input_listeners, &drag_listeners, &stop_propagation, &disabled, &hover_attrs];
+        let merged = inline::flow(&mut taffy, &mut render_data, &mut base_styles, &mut interactions, &mut tags, &texts, &kept, measurer);
+        list::add_markers(&mut taffy, &mut render_data, &mut base_styles, &mut tags, measurer);
+        let text_wraps = text_wrap::collect(&mut taffy, &render_data, &mut base_styles, measurer);
+        text_wrap::collect_line_breaks(&mut taffy, &render_data, &mut base_styles, &tags, measurer);
+        merged.rekey(&mut ids);
+        merged.rekey(&mut keys);
+        merged.rekey(&mut access_info);
+        merged.remove(&mut taffy);
+        let hover_styles = hover::collect(&taffy, &render_data, &hover_attrs);
+        select::collect(&taffy, &mut render_data, &collect_attribute_dynamic(&node_to_handle, "value"));
+        let focusable = focus::collect(&taffy, root, &render_data, &interactions);
+        let interaction_nodes = index_interactions(&taffy, root, &interactions);
 
         Ok(Self {
             taffy,
             render_data,
             interactions,
+            interaction_nodes,
             scroll_offsets: NodeMap::new(),
             root,
             node_to_handle,
             base_styles,
             keyframes,
+            access_info,
+            keys,
+            ids,
+            tags,
+            scroll_listeners,
+            input_listeners,
+            drag_listeners,
+            stop_propagation,
+            text_wraps,
+            hover_styles,
+            hovered: None,
+            disabled,
+            pressed: None,
+            pressed_style: None,
+            focusable,
+            focused: None,
+            caret_visible: true,
+            open_select: None,
         })
     }
 
+    /// Builds a `Ui` from an HTML string for one-shot, non-interactive use:
+    /// default styles and every `data-on-click` message accepted as-is.
+    #[cfg(feature = "dynamic-parser")]
+    pub fn from_html(html: &str, measurer: &impl TextMeasurer) -> Result<Self, Error> {
+        Self::new(html, measurer, ContainerStyle::default(), &|_: &str| true)
+    }
+
     fn preprocess_compiled_tree(
         _taffy: &taffy::TaffyTree,
         node_metadata: &mut NodeMap<NodeMetadata>,
@@ -190,7 +312,7 @@ impl Ui {
         measurer: &impl TextMeasurer,
         default_style: ContainerStyle,
         message_validator: &impl Fn(&str) -> bool,
-    ) -> Result<Self, TaffyError> {
+    ) -> Result<Self, Error> {
         profile!("ui_new_compiled");
         let mut builder = UiBuilder::new();
         let root = {
@@ -213,14 +335,19 @@ impl Ui {
                 let css_str = css_str.replace(":last-child", ".last-child");
                 let re_slash = regex::Regex::new(r"/[\d\.]+").unwrap();
                 let css_str = re_slash.replace_all(&css_str, "").into_owned();
+                let rewritten = attributes::rewrite_pseudo_elements(&css_str);
+                let has_pseudo_elements = rewritten != css_str;
+                let css_str = rewritten;
                 
                 let static_css_str: &'static str = Box::leak(css_str.into_boxed_str());
-                let stylesheet = simplecss::StyleSheet::parse(static_css_str);
+                let mut stylesheet = simplecss::StyleSheet::parse(static_css_str);
+                css::sort_by_specificity(&mut stylesheet);
                 
                 let cached_val: &'static style_resolution::CachedStyles = Box::leak(Box::new(style_resolution::CachedStyles {
                     stylesheet,
                     keyframes,
                     has_nth_or_last_child,
+                    has_pseudo_elements,
                     style_cache: std::cell::RefCell::new(HashMap::with_capacity(128)),
                 }));
                 cache_guard.insert(stylesheet_str, cached_val);
@@ -231,6 +358,9 @@ impl Ui {
         if cached.has_nth_or_last_child {
             Self::preprocess_compiled_tree(&builder.taffy, &mut builder.node_metadata, root);
         }
+        if cached.has_pseudo_elements {
+            style_resolution::insert_pseudo_elements(&mut builder, root, &cached.stylesheet);
+        }
 
         let mut base_styles = NodeMap::with_capacity(128);
         let mut style_cache = cached.style_cache.borrow_mut();
@@ -252,19 +382,232 @@ impl Ui {
             );
         }
 
+        // `display: none` detached the subtrees it hides; nothing in them may
+        // register an id, key or listener below.
+        let mut attached = NodeMap::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            attached.insert(node, ());
+            stack.extend(builder.taffy.children(node).unwrap_or_default());
+        }
+        let detached: Vec<NodeId> = builder.node_metadata.iter()
+            .map(|(node, _)| node)
+            .filter(|&node| !attached.contains_key(node))
+            .collect();
+        for node in detached {
+            builder.node_metadata.remove(node);
+        }
+
+        let mut access_info = accessibility::collect_compiled(&builder.node_metadata);
+        let mut keys = collect_attribute_compiled(&builder.node_metadata, "data-key");
+        let mut ids = NodeMap::new();
+        let mut tags = NodeMap::new();
+        let mut texts = NodeMap::new();
+        for (node, meta) in &builder.node_metadata {
+            if let Some(id) = &meta.id {
+                ids.insert(node, id.to_string());
+            }
+            if let Some(text) = &meta.text {
+                texts.insert(node, text.to_string());
+            }
+            tags.insert(node, meta.tag.clone());
+        }
+        let scroll_listeners = collect_attribute_compiled(&builder.node_metadata, "data-on-scroll");
+        let input_listeners = collect_attribute_compiled(&builder.node_metadata, "data-on-input");
+        let drag_listeners = collect_attribute_compiled(&builder.node_metadata, "data-on-drag");
+        let stop_propagation = collect_attribute_compiled(&builder.node_metadata, "data-stop-propagation");
+        let disabled = collect_attribute_compiled(&builder.node_metadata, "data-disabled");
+        let hover_attrs = collect_attribute_compiled(&builder.node_metadata, "data-hover-style");
+        let kept = [&scroll_listeners, &input_listeners, &drag_listeners, &stop_propagation, &disabled, &hover_attrs];
+        let merged = inline::flow(&mut builder.taffy, &mut builder.render_data, &mut base_styles, &mut builder.interactions, &mut tags, &texts, &kept, measurer);
+        list::add_markers(&mut builder.taffy, &mut builder.render_data, &mut base_styles, &mut tags, measurer);
+        let text_wraps = text_wrap::collect(&mut builder.taffy, &builder.render_data, &mut base_styles, measurer);
+        text_wrap::collect_line_breaks(&mut builder.taffy, &builder.render_data, &mut base_styles, &tags, measurer);
+        merged.rekey(&mut ids);
+        merged.rekey(&mut keys);
+        merged.rekey(&mut access_info);
+        merged.remove(&mut builder.taffy);
+        let hover_styles = hover::collect(&builder.taffy, &builder.render_data, &hover_attrs);
+        select::collect(&builder.taffy, &mut builder.render_data, &collect_attribute_compiled(&builder.node_metadata, "value"));
+        let focusable = focus::collect(&builder.taffy, root, &builder.render_data, &builder.interactions);
+        let interaction_nodes = index_interactions(&builder.taffy, root, &builder.interactions);
+
         Ok(Self {
             taffy: builder.taffy,
             render_data: builder.render_data,
             interactions: builder.interactions,
+            interaction_nodes,
             scroll_offsets: NodeMap::new(),
             root,
             node_to_handle: builder.node_to_handle,
             base_styles,
             keyframes: cached.keyframes.clone(),
+            access_info,
+            keys,
+            ids,
+            tags,
+            scroll_listeners,
+            input_listeners,
+            drag_listeners,
+            stop_propagation,
+            text_wraps,
+            hover_styles,
+            hovered: None,
+            disabled,
+            pressed: None,
+            pressed_style: None,
+            focusable,
+            focused: None,
+            caret_visible: true,
+            open_select: None,
         })
     }
 
+    /// Shows the hover style of the innermost node under `(x, y)` that has
+    /// one. Returns whether the hovered node changed.
+    pub fn update_hover(&mut self, x: f32, y: f32) -> bool {
+        let node = self.hit_test_stack(x, y).into_iter().rev()
+            .find(|node| self.hover_styles.contains_key(*node) && !self.disabled.contains_key(*node));
+        self.set_hovered(node)
+    }
+
+    /// Swaps the styles of the previously hovered node back and those of
+    /// `node` in. Returns whether the hovered node changed.
+    pub fn set_hovered(&mut self, node: Option<NodeId>) -> bool {
+        if node == self.hovered {
+            return false;
+        }
+        // The pressed look goes over the hover style, so it comes off first.
+        let pressed = self.pressed;
+        self.set_pressed(None);
+        for node in [self.hovered, node].into_iter().flatten() {
+            hover::swap(&mut self.render_data, &mut self.hover_styles, node);
+        }
+        self.hovered = node;
+        self.set_pressed(pressed);
+        true
+    }
+
+    /// Draws `node` pressed, its background darkened, and the previously
+    /// pressed node as it was. Over their base styles, states go disabled,
+    /// then pressed, then hovered: a disabled node can't be pressed, and a
+    /// hovered one is darkened from its hover style. Returns whether the
+    /// pressed node changed.
+    pub fn set_pressed(&mut self, node: Option<NodeId>) -> bool {
+        let node = node.filter(|&node| !self.disabled.contains_key(node));
+        if node == self.pressed {
+            return false;
+        }
+        if let (Some(previous), Some(style)) = (self.pressed, self.pressed_style.take())
+            && let Some(data) = self.render_data.get_mut(previous)
+        {
+            *data.style_mut() = style;
+        }
+        self.pressed = node;
+        if let Some(data) = node.and_then(|node| self.render_data.get_mut(node)) {
+            let style = data.style_mut();
+            self.pressed_style = Some(style.clone());
+            style.background_color = Some(style.background_color.map_or(PRESSED_SHADE, |color| color.mix(Color::BLACK, 0.15)));
+        }
+        true
+    }
+
+    /// What finds `node` again in a rebuilt tree: its `id` or `data-key`,
+    /// else its drag or click message and how many nodes before it, in
+    /// document order, have the same one.
+    pub fn node_key(&self, node: NodeId) -> Option<NodeKey> {
+        if let Some(id) = self.ids.get(node) {
+            return Some(NodeKey::Id(id.clone()));
+        }
+        if let Some(key) = self.keys.get(node) {
+            return Some(NodeKey::Key(key.clone()));
+        }
+        let handler = self.handler(node)?;
+        let index = self.nodes_with_handler(handler).position(|n| n == node)?;
+        Some(NodeKey::Handler(handler.to_string(), index))
+    }
+
+    /// The node `node_key` gave `key`, if it is still there.
+    pub fn find_node(&self, key: &NodeKey) -> Option<NodeId> {
+        match key {
+            NodeKey::Id(id) => self.ids.iter().find(|(_, v)| *v == id).map(|(node, _)| node),
+            NodeKey::Key(key) => self.keys.iter().find(|(_, v)| *v == key).map(|(node, _)| node),
+            NodeKey::Handler(handler, index) => self.nodes_with_handler(handler).nth(*index),
+        }
+    }
+
+    fn handler(&self, node: NodeId) -> Option<&str> {
+        self.drag_listeners.get(node).or_else(|| self.interactions.get(node)).map(String::as_str)
+    }
+
+    fn nodes_with_handler<'a>(&'a self, handler: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        let mut stack = vec![self.root];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(self.taffy.children(node).unwrap_or_default().into_iter().rev());
+            Some(node)
+        })
+        .filter(move |&node| self.handler(node) == Some(handler))
+    }
+
+    /// The id of a focusable text input, or the interaction of any other
+    /// focusable node; it names the node across rebuilds.
+    pub fn focus_key(&self, node: NodeId) -> Option<&str> {
+        focus::key(&self.render_data, &self.interactions, node)
+    }
+
+    /// Focuses the first focusable node whose `focus_key` is `key`. Returns
+    /// false, leaving focus as it was, if there is none.
+    pub fn focus(&mut self, key: &str) -> bool {
+        let Some(node) = self.focusable.iter().copied().find(|&node| self.focus_key(node) == Some(key)) else {
+            return false;
+        };
+        self.focused = Some(node);
+        true
+    }
+
+    /// Moves focus to the next focusable node in document order, or the
+    /// previous one, wrapping around at either end. Returns the new focus.
+    pub fn focus_next(&mut self, backwards: bool) -> Option<NodeId> {
+        let count = self.focusable.len();
+        let current = self.focused.and_then(|node| self.focusable.iter().position(|&n| n == node));
+        let next = match (current, backwards) {
+            (_, _) if count == 0 => return None,
+            (Some(i), false) => (i + 1) % count,
+            (Some(i), true) => (i + count - 1) % count,
+            (None, false) => 0,
+            (None, true) => count - 1,
+        };
+        self.focused = Some(self.focusable[next]);
+        self.focused
+    }
+
+    /// Whether `node` is a list row: an `li` with an interaction, directly
+    /// inside a `ul` or `ol`.
+    pub fn is_row(&self, node: NodeId) -> bool {
+        let tag = |node: NodeId| self.tags.get(node).map_or("", |tag| tag);
+        tag(node) == "li"
+            && self.interactions.get(node).is_some_and(|i| !i.is_empty())
+            && self.taffy.parent(node).is_some_and(|list| matches!(tag(list), "ul" | "ol"))
+    }
+
+    /// The row after the focused one in its list, or the one before, staying
+    /// put at either end. `None` unless a row is focused, e.g. by Tab or a
+    /// click, so arrow keys elsewhere still reach the model.
+    pub fn step_row(&self, backwards: bool) -> Option<NodeId> {
+        let current = self.focused.filter(|&node| self.is_row(node))?;
+        let rows: Vec<NodeId> = self.taffy.children(self.taffy.parent(current)?).ok()?.into_iter().filter(|&node| self.is_row(node)).collect();
+        let index = rows.iter().position(|&row| row == current)?;
+        let next = if backwards { index.saturating_sub(1) } else { (index + 1).min(rows.len() - 1) };
+        Some(rows[next])
+    }
+
     pub fn handle_scroll(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) -> bool {
+        self.scroll_at(x, y, delta_x, delta_y).is_some()
+    }
+
+    /// Scrolls the innermost scroll container under `(x, y)` and returns it.
+    pub fn scroll_at(&mut self, x: f32, y: f32, delta_x: f32, delta_y: f32) -> Option<NodeId> {
         profile!("handle_scroll");
         if let Some(mut node) = hit_test_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0) {
             loop {
@@ -277,20 +620,7 @@ impl Ui {
                          if let Ok(layout) = self.taffy.layout(node) {
                              let container_width = layout.size.width;
                              let container_height = layout.size.height;
-                             
-                             let mut content_width = 0.0f32;
-                             let mut content_height = 0.0f32;
-                             
-                             if let Ok(children) = self.taffy.children(node) {
-                                 for child in children {
-                                     if let Ok(child_layout) = self.taffy.layout(child) {
-                                         let right = child_layout.location.x + child_layout.size.width;
-                                         let bottom = child_layout.location.y + child_layout.size.height;
-                                         if right > content_width { content_width = right; }
-                                         if bottom > content_height { content_height = bottom; }
-                                     }
-                                 }
-                             }
+                             let (content_width, content_height) = content_size(&self.taffy, node);
                              
                              let max_sx = (content_width - container_width).max(0.0);
                              let max_sy = (content_height - container_height).max(0.0);
@@ -300,7 +630,7 @@ impl Ui {
                          }
 
                          self.scroll_offsets.insert(node, (sx, sy));
-                         return true;
+                         return Some(node);
                     }
                 }
                 
@@ -311,59 +641,333 @@ impl Ui {
                 }
             }
         }
-        false
+        None
     }
 
+    /// Finds the node with this `data-key`, falling back to the node whose
+    /// interaction (e.g. `data-on-click`) message is `key`.
+    pub fn node_by_key(&self, key: &str) -> Option<NodeId> {
+        self.keys.iter().find(|(_, v)| *v == key).map(|(k, _)| k)
+            .or_else(|| self.node_for_interaction(key))
+    }
+
+    /// The node with this interaction; when several share it, the first in
+    /// document order.
+    pub fn node_for_interaction(&self, interaction: &str) -> Option<NodeId> {
+        self.interaction_nodes.get(interaction)?.first().copied()
+    }
+
+    /// Bounds in window coordinates of the node found by
+    /// `node_for_interaction`, e.g. to place a tooltip next to a button.
+    pub fn bounds_of_interaction(&self, interaction: &str) -> Option<Rect> {
+        self.node_bounds(self.node_for_interaction(interaction)?)
+    }
+
+    /// Scroll offset of the node found by `node_by_key`; `(0.0, 0.0)` if it
+    /// has not been scrolled, `None` if no node matches.
+    pub fn scroll_offset_of(&self, key: &str) -> Option<(f32, f32)> {
+        let node = self.node_by_key(key)?;
+        Some(self.scroll_offsets.get(&node).copied().unwrap_or((0.0, 0.0)))
+    }
+
+    /// Scrolls every scroll container around the node with this interaction,
+    /// innermost first, just far enough on both axes to show the node.
     pub fn scroll_into_view(&mut self, interaction_id: &str) {
-        let node_opt = self.interactions.iter().find(|(_, v)| *v == interaction_id).map(|(k, _)| k);
-        if let Some(node) = node_opt {
-             let mut current = node;
-             while let Some(parent) = self.taffy.parent(current) {
-                 if let Some(RenderData::Container(style)) = self.render_data.get(&parent) {
-                     if style.overflow == Overflow::Scroll {
-                          if let Ok(parent_layout) = self.taffy.layout(parent) {
-                              if let Ok(node_layout) = self.taffy.layout(node) {
-                                   let (ck, cy) = self.scroll_offsets.get(&parent).copied().unwrap_or((0.0, 0.0));
-                                   let node_y = node_layout.location.y; 
-                                   
-                                   let mut new_y = cy;
-                                   if node_y < cy {
-                                       new_y = node_y;
-                                   } else if node_y + node_layout.size.height > cy + parent_layout.size.height {
-                                       new_y = node_y + node_layout.size.height - parent_layout.size.height;
-                                   }
-                                   self.scroll_offsets.insert(parent, (ck, new_y));
-                                   return;
-                              }
-                          }
-                     }
-                 }
-                 current = parent;
-             }
+        let Some(node) = self.node_for_interaction(interaction_id) else {
+            return;
+        };
+        let Ok(layout) = self.taffy.layout(node) else {
+            return;
+        };
+        // The node's box relative to the current ancestor's content.
+        let mut target = Rect { x: layout.location.x, y: layout.location.y, width: layout.size.width, height: layout.size.height };
+        let mut current = node;
+        while let Some(parent) = self.taffy.parent(current) {
+            let Ok(parent_layout) = self.taffy.layout(parent).copied() else {
+                return;
+            };
+            if let Some(RenderData::Container(style)) = self.render_data.get(&parent)
+                && style.overflow == Overflow::Scroll
+            {
+                let (sx, sy) = self.scroll_offsets.get(&parent).copied().unwrap_or((0.0, 0.0));
+                let (content_width, content_height) = content_size(&self.taffy, parent);
+                let new_x = reveal(sx, target.x, target.width, parent_layout.size.width, content_width);
+                let new_y = reveal(sy, target.y, target.height, parent_layout.size.height, content_height);
+                self.scroll_offsets.insert(parent, (new_x, new_y));
+                target.x -= new_x;
+                target.y -= new_y;
+            }
+            target.x += parent_layout.location.x;
+            target.y += parent_layout.location.y;
+            current = parent;
         }
     }
 
     pub fn compute_layout(&mut self, available_space: Size<AvailableSpace>) -> Result<(), TaffyError> {
         profile!("taffy_layout");
         self.taffy.compute_layout(self.root, available_space)?;
+        if text_wrap::apply(&mut self.taffy, &mut self.text_wraps) {
+            self.taffy.compute_layout(self.root, available_space)?;
+        }
         Ok(())
     }
 
-    pub fn build_commands(&self, _canvases: &HashMap<String, Canvas>, focused_id: Option<&str>) -> Vec<DrawCommand> {
-        layout_to_draw_commands(
+    pub fn build_commands(&self, _canvases: &HashMap<String, Canvas>) -> Vec<DrawCommand> {
+        let mut commands = layout_to_draw_commands(
             &self.taffy,
             self.root,
             &self.render_data,
             &self.scroll_offsets,
+            &self.text_wraps,
             0.0,
             0.0,
-            focused_id
-        )
+            self.focused.map(|node| focus::Focus { node, caret_visible: self.caret_visible, row: self.is_row(node) }),
+        );
+        if let Some((node, highlighted)) = self.open_select
+            && let Some(RenderData::Select(_, options, _, style)) = self.render_data.get(node)
+            && let Some(bounds) = self.node_bounds(node)
+        {
+            commands.extend(select::popup(bounds, options, highlighted, style));
+        }
+        commands
+    }
+
+    /// Outlines of every node's box, each labelled with its tag and size, to
+    /// draw over `build_commands` when a layout misbehaves.
+    pub fn build_debug_commands(&self) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        for (node, rect) in &self.layout_rects() {
+            if rect.width <= 0.0 && rect.height <= 0.0 {
+                continue;
+            }
+            commands.push(DrawCommand::DrawRect {
+                rect: *rect,
+                color: None,
+                gradient: None,
+                border_radius: 0.0,
+                border_width: 1.0,
+                border_color: Some(DEBUG_COLOR),
+            });
+            let tag = self.tags.get(node).map_or("?", |tag| tag);
+            commands.push(DrawCommand::DrawText {
+                text: format!("{tag} {}x{}", rect.width.round(), rect.height.round()),
+                rect: Rect { x: rect.x + 2.0, y: rect.y + 1.0, width: (rect.width - 4.0).max(0.0), height: DEBUG_FONT_SIZE },
+                color: DEBUG_COLOR,
+                font_size: DEBUG_FONT_SIZE,
+                weight: 0,
+                word_spacing: 0.0,
+                max_width: Some((rect.width - 4.0).max(0.0)),
+            });
+        }
+        commands
+    }
+
+    /// Shows the option list of the select `node`, highlighting the selected
+    /// option.
+    pub fn open_select(&mut self, node: NodeId) {
+        if let Some(RenderData::Select(_, options, selected, _)) = self.render_data.get(node) {
+            let highlighted = options.iter().position(|(value, _)| value == selected).unwrap_or(0);
+            self.open_select = Some((node, highlighted));
+        }
+    }
+
+    /// The select with id `id`, to reopen its list after a rebuild.
+    pub fn select_by_id(&self, id: &str) -> Option<NodeId> {
+        self.render_data.iter().find_map(|(node, data)| match data {
+            RenderData::Select(select_id, ..) if select_id == id => Some(node),
+            _ => None,
+        })
+    }
+
+    /// Index of the option of the open select under `(x, y)`, if any.
+    pub fn select_option_at(&self, x: f32, y: f32) -> Option<usize> {
+        let (node, _) = self.open_select?;
+        let Some(RenderData::Select(_, options, _, _)) = self.render_data.get(node) else {
+            return None;
+        };
+        select::option_rows(self.node_bounds(node)?, options.len())
+            .position(|row| x >= row.x && x <= row.x + row.width && y >= row.y && y < row.y + row.height)
+    }
+
+    /// Draws the current layout in a single full repaint, without canvases.
+    /// Call `compute_layout` first.
+    pub fn render(&self, renderer: &mut impl Renderer) {
+        let canvases = HashMap::new();
+        let mut commands = self.build_commands(&canvases);
+        if let Some(color) = self.document_background() {
+            commands.insert(0, DrawCommand::Clear { color });
+        }
+        renderer.render(&commands, &canvases, None);
+    }
+
+    /// Background color of the document: the first one set on the root or,
+    /// following first children, on `html` and `body`.
+    pub fn document_background(&self) -> Option<Color> {
+        let mut node = self.root;
+        loop {
+            if let Some(color) = self.render_data.get(node).and_then(|d| d.style().background_color) {
+                return Some(color);
+            }
+            node = *self.taffy.children(node).ok()?.first()?;
+            if !self.tags.get(node).is_some_and(|tag| tag == "html" || tag == "body") {
+                return None;
+            }
+        }
     }
 
     pub fn hit_test(&self, x: f32, y: f32) -> Option<(Interaction, NodeId)> {
          profile!("hit_test");
-         if let Some(clicked_node) = hit_test_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0) {
+         let clicked = hit_test_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0);
+         self.resolve_interaction(clicked)
+    }
+
+    /// The text node under `(x, y)` and the offset in characters of the
+    /// boundary nearest the point, e.g. to place a caret or start a selection
+    /// with a click. Wrapped lines count one space between them, as
+    /// `data-selection` does. None if the hit isn't over text.
+    ///
+    /// Offsets are measured along the logical text, so they only match what
+    /// is drawn for left-to-right text; in text that `bidi::visual_order`
+    /// reorders, the offset doesn't follow the reordered runs.
+    pub fn text_hit(&self, x: f32, y: f32, measurer: &impl TextMeasurer) -> Option<(NodeId, usize)> {
+        profile!("text_hit");
+        let node = hit_test_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0)?;
+        let Some(RenderData::Text(text, style)) = self.render_data.get(node) else {
+            return None;
+        };
+        let rect = self.node_bounds(node)?;
+        let Some(wrap) = self.text_wraps.get(node).filter(|wrap| !wrap.is_single_line()) else {
+            return Some((node, char_offset_at(text, x - rect.x, style, style.word_spacing, measurer)));
+        };
+        let last = wrap.lines.len() - 1;
+        let i = (((y - rect.y) / wrap.line_height).max(0.0) as usize).min(last);
+        let line_start: usize = wrap.lines[..i].iter().map(|line| line.chars().count() + 1).sum();
+        let line = &wrap.lines[i];
+        let free = (rect.width - wrap.line_widths[i]).max(0.0);
+        let (dx, word_spacing) = line_alignment(style, line, i == last, free);
+        Some((node, line_start + char_offset_at(line, x - rect.x - dx, style, word_spacing, measurer)))
+    }
+
+    /// Bounds of `node` in window coordinates, as drawn after scrolling.
+    pub fn node_bounds(&self, node: NodeId) -> Option<Rect> {
+        let layout = self.taffy.layout(node).ok()?;
+        let (mut x, mut y) = (layout.location.x, layout.location.y);
+        let mut current = node;
+        while let Some(parent) = self.taffy.parent(current) {
+            let parent_layout = self.taffy.layout(parent).ok()?;
+            let (sx, sy) = self.content_scroll(parent);
+            x += parent_layout.location.x - sx;
+            y += parent_layout.location.y - sy;
+            current = parent;
+        }
+        Some(Rect { x, y, width: layout.size.width, height: layout.size.height })
+    }
+
+    /// Bounds of every node in window coordinates, as `node_bounds` gives
+    /// them, for inspectors and debugging overlays. Call `compute_layout`
+    /// first; no renderer is involved.
+    pub fn layout_rects(&self) -> NodeMap<Rect> {
+        let mut rects = NodeMap::new();
+        let mut stack = vec![(self.root, 0.0, 0.0)];
+        while let Some((node, parent_x, parent_y)) = stack.pop() {
+            let Ok(layout) = self.taffy.layout(node) else { continue };
+            let (x, y) = (parent_x + layout.location.x, parent_y + layout.location.y);
+            rects.insert(node, Rect { x, y, width: layout.size.width, height: layout.size.height });
+            let (sx, sy) = self.content_scroll(node);
+            for child in self.taffy.children(node).unwrap_or_default() {
+                stack.push((child, x - sx, y - sy));
+            }
+        }
+        rects
+    }
+
+    /// How far the content of `node` is scrolled, if it is a scroll container.
+    fn content_scroll(&self, node: NodeId) -> (f32, f32) {
+        match (self.render_data.get(node), self.scroll_offsets.get(node)) {
+            (Some(RenderData::Container(style)), Some(&offset)) if style.overflow == Overflow::Scroll => offset,
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Bounds in window coordinates of the element with this `id` attribute,
+    /// e.g. to anchor a popup to it.
+    pub fn rect_for_id(&self, id: &str) -> Option<Rect> {
+        let node = self.ids.iter().find(|(_, v)| *v == id).map(|(k, _)| k)?;
+        self.node_bounds(node)
+    }
+
+    /// Every node containing `(x, y)`, root first, down to the deepest node
+    /// `hit_test` would start from. Interactive or not, all are included.
+    pub fn hit_test_stack(&self, x: f32, y: f32) -> Vec<NodeId> {
+        profile!("hit_test_stack");
+        let mut stack = Vec::new();
+        hit_test_stack_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0, &mut stack);
+        stack
+    }
+
+    /// The cursor of the deepest node at `(x, y)`, `Default` over nothing.
+    pub fn cursor_at(&self, x: f32, y: f32) -> crate::style::Cursor {
+        self.hit_test_stack(x, y).into_iter().rev()
+            .find_map(|node| self.render_data.get(node))
+            .map(|data| data.style().cursor)
+            .unwrap_or_default()
+    }
+
+    /// Like `hit_test`, but text and containers that paint nothing and carry no
+    /// interaction are transparent to clicks, so a hit falls through to whatever
+    /// is visually underneath them instead of to a decorative overlay.
+    pub fn hit_test_opaque(&self, x: f32, y: f32) -> Option<(Interaction, NodeId)> {
+         profile!("hit_test");
+         let clicked = hit_test_filtered(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0, &|node| self.is_hit_opaque(node));
+         self.resolve_interaction(clicked)
+    }
+
+    fn is_hit_opaque(&self, node: NodeId) -> bool {
+        if self.interactions.contains_key(node) {
+            return true;
+        }
+        match self.render_data.get(node) {
+            Some(RenderData::Text(..)) | None => false,
+            Some(RenderData::Container(style)) => {
+                style.background_color.is_some()
+                    || style.background_gradient.is_some()
+                    || style.background_image.is_some()
+                    || style.border_width > 0.0
+                    || style.has_side_borders()
+                    || style.overflow == Overflow::Scroll
+            }
+            Some(_) => true,
+        }
+    }
+
+    /// Interactions of the node at `(x, y)` and its ancestors, innermost
+    /// first, up to and including the first element with
+    /// `data-stop-propagation`. None inside a `data-disabled` element.
+    /// `hit_test` returns only the first of them.
+    pub fn hit_path(&self, x: f32, y: f32) -> Vec<Interaction> {
+        hit_test_recursive(&self.taffy, self.root, &self.scroll_offsets, &self.render_data, x, y, 0.0, 0.0)
+            .map_or_else(Vec::new, |node| self.interaction_path(node))
+    }
+
+    /// Like `hit_path`, from the node a hit test returned.
+    pub(crate) fn interaction_path(&self, node: NodeId) -> Vec<Interaction> {
+        let mut path = Vec::new();
+        for node in std::iter::successors(Some(node), |&node| self.taffy.parent(node)) {
+            if self.disabled.contains_key(node) {
+                return Vec::new();
+            }
+            if let Some(interaction) = self.interactions.get(node).filter(|i| !i.is_empty()) {
+                path.push(interaction.clone());
+            }
+            if self.stop_propagation.contains_key(node) {
+                break;
+            }
+        }
+        path
+    }
+
+    fn resolve_interaction(&self, clicked: Option<NodeId>) -> Option<(Interaction, NodeId)> {
+         if let Some(clicked_node) = clicked {
              let mut current = Some(clicked_node);
              while let Some(node) = current {
                  if let Some(act) = self.interactions.get(&node) {
@@ -377,7 +981,159 @@ impl Ui {
     }
 }
 
-pub(crate) fn normalize_text(text: &str) -> std::borrow::Cow<'_, str> {
+fn index_interactions(taffy: &TaffyTree, root: NodeId, interactions: &NodeMap<Interaction>) -> HashMap<Interaction, Vec<NodeId>> {
+    let mut index: HashMap<Interaction, Vec<NodeId>> = HashMap::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if let Some(interaction) = interactions.get(node) {
+            index.entry(interaction.clone()).or_default().push(node);
+        }
+        stack.extend(taffy.children(node).unwrap_or_default().into_iter().rev());
+    }
+    index
+}
+
+fn collect_attribute_compiled(node_metadata: &NodeMap<NodeMetadata>, name: &str) -> NodeMap<String> {
+    let mut values = NodeMap::new();
+    for (node, meta) in node_metadata {
+        if let Some((_, value)) = meta.other_attrs.iter().flatten().find(|(k, _)| k == name) {
+            values.insert(node, value.clone());
+        }
+    }
+    values
+}
+
+#[cfg(feature = "dynamic-parser")]
+fn collect_attribute_dynamic(node_to_handle: &NodeMap<Handle>, name: &str) -> NodeMap<String> {
+    let mut values = NodeMap::new();
+    for (node, handle) in node_to_handle {
+        if let NodeData::Element { ref attrs, .. } = handle.data
+            && let Some(attr) = attrs.borrow().iter().find(|a| a.name.local.as_ref() == name)
+        {
+            values.insert(node, attr.value.to_string());
+        }
+    }
+    values
+}
+
+/// Lays out an HTML fragment on its own under `MaxContent` and returns the
+/// `(width, height)` it occupies, e.g. to size a popover to its content.
+#[cfg(feature = "dynamic-parser")]
+pub fn measure_fragment(html: &str, measurer: &impl TextMeasurer) -> (f32, f32) {
+    let Ok(mut ui) = Ui::from_html(html, measurer) else {
+        return (0.0, 0.0);
+    };
+    if ui.compute_layout(Size::MAX_CONTENT).is_err() {
+        return (0.0, 0.0);
+    }
+    ui.taffy.layout(ui.root)
+        .map(|l| (l.size.width, l.size.height))
+        .unwrap_or((0.0, 0.0))
+}
+
+/// Outlines and labels of `Ui::build_debug_commands`.
+const DEBUG_COLOR: Color = Color { r: 255, g: 0, b: 255, a: 200 };
+const DEBUG_FONT_SIZE: f32 = 10.0;
+
+/// The background of a pressed node that has none of its own.
+const PRESSED_SHADE: Color = Color { r: 0, g: 0, b: 0, a: 30 };
+
+/// Fill behind selected text.
+const SELECTION_COLOR: Color = Color { r: 0, g: 122, b: 255, a: 80 };
+
+/// Highlights the characters `range.0..range.1` of `text`, drawn in `rect`,
+/// if any of them are in it. The range is measured along the logical text,
+/// so it is only placed right for left-to-right text, not for runs that
+/// `bidi::visual_order` reorders.
+fn push_text_selection(text: &str, range: (usize, usize), rect: Rect, style: &ContainerStyle, word_spacing: f32, commands: &mut Vec<DrawCommand>) {
+    if range.0 >= range.1.min(text.chars().count()) {
+        return;
+    }
+    commands.push(DrawCommand::DrawTextSelection {
+        rect,
+        text: text.to_string(),
+        range,
+        color: SELECTION_COLOR,
+        font_size: style.font_size,
+        weight: style.font_variant(),
+        word_spacing,
+    });
+}
+
+/// The outline of a box: a stroke of `outline_width` centred half its width
+/// outside `rect`, so it lies entirely beyond the border.
+fn push_outline(style: &ContainerStyle, rect: Rect, commands: &mut Vec<DrawCommand>) {
+    let half = style.outline_width / 2.0;
+    commands.push(DrawCommand::DrawRect {
+        rect: rect.inflate(half),
+        color: None,
+        gradient: None,
+        border_radius: if style.border_radius > 0.0 { style.border_radius + half } else { 0.0 },
+        border_width: style.outline_width,
+        border_color: Some(style.outline_color.unwrap_or(style.color)),
+    });
+}
+
+/// A line under `width` of the text drawn in `rect`, along the bottom of
+/// its glyphs.
+fn push_underline(rect: Rect, width: f32, style: &ContainerStyle, commands: &mut Vec<DrawCommand>) {
+    let thickness = (style.font_size / 16.0).max(1.0);
+    commands.push(DrawCommand::DrawRect {
+        rect: Rect { x: rect.x, y: rect.y + rect.height - thickness, width, height: thickness },
+        color: Some(style.color),
+        gradient: None,
+        border_radius: 0.0,
+        border_width: 0.0,
+        border_color: None,
+    });
+}
+
+/// How far a wrapped line with `free` room left in its box is shifted, and
+/// the word spacing it is drawn with.
+fn line_alignment(style: &ContainerStyle, line: &str, is_last: bool, free: f32) -> (f32, f32) {
+    match style.text_align {
+        Some(TextAlign::Center) => (free / 2.0, style.word_spacing),
+        Some(TextAlign::Right) => (free, style.word_spacing),
+        // All but the last line spread their spaces to fill the box.
+        Some(TextAlign::Justify) if !is_last && line.contains(' ') => {
+            (0.0, style.word_spacing + free / line.matches(' ').count() as f32)
+        }
+        // Lines start on the right in a right-to-left paragraph.
+        None | Some(TextAlign::Justify) if style.direction == Direction::Rtl => (free, style.word_spacing),
+        _ => (0.0, style.word_spacing),
+    }
+}
+
+/// Offset of the character boundary in `text` nearest to `x`, measured from
+/// where the text starts.
+fn char_offset_at(text: &str, x: f32, style: &ContainerStyle, word_spacing: f32, measurer: &impl TextMeasurer) -> usize {
+    let mut spacing = 0.0;
+    measurer.text_layout(text, style.font_size, style.font_variant()).iter()
+        .take_while(|&&(c, left, advance)| {
+            let middle = left + spacing + advance / 2.0;
+            if c == ' ' {
+                spacing += word_spacing;
+            }
+            middle < x
+        })
+        .count()
+}
+
+/// Expands `{value}`, `{max}` and `{percent}` in a `data-label` format.
+fn format_value_label(format: &str, value: f32, max: f32) -> String {
+    let percent = if max > 0.0 { value / max * 100.0 } else { 0.0 };
+    format
+        .replace("{value}", &value.to_string())
+        .replace("{max}", &max.to_string())
+        .replace("{percent}", &format!("{:.0}", percent))
+}
+
+/// Collapses runs of white space in `text` to single spaces and trims it,
+/// unless `white_space` is `Pre`, which keeps the text as written.
+pub(crate) fn normalize_text(text: &str, white_space: WhiteSpace) -> std::borrow::Cow<'_, str> {
+    if white_space == WhiteSpace::Pre {
+        return std::borrow::Cow::Borrowed(text);
+    }
     let mut needs_normalization = false;
     let mut last_was_space = false;
     let mut is_first = true;
@@ -466,24 +1222,177 @@ fn layout_to_draw_commands(
     root: NodeId,
     render_data: &NodeMap<RenderData>,
     scroll_offsets: &NodeMap<(f32, f32)>,
+    text_wraps: &NodeMap<TextWrap>,
     offset_x: f32,
     offset_y: f32,
-    focused_id: Option<&str>,
+    focus: Option<focus::Focus>,
 ) -> Vec<DrawCommand> {
     let mut commands = Vec::new();
-    traverse_layout(taffy, root, render_data, scroll_offsets, offset_x, offset_y, &mut commands, focused_id);
+    traverse_layout(taffy, root, render_data, scroll_offsets, text_wraps, offset_x, offset_y, 1.0, &mut commands, focus);
     commands
 }
 
+/// The scroll offset along one axis that shows `start..start + length` in a
+/// viewport `view` long, moving as little as possible from `offset`.
+fn reveal(offset: f32, start: f32, length: f32, view: f32, content: f32) -> f32 {
+    let new_offset = if start < offset {
+        start
+    } else if start + length > offset + view {
+        start + length - view
+    } else {
+        offset
+    };
+    new_offset.clamp(0.0, (content - view).max(0.0))
+}
+
+/// Extent of a node's children from its top-left corner, i.e. how far its
+/// content can be scrolled.
+fn content_size(taffy: &TaffyTree, node: NodeId) -> (f32, f32) {
+    let mut content_width = 0.0f32;
+    let mut content_height = 0.0f32;
+    for child in taffy.children(node).unwrap_or_default() {
+        if let Ok(child_layout) = taffy.layout(child) {
+            content_width = content_width.max(child_layout.location.x + child_layout.size.width);
+            content_height = content_height.max(child_layout.location.y + child_layout.size.height);
+        }
+    }
+    (content_width, content_height)
+}
+
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const SCROLLBAR_MIN_THUMB: f32 = 16.0;
+
+/// Pushes a scrollbar along the right edge of a scroll container if its
+/// content overflows vertically, and along the bottom if horizontally. With
+/// both, each stops short of the corner, so they don't overlap. The thumb's
+/// length is the visible fraction of the content, its position the scroll
+/// offset.
+fn push_scrollbars(rect: Rect, content: (f32, f32), offset: (f32, f32), commands: &mut Vec<DrawCommand>) {
+    let (vertical, horizontal) = (content.1 > rect.height, content.0 > rect.width);
+    if vertical {
+        let height = if horizontal { (rect.height - SCROLLBAR_WIDTH).max(0.0) } else { rect.height };
+        let track = Rect::new(rect.x + rect.width - SCROLLBAR_WIDTH, rect.y, SCROLLBAR_WIDTH, height);
+        let length = (track.height * rect.height / content.1).clamp(SCROLLBAR_MIN_THUMB.min(track.height), track.height);
+        let progress = (offset.1 / (content.1 - rect.height)).clamp(0.0, 1.0);
+        commands.push(DrawCommand::DrawScrollbar {
+            rect: track,
+            thumb_rect: Rect { y: track.y + (track.height - length) * progress, height: length, ..track },
+            orientation: Orientation::Vertical,
+        });
+    }
+    if horizontal {
+        let width = if vertical { (rect.width - SCROLLBAR_WIDTH).max(0.0) } else { rect.width };
+        let track = Rect::new(rect.x, rect.y + rect.height - SCROLLBAR_WIDTH, width, SCROLLBAR_WIDTH);
+        let length = (track.width * rect.width / content.0).clamp(SCROLLBAR_MIN_THUMB.min(track.width), track.width);
+        let progress = (offset.0 / (content.0 - rect.width)).clamp(0.0, 1.0);
+        commands.push(DrawCommand::DrawScrollbar {
+            rect: track,
+            thumb_rect: Rect { x: track.x + (track.width - length) * progress, width: length, ..track },
+            orientation: Orientation::Horizontal,
+        });
+    }
+}
+
+/// Draws `background-image` inside the padding box, clipped to it, so the
+/// image neither covers the border nor gets the box's rounded corners once
+/// `background-size` has shrunk it.
+fn push_background_image(style: &ContainerStyle, rect: Rect, commands: &mut Vec<DrawCommand>) {
+    let Some(src) = &style.background_image else {
+        return;
+    };
+    let [top, right, bottom, left] = style.border_widths();
+    let padding_box = Rect {
+        x: rect.x + left,
+        y: rect.y + top,
+        width: (rect.width - left - right).max(0.0),
+        height: (rect.height - top - bottom).max(0.0),
+    };
+    let border_radius = (style.border_radius - top.max(right).max(bottom).max(left)).max(0.0);
+    commands.push(DrawCommand::Clip { rect: padding_box, border_radius });
+    commands.push(DrawCommand::DrawImage {
+        src: src.clone(),
+        rect: padding_box,
+        border_radius: 0.0,
+        src_rect: None,
+        tint: None,
+        opacity: 1.0,
+        size: style.background_size,
+        position: style.background_position,
+    });
+    commands.push(DrawCommand::PopClip);
+}
+
+/// Draws each border edge as a filled bar along its side, so a single edge
+/// (e.g. the underline of an active tab) can be styled independently. Dashed
+/// and dotted edges are split into one bar or dot per dash.
+fn push_side_borders(style: &ContainerStyle, rect: Rect, commands: &mut Vec<DrawCommand>) {
+    if style.border_style == BorderStyle::None {
+        return;
+    }
+    let [top, right, bottom, left] = style.border_widths();
+    let edges = [
+        Rect { x: rect.x, y: rect.y, width: rect.width, height: top },
+        Rect { x: rect.x + rect.width - right, y: rect.y, width: right, height: rect.height },
+        Rect { x: rect.x, y: rect.y + rect.height - bottom, width: rect.width, height: bottom },
+        Rect { x: rect.x, y: rect.y, width: left, height: rect.height },
+    ];
+    for (side, edge) in edges.into_iter().enumerate() {
+        if edge.width <= 0.0 || edge.height <= 0.0 {
+            continue;
+        }
+        let color = style.border_side_colors[side].or(style.border_color).unwrap_or(style.color);
+        let horizontal = side % 2 == 0;
+        let thickness = if horizontal { edge.height } else { edge.width };
+        let (dash, gap, border_radius) = match style.border_style {
+            BorderStyle::Dashed => (thickness * 3.0, thickness * 2.0, 0.0),
+            BorderStyle::Dotted => (thickness, thickness, thickness / 2.0),
+            BorderStyle::Solid | BorderStyle::None => (f32::INFINITY, 0.0, 0.0),
+        };
+        for segment in dash_segments(edge, horizontal, dash, gap) {
+            commands.push(DrawCommand::DrawRect {
+                rect: segment,
+                color: Some(color),
+                gradient: None,
+                border_radius,
+                border_width: 0.0,
+                border_color: None,
+            });
+        }
+    }
+}
+
+/// Splits `edge` along its length into dashes of `dash` separated by roughly
+/// `gap`, stretching the gaps so that both ends of the edge get a full dash.
+fn dash_segments(edge: Rect, horizontal: bool, dash: f32, gap: f32) -> Vec<Rect> {
+    let length = if horizontal { edge.width } else { edge.height };
+    if dash >= length {
+        return vec![edge];
+    }
+    let count = ((length + gap) / (dash + gap)).floor().max(2.0);
+    let step = dash + (length - count * dash) / (count - 1.0);
+    (0..count as usize)
+        .map(|i| {
+            let start = i as f32 * step;
+            if horizontal {
+                Rect { x: edge.x + start, width: dash, ..edge }
+            } else {
+                Rect { y: edge.y + start, height: dash, ..edge }
+            }
+        })
+        .collect()
+}
+
 fn traverse_layout(
     taffy: &TaffyTree,
     root: NodeId,
     render_data: &NodeMap<RenderData>,
     scroll_offsets: &NodeMap<(f32, f32)>,
+    text_wraps: &NodeMap<TextWrap>,
     offset_x: f32,
     offset_y: f32,
+    opacity: f32,
     commands: &mut Vec<DrawCommand>,
-    focused_id: Option<&str>,
+    focus: Option<focus::Focus>,
 ) {
     let layout = match taffy.layout(root) {
         Ok(l) => l,
@@ -498,42 +1407,160 @@ fn traverse_layout(
     let mut overflow = Overflow::Visible;
     let rect = Rect { x, y, width, height };
 
+    let layer = matches!(render_data.get(&root), Some(RenderData::Container(style)) if style.layer);
+    if layer {
+        commands.push(DrawCommand::BeginLayer { rect });
+    }
+
+    // Opacity compounds down the tree, so the node's own drawing is faded by the product.
+    let opacity = opacity * render_data.get(&root).map_or(1.0, |data| data.style().opacity);
+    let first_command = commands.len();
+    let hidden = render_data.get(&root).is_some_and(|data| data.style().hidden);
+
+    // The transform covers the node's own drawing and its text, not its child elements.
+    let transform = render_data.get(&root).and_then(|data| data.style().transform_in(rect));
+    if let Some(matrix) = transform {
+        commands.push(DrawCommand::PushTransform { matrix, rect });
+    }
+
     if let Some(data) = render_data.get(&root) {
         let maybe_style = match data {
             RenderData::Container(style) => Some(style),
             RenderData::Text(_, style) => Some(style),
+            RenderData::Select(_, _, _, style) => Some(style),
             _ => None,
         };
 
         if let Some(style) = maybe_style {
             overflow = style.overflow;
-            if style.background_color.is_some() || style.background_gradient.is_some() || style.border_width > 0.0 {
-                 commands.push(DrawCommand::DrawRect {
-                    rect,
-                    color: style.background_color,
-                    gradient: style.background_gradient.clone(),
-                    border_radius: style.border_radius,
-                    border_width: style.border_width,
-                    border_color: style.border_color,
+        }
+
+        // A hidden node still clips and scrolls its children, but draws nothing itself.
+        if let Some(style) = maybe_style.filter(|_| !hidden) {
+            if style.backdrop_blur > 0.0 {
+                commands.push(DrawCommand::BackdropBlur { rect, radius: style.backdrop_blur });
+            }
+            if let Some(shadow) = style.box_shadow {
+                commands.push(DrawCommand::DrawShadow {
+                    rect: Rect { x: rect.x + shadow.offset_x, y: rect.y + shadow.offset_y, ..rect }.inflate(shadow.spread),
+                    border_radius: (style.border_radius + shadow.spread).max(0.0),
+                    blur: shadow.blur,
+                    color: shadow.color,
                 });
             }
+            if style.has_side_borders() || style.border_style != BorderStyle::Solid {
+                if style.background_color.is_some() || style.background_gradient.is_some() {
+                    commands.push(DrawCommand::DrawRect {
+                        rect,
+                        color: style.background_color,
+                        gradient: style.background_gradient.clone(),
+                        border_radius: style.border_radius,
+                        border_width: 0.0,
+                        border_color: None,
+                    });
+                }
+                push_background_image(style, rect, commands);
+                push_side_borders(style, rect, commands);
+            } else {
+                if style.background_color.is_some() || style.background_gradient.is_some() || style.border_width > 0.0 {
+                    commands.push(DrawCommand::DrawRect {
+                        rect,
+                        color: style.background_color,
+                        gradient: style.background_gradient.clone(),
+                        border_radius: style.border_radius,
+                        border_width: style.border_width,
+                        border_color: style.border_color,
+                    });
+                }
+                push_background_image(style, rect, commands);
+            }
+        }
+
+        if let Some(style) = maybe_style.filter(|style| !hidden && style.outline_width > 0.0) {
+            push_outline(style, rect, commands);
+        }
+
+        let focused = focus.filter(|focus| focus.node == root && !hidden);
+        if focused.is_some_and(|focus| focus.row) {
+            commands.push(focus::row_highlight(rect, data.style().border_radius));
         }
 
         match data {
+            _ if hidden => {}
             RenderData::Text(text, style) => {
-                commands.push(DrawCommand::DrawText {
-                    text: text.clone(),
-                    rect,
-                    color: style.color,
-                    font_size: style.font_size,
-                    weight: style.weight,
-                });
+                if let Some(wrap) = text_wraps.get(&root)
+                    && !wrap.is_single_line()
+                {
+                    let last = wrap.lines.len() - 1;
+                    let rtl = style.direction == Direction::Rtl;
+                    // Offset of each line's first character, counting one space between lines.
+                    let mut line_start = 0;
+                    for (i, (line, line_width)) in wrap.lines.iter().zip(&wrap.line_widths).enumerate() {
+                        // The box spans the container, so each line is aligned within it.
+                        let free = (rect.width - line_width).max(0.0);
+                        let (dx, word_spacing) = line_alignment(style, line, i == last, free);
+                        let line_rect = Rect {
+                            x: rect.x + dx,
+                            y: rect.y + i as f32 * wrap.line_height + (wrap.line_height - wrap.glyph_height) / 2.0,
+                            width: rect.width - dx,
+                            height: wrap.glyph_height,
+                        };
+                        if let Some((start, end)) = style.text_selection {
+                            let range = (start.saturating_sub(line_start), end.saturating_sub(line_start));
+                            push_text_selection(line, range, line_rect, style, word_spacing, commands);
+                        }
+                        line_start += line.chars().count() + 1;
+                        commands.push(DrawCommand::DrawText {
+                            text: bidi::visual_order(line, rtl).into_owned(),
+                            rect: line_rect,
+                            color: style.color,
+                            font_size: style.font_size,
+                            weight: style.font_variant(),
+                            word_spacing,
+                            max_width: None,
+                        });
+                        if style.underline {
+                            // Justified lines are as wide as their box.
+                            let spread = (word_spacing - style.word_spacing) * line.matches(' ').count() as f32;
+                            push_underline(line_rect, line_width + spread, style, commands);
+                        }
+                    }
+                } else {
+                    // The glyphs sit in the middle of the line box.
+                    let rect = match text_wraps.get(&root) {
+                        Some(wrap) if wrap.glyph_height != wrap.line_height => {
+                            Rect { y: rect.y + (wrap.line_height - wrap.glyph_height) / 2.0, height: wrap.glyph_height, ..rect }
+                        }
+                        _ => rect,
+                    };
+                    if let Some(range) = style.text_selection {
+                        push_text_selection(text, range, rect, style, style.word_spacing, commands);
+                    }
+                    let max_width = ellipsis_width(taffy, root, render_data, scroll_offsets, offset_x, rect);
+                    commands.push(DrawCommand::DrawText {
+                        text: bidi::visual_order(text, style.direction == Direction::Rtl).into_owned(),
+                        rect,
+                        color: style.color,
+                        font_size: style.font_size,
+                        weight: style.font_variant(),
+                        word_spacing: style.word_spacing,
+                        max_width,
+                    });
+                    if style.underline {
+                        push_underline(rect, max_width.map_or(rect.width, |max| max.min(rect.width)), style, commands);
+                    }
+                }
             },
             RenderData::Image(src, style) => {
                  commands.push(DrawCommand::DrawImage {
                     src: src.clone(),
                     rect,
                     border_radius: style.border_radius,
+                    src_rect: style.object_view_box,
+                    tint: if style.is_icon { Some(style.fill.unwrap_or(style.color)) } else { style.fill },
+                    opacity: 1.0,
+                    size: BackgroundSize::Stretch,
+                    position: crate::style::BACKGROUND_POSITION_TOP_LEFT,
                 });
             },
             RenderData::Checkbox(checked, style) => {
@@ -548,6 +1575,7 @@ fn traverse_layout(
                     rect,
                     value: *value,
                     color: style.color,
+                    label: style.value_label.as_deref().map(|f| format_value_label(f, *value, 1.0)),
                 });
             },
             RenderData::Progress(value, max, style) => {
@@ -556,6 +1584,7 @@ fn traverse_layout(
                     value: *value,
                     max: *max,
                     color: style.color,
+                    label: style.value_label.as_deref().map(|f| format_value_label(f, *value, *max)),
                 });
             },
             RenderData::Canvas(id, _) => {
@@ -564,45 +1593,69 @@ fn traverse_layout(
                     rect,
                 });
             },
-            RenderData::TextInput(id, text, style) => {
-                let is_focused = focused_id == Some(id.as_str()) && !id.is_empty();
-                
-                let focus_outline_width = if is_focused { 2.0 } else { 0.0 };
-                let focus_outline_color = if is_focused { Some(crate::Color::from_rgba8(0, 122, 255, 255)) } else { None };
-
+            RenderData::Select(_, options, selected, style) => {
+                select::draw_selected(rect, options, selected, style, commands);
+            },
+            RenderData::TextInput(_, text, style) => {
                 commands.push(DrawCommand::DrawRect {
                     rect,
                     color: style.background_color,
                     gradient: style.background_gradient.clone(),
                     border_radius: style.border_radius,
-                    border_width: if is_focused { focus_outline_width } else { style.border_width },
-                    border_color: if is_focused { focus_outline_color } else { style.border_color },
+                    border_width: style.border_width,
+                    border_color: style.border_color,
                 });
 
+                let text_rect = Rect {
+                    x: rect.x + 8.0,
+                    y: rect.y + 5.0,
+                    width: rect.width - 16.0,
+                    height: rect.height - 10.0,
+                };
                 if let Some(t) = text {
                     if !t.is_empty() {
-                        let text_rect = Rect {
-                            x: rect.x + 8.0,
-                            y: rect.y + 5.0,
-                            width: rect.width - 16.0,
-                            height: rect.height - 10.0,
-                        };
                         commands.push(DrawCommand::DrawText {
                             text: t.clone(),
                             rect: text_rect,
                             color: style.color,
                             font_size: style.font_size,
-                            weight: style.weight,
+                            weight: style.font_variant(),
+                            word_spacing: style.word_spacing,
+                            max_width: None,
                         });
                     }
                 }
+                if focused.is_some_and(|focus| focus.caret_visible) {
+                    commands.push(DrawCommand::DrawCaret {
+                        rect: text_rect,
+                        text: text.clone().unwrap_or_default(),
+                        color: style.color,
+                        font_size: style.font_size,
+                        weight: style.font_variant(),
+                    });
+                }
             },
             _ => {} 
         }
+
+        if focused.is_some() {
+            commands.push(focus::ring(rect, data.style().border_radius));
+        }
+    }
+
+    if transform.is_some() {
+        commands.push(DrawCommand::PopTransform);
+    }
+
+    if opacity < 1.0 {
+        for command in &mut commands[first_command..] {
+            command.fade(opacity);
+        }
     }
 
     if overflow != Overflow::Visible {
-        commands.push(DrawCommand::Clip { rect });
+        let border_radius = render_data.get(&root).map_or(0.0, |data| data.style().border_radius);
+        commands.push(DrawCommand::Clip { rect, border_radius });
     }
 
     let mut child_offset_x = x;
@@ -615,15 +1668,65 @@ fn traverse_layout(
         }
     }
 
-    if let Ok(children) = taffy.children(root) {
-        for child in children {
-            traverse_layout(taffy, child, render_data, scroll_offsets, child_offset_x, child_offset_y, commands, focused_id);
+    // A select draws its options itself, in the list it opens.
+    let is_select = matches!(render_data.get(&root), Some(RenderData::Select(..)));
+    if !is_select && let Ok(children) = taffy.children(root) {
+        for child in paint_order(render_data, children) {
+            let text_transform = transform.filter(|_| matches!(render_data.get(child), Some(RenderData::Text(..))));
+            if let Some(matrix) = text_transform {
+                commands.push(DrawCommand::PushTransform { matrix, rect });
+            }
+            traverse_layout(taffy, child, render_data, scroll_offsets, text_wraps, child_offset_x, child_offset_y, opacity, commands, focus);
+            if text_transform.is_some() {
+                commands.push(DrawCommand::PopTransform);
+            }
         }
     }
 
+    if overflow == Overflow::Scroll && !hidden && !render_data.get(&root).is_some_and(|data| data.style().hide_scrollbar) {
+        let offset = scroll_offsets.get(&root).copied().unwrap_or((0.0, 0.0));
+        push_scrollbars(rect, content_size(taffy, root), offset, commands);
+    }
+
     if overflow != Overflow::Visible {
         commands.push(DrawCommand::PopClip);
     }
+
+    if layer {
+        commands.push(DrawCommand::EndLayer);
+    }
+}
+
+/// The width a single line of text at `rect` has before it overflows its
+/// parent, if the parent clips it with `text-overflow: ellipsis`.
+/// `offset_x` is where the parent places its children: its left edge less
+/// its horizontal scroll.
+fn ellipsis_width(
+    taffy: &TaffyTree,
+    text: NodeId,
+    render_data: &NodeMap<RenderData>,
+    scroll_offsets: &NodeMap<(f32, f32)>,
+    offset_x: f32,
+    rect: Rect,
+) -> Option<f32> {
+    let parent = taffy.parent(text)?;
+    let style = render_data.get(parent)?.style();
+    if !style.text_overflow || style.overflow == Overflow::Visible {
+        return None;
+    }
+    let scroll_x = if style.overflow == Overflow::Scroll { scroll_offsets.get(parent).map_or(0.0, |offset| offset.0) } else { 0.0 };
+    let parent_x = offset_x + scroll_x;
+    let layout = taffy.layout(parent).ok()?;
+    let content_right = parent_x + layout.size.width - layout.padding.right - layout.border.right;
+    (rect.x + rect.width > content_right).then_some(content_right - rect.x)
+}
+
+/// `children` in the order they paint: by `z-index`, then document order.
+/// Only siblings are reordered, as if every element were a stacking context.
+/// Hit tests walk it backwards so the topmost sibling takes the hit.
+fn paint_order(render_data: &NodeMap<RenderData>, mut children: Vec<NodeId>) -> Vec<NodeId> {
+    children.sort_by_key(|&child| render_data.get(child).map_or(0, |data| data.style().z_index));
+    children
 }
 
 pub fn hit_test_recursive(
@@ -635,6 +1738,24 @@ pub fn hit_test_recursive(
     y: f32,
     abs_x: f32,
     abs_y: f32,
+) -> Option<NodeId> {
+    hit_test_filtered(taffy, root, scroll_offsets, render_data, x, y, abs_x, abs_y, &|_| true)
+}
+
+/// Hit test where nodes rejected by `accepts` never take the hit themselves,
+/// though their descendants still can. Descendants are only searched inside
+/// a node's box, which is also its clip rect when it clips, so content
+/// scrolled or overflowing out of view can't be hit.
+fn hit_test_filtered(
+    taffy: &TaffyTree,
+    root: NodeId,
+    scroll_offsets: &NodeMap<(f32, f32)>,
+    render_data: &NodeMap<RenderData>,
+    x: f32,
+    y: f32,
+    abs_x: f32,
+    abs_y: f32,
+    accepts: &dyn Fn(NodeId) -> bool,
 ) -> Option<NodeId> {
     let layout = taffy.layout(root).ok()?;
     let left = abs_x + layout.location.x;
@@ -662,13 +1783,61 @@ pub fn hit_test_recursive(
         }
 
         if let Ok(children) = taffy.children(root) {
-             for child in children.iter().rev() {
-                 if let Some(hit) = hit_test_recursive(taffy, *child, scroll_offsets, render_data, x, y, child_abs_x, child_abs_y) {
+             for child in paint_order(render_data, children).iter().rev() {
+                 if let Some(hit) = hit_test_filtered(taffy, *child, scroll_offsets, render_data, x, y, child_abs_x, child_abs_y, accepts) {
                      return Some(hit);
                  }
              }
         }
-        return Some(root);
+        let hidden = render_data.get(&root).is_some_and(|data| data.style().hidden);
+        return (accepts(root) && !hidden).then_some(root);
     }
     None
 }
+
+fn hit_test_stack_recursive(
+    taffy: &TaffyTree,
+    root: NodeId,
+    scroll_offsets: &NodeMap<(f32, f32)>,
+    render_data: &NodeMap<RenderData>,
+    x: f32,
+    y: f32,
+    abs_x: f32,
+    abs_y: f32,
+    stack: &mut Vec<NodeId>,
+) -> bool {
+    let Ok(layout) = taffy.layout(root) else {
+        return false;
+    };
+    let left = abs_x + layout.location.x;
+    let top = abs_y + layout.location.y;
+    if x < left || x > left + layout.size.width || y < top || y > top + layout.size.height {
+        return false;
+    }
+    // Hidden nodes are left out, and only hold the hit through a visible descendant.
+    let hidden = render_data.get(&root).is_some_and(|data| data.style().hidden);
+    if !hidden {
+        stack.push(root);
+    }
+
+    let (mut child_abs_x, mut child_abs_y) = (left, top);
+    let overflow = match render_data.get(&root) {
+        Some(RenderData::Container(style)) | Some(RenderData::TextInput(_, _, style)) => style.overflow,
+        _ => Overflow::Visible,
+    };
+    if overflow == Overflow::Scroll
+        && let Some((sx, sy)) = scroll_offsets.get(&root)
+    {
+        child_abs_x -= sx;
+        child_abs_y -= sy;
+    }
+
+    if let Ok(children) = taffy.children(root) {
+        for child in paint_order(render_data, children).iter().rev() {
+            if hit_test_stack_recursive(taffy, *child, scroll_offsets, render_data, x, y, child_abs_x, child_abs_y, stack) {
+                return true;
+            }
+        }
+    }
+    !hidden
+}
@@ -0,0 +1,99 @@
+use taffy::prelude::*;
+
+use super::NodeMap;
+use crate::graphics::{Color, DrawCommand, Rect};
+use crate::style::{ContainerStyle, RenderData};
+
+const LABEL_PADDING: f32 = 8.0;
+
+/// Fills every select's options from its `option` children: the label is the
+/// option's text and the value its `value` attribute, or the label without
+/// one. A select with no value yet shows its first option.
+pub(crate) fn collect(taffy: &TaffyTree, render_data: &mut NodeMap<RenderData>, values: &NodeMap<String>) {
+    let selects: Vec<NodeId> = render_data.iter().filter(|(_, data)| matches!(data, RenderData::Select(..))).map(|(node, _)| node).collect();
+    for node in selects {
+        let options: Vec<(String, String)> = taffy.children(node).unwrap_or_default().into_iter()
+            .map(|option| {
+                let label = option_label(taffy, render_data, option);
+                let value = values.get(option).cloned().unwrap_or_else(|| label.clone());
+                (value, label)
+            })
+            .collect();
+        if let Some(RenderData::Select(_, slot, selected, _)) = render_data.get_mut(node) {
+            if selected.is_empty()
+                && let Some((value, _)) = options.first()
+            {
+                selected.clone_from(value);
+            }
+            *slot = options;
+        }
+    }
+}
+
+fn option_label(taffy: &TaffyTree, render_data: &NodeMap<RenderData>, option: NodeId) -> String {
+    taffy.children(option).unwrap_or_default().into_iter()
+        .filter_map(|child| match render_data.get(child) {
+            Some(RenderData::Text(text, _)) => Some(text.trim()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The label of the selected option, drawn inside the closed select.
+pub(crate) fn draw_selected(rect: Rect, options: &[(String, String)], selected: &str, style: &ContainerStyle, commands: &mut Vec<DrawCommand>) {
+    if let Some((_, label)) = options.iter().find(|(value, _)| value == selected) {
+        commands.push(label_text(label, rect, style));
+    }
+}
+
+/// Rows of the open list, one per option, stacked below the select and as
+/// tall as it is.
+pub(crate) fn option_rows(bounds: Rect, count: usize) -> impl Iterator<Item = Rect> {
+    (0..count).map(move |i| Rect { y: bounds.y + bounds.height * (i + 1) as f32, ..bounds })
+}
+
+/// The open list of options, drawn after everything else so it covers the
+/// content below the select.
+pub(crate) fn popup(bounds: Rect, options: &[(String, String)], highlighted: usize, style: &ContainerStyle) -> Vec<DrawCommand> {
+    let list = Rect { y: bounds.y + bounds.height, height: bounds.height * options.len() as f32, ..bounds };
+    let mut commands = vec![DrawCommand::DrawRect {
+        rect: list,
+        color: Some(style.background_color.unwrap_or(Color::WHITE)),
+        gradient: None,
+        border_radius: style.border_radius,
+        border_width: style.border_width.max(1.0),
+        border_color: Some(style.border_color.unwrap_or(Color::from_rgba8(200, 200, 200, 255))),
+    }];
+    for (i, (row, (_, label))) in option_rows(bounds, options.len()).zip(options).enumerate() {
+        if i == highlighted {
+            commands.push(DrawCommand::DrawRect {
+                rect: row,
+                color: Some(Color::from_rgba8(0, 122, 255, 40)),
+                gradient: None,
+                border_radius: 0.0,
+                border_width: 0.0,
+                border_color: None,
+            });
+        }
+        commands.push(label_text(label, row, style));
+    }
+    commands
+}
+
+fn label_text(label: &str, rect: Rect, style: &ContainerStyle) -> DrawCommand {
+    DrawCommand::DrawText {
+        text: label.to_string(),
+        rect: Rect {
+            x: rect.x + LABEL_PADDING,
+            y: rect.y + (rect.height - style.font_size) / 2.0,
+            width: (rect.width - 2.0 * LABEL_PADDING).max(0.0),
+            height: style.font_size,
+        },
+        color: style.color,
+        font_size: style.font_size,
+        weight: style.font_variant(),
+        word_spacing: style.word_spacing,
+        max_width: None,
+    }
+}
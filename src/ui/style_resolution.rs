@@ -6,7 +6,8 @@ use crate::defaults;
 use super::node_map::NodeMap;
 use super::metadata::NodeMetadata;
 use super::Interaction;
-use super::builder::TaffyElementWrapper;
+use super::builder::{is_pseudo_element, TaffyElementWrapper, UiBuilder};
+use super::attributes::{parse_content, PSEUDO_AFTER_TAG, PSEUDO_BEFORE_TAG};
 use std::collections::HashMap;
 
 #[derive(Hash, PartialEq, Eq, Clone)]
@@ -16,14 +17,26 @@ pub struct StyleCacheKey {
     pub id: Option<std::borrow::Cow<'static, str>>,
     pub other_attrs: Option<Vec<(String, String)>>,
     pub parent_font_size_bits: u32,
+    pub parent_root_font_size_bits: u32,
     pub parent_weight: u16,
     pub parent_color_u32: u32,
+    pub parent_word_spacing_bits: u32,
+    pub parent_hidden: bool,
+    pub parent_white_space: crate::style::WhiteSpace,
+    pub parent_direction: Direction,
+    pub parent_underline: bool,
+    pub parent_cursor: crate::style::Cursor,
+    pub parent_list_style: crate::style::ListStyle,
+    /// The parent's `line-height`, `Px` values negated to tell them apart.
+    pub parent_line_height_bits: u32,
 }
 
 pub(crate) struct CachedStyles {
     pub(crate) stylesheet: simplecss::StyleSheet<'static>,
     pub(crate) keyframes: HashMap<String, css::KeyframesAnimation>,
     pub(crate) has_nth_or_last_child: bool,
+    /// Whether any rule styles `::before` or `::after`; see `insert_pseudo_elements`.
+    pub(crate) has_pseudo_elements: bool,
     pub(crate) style_cache: std::cell::RefCell<HashMap<StyleCacheKey, (Style, ContainerStyle)>>,
 }
 
@@ -48,6 +61,54 @@ pub(crate) fn to_content_box(dim: taffy::style::Dimension, sub: f32) -> taffy::s
     }
 }
 
+/// Inserts a `xerune-before`/`xerune-after` element holding the `content`
+/// text as the first/last child of every element a pseudo-element rule
+/// matches, as `attributes::insert_pseudo_elements` does for parsed documents.
+pub(crate) fn insert_pseudo_elements(builder: &mut UiBuilder, node: NodeId, stylesheet: &simplecss::StyleSheet<'_>) {
+    let children = match builder.node_metadata.get(node) {
+        Some(meta) if meta.tag != "#text" => meta.children.clone(),
+        _ => return,
+    };
+    for child in children {
+        insert_pseudo_elements(builder, child, stylesheet);
+    }
+
+    for tag in [PSEUDO_BEFORE_TAG, PSEUDO_AFTER_TAG] {
+        // Attached last so that `X > xerune-before` can match it.
+        let pseudo = builder.create_element(tag, &[]);
+        let _ = builder.taffy.add_child(node, pseudo);
+        let mut content = None;
+        if let Some(meta) = builder.node_metadata.get(pseudo) {
+            let wrapper = TaffyElementWrapper { node: pseudo, taffy: &builder.taffy, metadata: &builder.node_metadata, meta };
+            for rule in &stylesheet.rules {
+                if rule.selector.matches(&wrapper) {
+                    for decl in rule.declarations.iter().filter(|d| d.name.eq_ignore_ascii_case("content")) {
+                        content = parse_content(decl.value);
+                    }
+                }
+            }
+        }
+
+        let Some(text) = content else {
+            let _ = builder.taffy.remove(pseudo);
+            builder.node_metadata.remove(pseudo);
+            continue;
+        };
+        let text_node = builder.create_text(&text, &[]);
+        builder.append_child(pseudo, text_node);
+        let Some(meta) = builder.node_metadata.get_mut(node) else {
+            continue;
+        };
+        if tag == PSEUDO_BEFORE_TAG {
+            let _ = builder.taffy.remove_child(node, pseudo);
+            let _ = builder.taffy.insert_child_at_index(node, 0, pseudo);
+            meta.children.insert(0, pseudo);
+        } else {
+            meta.children.push(pseudo);
+        }
+    }
+}
+
 pub(crate) fn resolve_styles(
     taffy: &mut TaffyTree,
     node: NodeId,
@@ -70,10 +131,32 @@ pub(crate) fn resolve_styles(
         let mut current_style = parent_style;
         current_style.background_color = None;
         current_style.background_gradient = None;
+        current_style.background_image = None;
+        current_style.background_size = crate::graphics::BackgroundSize::Stretch;
+        current_style.background_position = crate::style::BACKGROUND_POSITION_TOP_LEFT;
+        current_style.object_view_box = None;
+        current_style.value_label = None;
+        current_style.backdrop_blur = 0.0;
+        current_style.box_shadow = None;
+        current_style.opacity = 1.0;
+        current_style.layer = false;
+        current_style.transform = None;
+        current_style.transform_origin = crate::style::TRANSFORM_ORIGIN_CENTER;
         current_style.border_width = 0.0;
         current_style.border_radius = 0.0;
         current_style.border_color = None;
+        current_style.outline_width = 0.0;
+        current_style.outline_color = None;
+        current_style.border_side_widths = [None; 4];
+        current_style.border_side_colors = [None; 4];
+        current_style.border_style = crate::style::BorderStyle::Solid;
+        current_style.fill = None;
+        current_style.is_icon = false;
         current_style.overflow = Overflow::Visible;
+        current_style.hide_scrollbar = false;
+        current_style.text_overflow = false;
+        current_style.sized = (false, false);
+        current_style.z_index = 0;
         current_style.animation_name = None;
         current_style.animation_duration = 0.0;
         current_style.animation_timing_function = std::sync::Arc::from("ease");
@@ -82,13 +165,17 @@ pub(crate) fn resolve_styles(
         current_style.animation_direction = std::sync::Arc::from("normal");
         current_style.animation_fill_mode = std::sync::Arc::from("none");
         current_style.animation_play_state = std::sync::Arc::from("running");
+        current_style.transitions = std::sync::Arc::from([]);
 
         if let Some(ref text) = meta.text {
-            let normalized = super::normalize_text(text);
+            let normalized = super::normalize_text(text, current_style.white_space);
             if !normalized.is_empty() {
-                let (width, height) = text_measurer.measure_text(&normalized, current_style.font_size, current_style.weight);
+                let (width, glyph_height) = text_measurer.measure_text_spaced(&normalized, current_style.font_size, current_style.font_variant(), current_style.word_spacing);
+                let height = current_style.line_height.resolve(glyph_height);
                 let text_layout_style = Style {
                     size: Size { width: length(width), height: length(height) },
+                    // Text that can't wrap overflows its container rather than shrinking.
+                    flex_shrink: if current_style.white_space == crate::style::WhiteSpace::Normal { 1.0 } else { 0.0 },
                     ..Style::default()
                 };
                 let _ = taffy.set_style(node, text_layout_style.clone());
@@ -103,9 +190,6 @@ pub(crate) fn resolve_styles(
     let element_type = defaults::get_default_style(tag, &ContainerStyle::default()).element_type;
     let mut parsed = super::attributes::ParsedAttributes::new(element_type);
 
-    if let Some(checked) = meta.checked {
-        parsed.checkbox_checked = checked;
-    }
     if let Some(slider_value) = meta.slider_value {
         parsed.slider_value = slider_value;
     }
@@ -133,11 +217,25 @@ pub(crate) fn resolve_styles(
         id: meta.id.clone(),
         other_attrs: meta.other_attrs.clone(),
         parent_font_size_bits: parent_style.font_size.to_bits(),
-        parent_weight: parent_style.weight,
+        parent_root_font_size_bits: parent_style.root_font_size.to_bits(),
+        parent_weight: parent_style.font_variant(),
         parent_color_u32,
+        parent_word_spacing_bits: parent_style.word_spacing.to_bits(),
+        parent_hidden: parent_style.hidden,
+        parent_white_space: parent_style.white_space,
+        parent_direction: parent_style.direction,
+        parent_underline: parent_style.underline,
+        parent_cursor: parent_style.cursor,
+        parent_list_style: parent_style.list_style,
+        parent_line_height_bits: match parent_style.line_height {
+            crate::style::LineHeight::Multiple(multiple) => multiple.to_bits(),
+            crate::style::LineHeight::Px(px) => (-px).to_bits(),
+        },
     };
 
-    let (mut layout_style, mut current_style) = if let Some(cached_styles) = style_cache.get(&cache_key) {
+    // What a pseudo-element matches depends on its parent, which the key doesn't hold.
+    let cacheable = !is_pseudo_element(meta);
+    let (mut layout_style, mut current_style) = if let Some(cached_styles) = style_cache.get(&cache_key).filter(|_| cacheable) {
         CACHE_STATS.with(|stats| {
             let (hits, misses) = stats.get();
             stats.set((hits + 1, misses));
@@ -173,7 +271,9 @@ pub(crate) fn resolve_styles(
             }
         }
         let pair = (l_style, c_style);
-        style_cache.insert(cache_key, pair.clone());
+        if cacheable {
+            style_cache.insert(cache_key, pair.clone());
+        }
         pair
     };
 
@@ -229,6 +329,32 @@ pub(crate) fn resolve_styles(
         );
     }
 
+    super::attributes::fit_aspect_ratio(tag, &current_style, &mut layout_style);
+
+    if let Some(names) = current_style.font_family_names.take() {
+        current_style.font_family = css::pick_font_family(&names, text_measurer);
+    }
+
+    // The root element's own font size is what `rem` means below it.
+    if taffy.parent(node).is_none() {
+        current_style.root_font_size = current_style.font_size;
+    }
+
+    // The model-driven `checked` value is authoritative on every rebuild, even
+    // if a static `checked` attribute was also present.
+    if let Some(checked) = meta.checked {
+        parsed.checkbox_checked = checked;
+    }
+
+    // `display: none` takes the subtree out of layout: its children are
+    // detached without being measured, and nothing of it is drawn.
+    if current_style.display == Display::None {
+        layout_style.display = taffy::style::Display::None;
+        let _ = taffy.set_style(node, layout_style);
+        let _ = taffy.set_children(node, &[]);
+        return;
+    }
+
     if let Some(d) = current_style.inline_size {
         layout_style.size.width = d;
         if d == taffy::style::Dimension::length(d.value()) {
@@ -254,8 +380,9 @@ pub(crate) fn resolve_styles(
         layout_style.max_size.height = d;
     }
 
-    let add_h = current_style.padding_left + current_style.padding_right + current_style.border_width * 2.0;
-    let add_v = current_style.padding_top + current_style.padding_bottom + current_style.border_width * 2.0;
+    let [border_top, border_right, border_bottom, border_left] = current_style.border_widths();
+    let add_h = current_style.padding_left + current_style.padding_right + border_left + border_right;
+    let add_v = current_style.padding_top + current_style.padding_bottom + border_top + border_bottom;
 
     if current_style.box_sizing == BoxSizing::ContentBox {
         layout_style.size.width = to_border_box(layout_style.size.width, add_h);
@@ -480,7 +607,7 @@ pub(crate) fn resolve_styles(
         );
     }
 
-    if current_style.display == Display::Flex {
+    if matches!(current_style.display, Display::Flex | Display::Grid) {
         let mut chs = meta.children.clone();
         let original = chs.clone();
         chs.sort_by_key(|child_id| {
@@ -491,9 +618,7 @@ pub(crate) fn resolve_styles(
         }
     }
 
-    if current_style.display == Display::None {
-        layout_style.display = taffy::style::Display::None;
-    } else if current_style.display != Display::Flex {
+    if !matches!(current_style.display, Display::Flex | Display::Grid) {
         let mut has_inline_child = false;
         let chs = &meta.children;
         for child_id in chs {
@@ -516,29 +641,34 @@ pub(crate) fn resolve_styles(
         if has_inline_child || tag == "tr" {
             layout_style.flex_direction = FlexDirection::Row;
             layout_style.flex_wrap = FlexWrap::Wrap;
-            if let Some(align) = current_style.text_align {
-                match align {
-                    TextAlign::Right => layout_style.justify_content = Some(JustifyContent::FlexEnd),
-                    TextAlign::Center => layout_style.justify_content = Some(JustifyContent::Center),
-                    TextAlign::Left => layout_style.justify_content = Some(JustifyContent::FlexStart),
-                }
+            let rtl = current_style.direction == Direction::Rtl;
+            match current_style.text_align {
+                Some(TextAlign::Right) => layout_style.justify_content = Some(JustifyContent::FlexEnd),
+                Some(TextAlign::Center) => layout_style.justify_content = Some(JustifyContent::Center),
+                // Justified text is only spread by `line_alignment`, over the wrapped lines of a
+                // single text node. A node on one line is a final line, and the words of mixed
+                // inline runs aren't spread, so both start like unaligned text: on the right in
+                // a right-to-left block.
+                None | Some(TextAlign::Justify) if rtl => layout_style.justify_content = Some(JustifyContent::FlexEnd),
+                Some(TextAlign::Left | TextAlign::Justify) => layout_style.justify_content = Some(JustifyContent::FlexStart),
+                None => {}
             }
         } else {
             layout_style.flex_direction = FlexDirection::Column;
         }
     }
 
-    if parent_style.display != Display::Flex && current_style.display == Display::Block {
+    if !matches!(parent_style.display, Display::Flex | Display::Grid) && current_style.display == Display::Block {
         if layout_style.size.width.is_auto() {
             layout_style.size.width = Dimension::percent(1.0);
         }
     }
 
     layout_style.border = taffy::geometry::Rect {
-        left: length(current_style.border_width),
-        right: length(current_style.border_width),
-        top: length(current_style.border_width),
-        bottom: length(current_style.border_width),
+        left: length(border_left),
+        right: length(border_right),
+        top: length(border_top),
+        bottom: length(border_bottom),
     };
 
     if current_style.direction == Direction::Rtl {
@@ -573,25 +703,25 @@ pub(crate) fn resolve_styles(
     if !layout_style.max_size.height.is_auto() {
         let val = layout_style.max_size.height.value();
         if layout_style.max_size.height == Dimension::length(val) {
-            layout_style.max_size.height = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_top + current_style.padding_bottom);
+            layout_style.max_size.height = Dimension::length(val + border_top + border_bottom + current_style.padding_top + current_style.padding_bottom);
         }
     }
     if !layout_style.max_size.width.is_auto() {
         let val = layout_style.max_size.width.value();
         if layout_style.max_size.width == Dimension::length(val) {
-            layout_style.max_size.width = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_left + current_style.padding_right);
+            layout_style.max_size.width = Dimension::length(val + border_left + border_right + current_style.padding_left + current_style.padding_right);
         }
     }
     if !layout_style.min_size.height.is_auto() {
         let val = layout_style.min_size.height.value();
         if layout_style.min_size.height == Dimension::length(val) {
-            layout_style.min_size.height = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_top + current_style.padding_bottom);
+            layout_style.min_size.height = Dimension::length(val + border_top + border_bottom + current_style.padding_top + current_style.padding_bottom);
         }
     }
     if !layout_style.min_size.width.is_auto() {
         let val = layout_style.min_size.width.value();
         if layout_style.min_size.width == Dimension::length(val) {
-            layout_style.min_size.width = Dimension::length(val + current_style.border_width * 2.0 + current_style.padding_left + current_style.padding_right);
+            layout_style.min_size.width = Dimension::length(val + border_left + border_right + current_style.padding_left + current_style.padding_right);
         }
     }
 
@@ -0,0 +1,266 @@
+use taffy::prelude::*;
+
+use super::NodeMap;
+use crate::graphics::TextMeasurer;
+use crate::style::{ContainerStyle, LineHeight, RenderData, WhiteSpace};
+
+pub(crate) const ELLIPSIS: &str = "\u{2026}";
+
+/// Word widths of a text node, measured once when the tree is built so its
+/// lines can be re-broken whenever layout changes its width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextWrap {
+    text: String,
+    natural_width: f32,
+    words: Vec<(String, f32)>,
+    space_width: f32,
+    ellipsis_width: f32,
+    /// Distance between the tops of consecutive lines.
+    pub line_height: f32,
+    /// Height of the glyphs, centered in each line.
+    pub glyph_height: f32,
+    /// `line-clamp`; `None` keeps every line.
+    max_lines: Option<usize>,
+    /// `white-space: pre`: the lines are fixed at the newlines of the text.
+    preformatted: bool,
+    width: Option<f32>,
+    /// The wrapped lines for the current width, at most `max_lines` of them,
+    /// the last one ending in an ellipsis if the text was cut short.
+    pub lines: Vec<String>,
+    /// The width of each of `lines`, for aligning them.
+    pub line_widths: Vec<f32>,
+}
+
+impl TextWrap {
+    pub(crate) fn new(text: &str, max_lines: Option<u32>, font_size: f32, weight: u16, word_spacing: f32, measurer: &impl TextMeasurer) -> Self {
+        let words: Vec<(String, f32)> = text
+            .split_whitespace()
+            .map(|w| (w.to_string(), measurer.measure_text(w, font_size, weight).0))
+            .collect();
+        let space_width = measurer.measure_text(" ", font_size, weight).0 + word_spacing;
+        // Either measurement may be the wider one, e.g. through kerning; the
+        // text is only wrapped once it does not fit with both.
+        let words_width = words.iter().map(|(_, w)| w).sum::<f32>() + space_width * words.len().saturating_sub(1) as f32;
+        let (text_width, line_height) = measurer.measure_text_spaced(text, font_size, weight, word_spacing);
+        let natural_width = text_width.max(words_width);
+        Self {
+            text: text.to_string(),
+            natural_width,
+            words,
+            space_width,
+            ellipsis_width: measurer.measure_text(ELLIPSIS, font_size, weight).0,
+            line_height,
+            glyph_height: line_height,
+            max_lines: max_lines.map(|n| n.max(1) as usize),
+            preformatted: false,
+            width: None,
+            // Text nodes are built one line tall.
+            lines: vec![text.to_string()],
+            line_widths: vec![natural_width],
+        }
+    }
+
+    /// Preformatted text, with a line per line of `text` whatever the width.
+    pub(crate) fn preformatted(text: &str, font_size: f32, weight: u16, word_spacing: f32, measurer: &impl TextMeasurer) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let measured: Vec<(f32, f32)> = lines.iter().map(|line| measurer.measure_text_spaced(line, font_size, weight, word_spacing)).collect();
+        let line_widths: Vec<f32> = measured.iter().map(|(width, _)| *width).collect();
+        let glyph_height = measured.iter().map(|(_, height)| *height).fold(0.0, f32::max);
+        Self {
+            text: text.to_string(),
+            natural_width: line_widths.iter().copied().fold(0.0, f32::max),
+            words: Vec::new(),
+            space_width: 0.0,
+            ellipsis_width: 0.0,
+            line_height: glyph_height,
+            glyph_height,
+            max_lines: None,
+            preformatted: true,
+            width: None,
+            lines,
+            line_widths,
+        }
+    }
+
+    /// Spaces the lines `line_height` apart, e.g. a multiple of the glyph box.
+    pub(crate) fn with_line_height(mut self, line_height: LineHeight) -> Self {
+        self.line_height = line_height.resolve(self.glyph_height);
+        self
+    }
+
+    /// Whether the text is drawn as it is, on a single line.
+    pub fn is_single_line(&self) -> bool {
+        self.lines.len() == 1 && self.lines[0] == self.text
+    }
+
+    /// Greedily wraps the words into `width`. Returns true if the number of
+    /// lines changed, i.e. the node's height has to be updated.
+    pub(crate) fn break_lines(&mut self, width: f32) -> bool {
+        if self.preformatted || self.width == Some(width) {
+            return false;
+        }
+        self.width = Some(width);
+        let old_count = self.lines.len();
+
+        if self.natural_width <= width {
+            self.lines = vec![self.text.clone()];
+            self.line_widths = vec![self.natural_width];
+            return old_count != 1;
+        }
+
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        let mut line_width = 0.0;
+        for (i, (_, w)) in self.words.iter().enumerate() {
+            match lines.last_mut() {
+                Some(line) if line_width + self.space_width + w <= width => {
+                    line.push(i);
+                    line_width += self.space_width + w;
+                }
+                _ => {
+                    lines.push(vec![i]);
+                    line_width = *w;
+                }
+            }
+        }
+
+        let max_lines = self.max_lines.unwrap_or(usize::MAX);
+        let truncated = lines.len() > max_lines;
+        lines.truncate(max_lines);
+        if truncated && let Some(last) = lines.last_mut() {
+            // Drop trailing words until the ellipsis fits, keeping at least one.
+            while last.len() > 1 && self.line_width(last) + self.ellipsis_width > width {
+                last.pop();
+            }
+        }
+
+        self.line_widths = lines.iter().map(|line| self.line_width(line)).collect();
+        self.lines = lines
+            .iter()
+            .map(|line| line.iter().map(|&i| self.words[i].0.as_str()).collect::<Vec<_>>().join(" "))
+            .collect();
+        if truncated && let Some(last) = self.lines.last_mut() {
+            last.push_str(ELLIPSIS);
+        }
+        if truncated && let Some(last) = self.line_widths.last_mut() {
+            *last += self.ellipsis_width;
+        }
+        old_count != self.lines.len()
+    }
+
+    /// Width of the widest line.
+    pub fn max_line_width(&self) -> f32 {
+        self.line_widths.iter().copied().fold(0.0, f32::max)
+    }
+
+    fn line_width(&self, line: &[usize]) -> f32 {
+        let words: f32 = line.iter().map(|&i| self.words[i].1).sum();
+        words + self.space_width * line.len().saturating_sub(1) as f32
+    }
+}
+
+/// Measures every text node that may wrap, i.e. `white-space: normal` ones,
+/// and lets its width shrink to the container, so that `apply` can wrap it
+/// after layout. Without a width constraint the text stays on one line.
+/// `white-space: pre` text spanning several lines is sized to them up front.
+/// Other text whose `line-height` isn't the glyph box keeps a single fixed
+/// line, which places its glyphs in the middle of the line box.
+pub(crate) fn collect(
+    taffy: &mut TaffyTree,
+    render_data: &NodeMap<RenderData>,
+    base_styles: &mut NodeMap<(Style, crate::style::ContainerStyle)>,
+    measurer: &impl TextMeasurer,
+) -> NodeMap<TextWrap> {
+    let mut wraps = NodeMap::new();
+    for (node, data) in render_data {
+        let RenderData::Text(text, style) = data else {
+            continue;
+        };
+        match style.white_space {
+            WhiteSpace::Normal if text.split_whitespace().nth(1).is_some() => {
+                let wrap = TextWrap::new(text, style.line_clamp, style.font_size, style.font_variant(), style.word_spacing, measurer)
+                    .with_line_height(style.line_height);
+                if let Some((layout_style, _)) = base_styles.get_mut(node) {
+                    layout_style.size.width = length(wrap.natural_width);
+                    layout_style.max_size.width = Dimension::percent(1.0);
+                    let _ = taffy.set_style(node, layout_style.clone());
+                }
+                wraps.insert(node, wrap);
+            }
+            WhiteSpace::Pre if text.lines().nth(1).is_some() => {
+                let wrap = TextWrap::preformatted(text, style.font_size, style.font_variant(), style.word_spacing, measurer)
+                    .with_line_height(style.line_height);
+                if let Some((layout_style, _)) = base_styles.get_mut(node) {
+                    layout_style.size.width = length(wrap.natural_width);
+                    layout_style.size.height = length(wrap.line_height * wrap.lines.len() as f32);
+                    let _ = taffy.set_style(node, layout_style.clone());
+                }
+                wraps.insert(node, wrap);
+            }
+            _ if style.line_height != LineHeight::Multiple(1.0) => {
+                let wrap = TextWrap::preformatted(text, style.font_size, style.font_variant(), style.word_spacing, measurer)
+                    .with_line_height(style.line_height);
+                wraps.insert(node, wrap);
+            }
+            _ => {}
+        }
+    }
+    wraps
+}
+
+/// Gives every `<br>` that ends an empty line, i.e. one that starts its
+/// parent or follows another `<br>`, the height of a line of its text, so
+/// that repeated breaks leave blank lines. Other breaks stay zero-height and
+/// only end the line they follow.
+pub(crate) fn collect_line_breaks(
+    taffy: &mut TaffyTree,
+    render_data: &NodeMap<RenderData>,
+    base_styles: &mut NodeMap<(Style, ContainerStyle)>,
+    tags: &NodeMap<std::borrow::Cow<'static, str>>,
+    measurer: &impl TextMeasurer,
+) {
+    let is_break = |node: NodeId| tags.get(node).is_some_and(|tag| tag == "br");
+    let mut empty_lines = Vec::new();
+    for (parent, _) in tags {
+        let mut line_empty = true;
+        for child in taffy.children(parent).unwrap_or_default() {
+            match render_data.get(child) {
+                // Collapsed white space between breaks doesn't fill the line.
+                Some(RenderData::Text(text, _)) if text.trim().is_empty() => continue,
+                Some(RenderData::Container(style)) if is_break(child) => {
+                    if line_empty {
+                        empty_lines.push((child, style));
+                    }
+                    line_empty = true;
+                }
+                _ => line_empty = false,
+            }
+        }
+    }
+    for (node, style) in empty_lines {
+        let glyph_height = measurer.measure_text(" ", style.font_size, style.font_variant()).1;
+        if let Some((layout_style, _)) = base_styles.get_mut(node) {
+            layout_style.size.height = length(style.line_height.resolve(glyph_height));
+            let _ = taffy.set_style(node, layout_style.clone());
+        }
+    }
+}
+
+/// Re-wraps text to the widths from the last layout pass and resizes the
+/// nodes whose line count changed. Returns true if another pass is needed.
+pub(crate) fn apply(taffy: &mut TaffyTree, wraps: &mut NodeMap<TextWrap>) -> bool {
+    let nodes: Vec<NodeId> = wraps.iter().map(|(node, _)| node).collect();
+    let mut changed = false;
+    for node in nodes {
+        let (Ok(layout), Some(wrap)) = (taffy.layout(node), wraps.get_mut(node)) else {
+            continue;
+        };
+        if wrap.break_lines(layout.size.width)
+            && let Ok(mut style) = taffy.style(node).cloned()
+        {
+            style.size.height = length(wrap.line_height * wrap.lines.len() as f32);
+            let _ = taffy.set_style(node, style);
+            changed = true;
+        }
+    }
+    changed
+}
@@ -5,12 +5,14 @@ struct MockModel;
 #[derive(Debug, PartialEq)]
 enum MockMsg {
     Tick,
+    Toggle,
 }
 impl std::str::FromStr for MockMsg {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "tick" => Ok(MockMsg::Tick),
+            "toggle" => Ok(MockMsg::Toggle),
             _ => Err(()),
         }
     }
@@ -18,7 +20,9 @@ impl std::str::FromStr for MockMsg {
 
 impl Model for MockModel {
     type Message = MockMsg;
-    fn update(&mut self, _msg: Self::Message, _context: &mut Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 impl TemplateLayout for MockModel {
@@ -42,6 +46,37 @@ impl TextMeasurer for MockMeasurer {
     }
 }
 
+/// A model that only lays out the tree `build` makes, with `stylesheet`, and
+/// ignores its messages, for tests of what the UI builds.
+struct Fixture<F> {
+    stylesheet: &'static str,
+    build: F,
+}
+
+fn fixture<F: Fn(&mut UiBuilder) -> taffy::NodeId>(build: F) -> Fixture<F> {
+    styled_fixture("", build)
+}
+
+fn styled_fixture<F: Fn(&mut UiBuilder) -> taffy::NodeId>(stylesheet: &'static str, build: F) -> Fixture<F> {
+    Fixture { stylesheet, build }
+}
+
+impl<F> Model for Fixture<F> {
+    type Message = MockMsg;
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
+}
+
+impl<F: Fn(&mut UiBuilder) -> taffy::NodeId> TemplateLayout for Fixture<F> {
+    fn stylesheet(&self) -> &'static str {
+        self.stylesheet
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        (self.build)(builder)
+    }
+}
+
 #[test]
 fn test_scroll_persistence() {
     let model = MockModel;
@@ -90,10 +125,474 @@ fn test_scroll_persistence() {
     assert_eq!(hit.unwrap().0, "test_interaction".to_string());
 }
 
+fn keyed_scroll_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let page = builder.create_element("div", &[("style", "display: flex; flex-direction: row;")]);
+    for attrs in [("data-key", "list"), ("data-on-click", "tick")] {
+        let list = builder.create_element("div", &[("style", "width: 50px; height: 100px; overflow: scroll;"), attrs]);
+        let content = builder.create_element("div", &[("style", "height: 300px; flex-shrink: 0;")]);
+        builder.append_child(list, content);
+        builder.append_child(page, list);
+    }
+    page
+}
+
+#[test]
+fn test_scroll_offset_of_finds_containers_by_key_or_interaction() {
+    let mut runtime = Runtime::new(fixture(keyed_scroll_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(runtime.scroll_offset_of("list"), Some((0.0, 0.0)), "Unscrolled containers are at the origin");
+    assert_eq!(runtime.scroll_offset_of("missing"), None);
+
+    runtime.handle_event(InputEvent::Scroll { x: 10.0, y: 10.0, delta_x: 0.0, delta_y: -10.0 });
+    runtime.handle_event(InputEvent::Scroll { x: 60.0, y: 10.0, delta_x: 0.0, delta_y: -30.0 });
+    assert_eq!(runtime.scroll_offset_of("list"), Some((0.0, 10.0)));
+    assert_eq!(runtime.scroll_offset_of("tick"), Some((0.0, 30.0)));
+}
+
+fn clipped_scroll_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let page = builder.create_element("div", &[("style", "width: 100px; height: 300px;"), ("data-on-click", "toggle")]);
+    let list = builder.create_element("div", &[("style", "height: 100px; flex-shrink: 0; overflow: scroll;")]);
+    let content = builder.create_element("div", &[("style", "height: 200px; flex-shrink: 0;"), ("data-on-click", "tick")]);
+    builder.append_child(list, content);
+    builder.append_child(page, list);
+    page
+}
+
+#[test]
+fn test_hit_test_respects_scroll_clip() {
+    let mut runtime = Runtime::new(fixture(clipped_scroll_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    runtime.handle_event(InputEvent::Scroll { x: 10.0, y: 10.0, delta_x: 0.0, delta_y: -10.0 });
+
+    assert_eq!(runtime.ui.hit_test(10.0, 50.0).unwrap().0, "tick");
+    // The content still reaches y = 190 but is clipped at the list's bottom edge.
+    assert_eq!(runtime.ui.hit_test(10.0, 150.0).unwrap().0, "toggle");
+}
+
+struct BubblingModel;
+impl Model for BubblingModel {
+    type Message = MockMsg;
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
+    fn bubbles(&self, msg: &Self::Message) -> bool {
+        *msg == MockMsg::Tick
+    }
+}
+
+impl TemplateLayout for BubblingModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let card = builder.create_element("div", &[("style", "width: 100px; height: 100px;"), ("data-on-click", "toggle")]);
+        let bubbling = builder.create_element("div", &[("style", "height: 20px; flex-shrink: 0;"), ("data-on-click", "tick")]);
+        let stopped = builder.create_element("div", &[("style", "height: 20px; flex-shrink: 0;"), ("data-on-click", "tick"), ("data-stop-propagation", "")]);
+        builder.append_child(card, bubbling);
+        builder.append_child(card, stopped);
+        card
+    }
+}
+
+#[test]
+fn test_click_bubbles_until_handled() {
+    let mut runtime = Runtime::new(BubblingModel, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+
+    assert_eq!(runtime.ui.hit_path(10.0, 10.0), ["tick", "toggle"]);
+    runtime.handle_event(InputEvent::Click { x: 10.0, y: 10.0 });
+    assert_eq!(*sent.lock().unwrap(), ["tick", "toggle"]);
+
+    sent.lock().unwrap().clear();
+    runtime.handle_event(InputEvent::Click { x: 10.0, y: 30.0 });
+    assert_eq!(*sent.lock().unwrap(), ["tick"], "data-stop-propagation ends the bubbling");
+}
+
+fn nested_scroll_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let list = builder.create_element("div", &[("style", "height: 100px; overflow: scroll;"), ("data-key", "list")]);
+    let outer = builder.create_element("div", &[("style", "flex-shrink: 0;")]);
+    let spacer = builder.create_element("div", &[("style", "height: 300px; flex-shrink: 0;")]);
+    let middle = builder.create_element("div", &[]);
+    let target = builder.create_element("div", &[("style", "height: 20px;"), ("data-on-click", "tick")]);
+    builder.append_child(middle, target);
+    builder.append_child(outer, spacer);
+    builder.append_child(outer, middle);
+    builder.append_child(list, outer);
+    list
+}
+
+#[test]
+fn test_scroll_into_view_nested_target() {
+    let mut runtime = Runtime::new(fixture(nested_scroll_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    // The target sits 300px down, three levels below the scroll container.
+    runtime.scroll_into_view("tick");
+    assert_eq!(runtime.scroll_offset_of("list"), Some((0.0, 220.0)), "Target should end at the bottom edge");
+}
+
+struct CheckboxModel {
+    checked: bool,
+}
+
+impl Model for CheckboxModel {
+    type Message = MockMsg;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if msg == MockMsg::Toggle {
+            self.checked = !self.checked;
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for CheckboxModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let parent = builder.create_element("div", &[]);
+        let checkbox = builder.create_checkbox(self.checked, &[("data-on-click", "toggle")]);
+        builder.append_child(parent, checkbox);
+        parent
+    }
+}
+
+fn checkbox_state(runtime: &Runtime<CheckboxModel, MockMeasurer>) -> Option<bool> {
+    runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::Checkbox(checked, _) => Some(*checked),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_checkbox_follows_model_on_rebuild() {
+    let mut runtime = Runtime::new(CheckboxModel { checked: false }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(checkbox_state(&runtime), Some(false));
+
+    // The checkbox sits at x = 5 (margin), y = 0 with a 20x20 box.
+    assert!(runtime.handle_event(InputEvent::Click { x: 10.0, y: 10.0 }));
+    assert_eq!(checkbox_state(&runtime), Some(true), "Rebuild should reflect the toggled model");
+
+    assert!(runtime.handle_event(InputEvent::Click { x: 10.0, y: 10.0 }));
+    assert_eq!(checkbox_state(&runtime), Some(false));
+}
+
+fn line_clamp_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let card = builder.create_element("div", &[("style", "width: 50px; line-clamp: 2;")]);
+    let text = builder.create_text("a b c d e f g h", &[]);
+    builder.append_child(card, text);
+    card
+}
+
+#[test]
+fn test_line_clamp_keeps_first_lines() {
+    let mut runtime = Runtime::new(fixture(line_clamp_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    // Every word and space measures 10px, so three words fit per 50px line.
+    let lines: Vec<_> = runtime.ui.build_commands(&Default::default()).into_iter().filter_map(|cmd| match cmd {
+        DrawCommand::DrawText { text, rect, .. } => Some((text, rect.y)),
+        _ => None,
+    }).collect();
+    assert_eq!(lines, vec![("a b c".to_string(), 0.0), ("d e\u{2026}".to_string(), 10.0)]);
+
+    let root = runtime.ui.root;
+    assert_eq!(runtime.ui.taffy.layout(root).unwrap().size.height, 20.0, "Card should be two lines tall");
+}
+
+fn focus_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let parent = builder.create_element("div", &[]);
+    for action in ["tick", "toggle"] {
+        let button = builder.create_element("button", &[("data-on-click", action)]);
+        builder.append_child(parent, button);
+    }
+    parent
+}
+
+fn focused_key<M: Model>(runtime: &Runtime<M, MockMeasurer>) -> Option<String> {
+    runtime.ui.focused.and_then(|node| runtime.ui.focus_key(node)).map(str::to_string)
+}
+
+#[test]
+fn test_tab_cycles_focus() {
+    let mut runtime = Runtime::new(fixture(focus_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(focused_key(&runtime), None);
+
+    runtime.handle_event(InputEvent::KeyDown("Tab".to_string()));
+    assert_eq!(focused_key(&runtime).as_deref(), Some("tick"));
+    runtime.handle_event(InputEvent::KeyDown("Tab".to_string()));
+    assert_eq!(focused_key(&runtime).as_deref(), Some("toggle"));
+    runtime.handle_event(InputEvent::KeyDown("Tab".to_string()));
+    assert_eq!(focused_key(&runtime).as_deref(), Some("tick"), "Tab should wrap around");
+
+    runtime.handle_event(InputEvent::KeyDown("ShiftLeft".to_string()));
+    runtime.handle_event(InputEvent::KeyDown("Tab".to_string()));
+    assert_eq!(focused_key(&runtime).as_deref(), Some("toggle"), "Shift+Tab should move backwards");
+    runtime.handle_event(InputEvent::KeyUp("ShiftLeft".to_string()));
+
+    assert!(runtime.focus("tick"));
+    assert!(!runtime.focus("missing"));
+    runtime.sync_view();
+    assert_eq!(focused_key(&runtime).as_deref(), Some("tick"), "Focus should survive a rebuild");
+}
+
+struct SelectModel {
+    size: String,
+}
+
+struct SizeMsg(String);
+impl std::str::FromStr for SizeMsg {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("select:size:").map(|value| SizeMsg(value.to_string())).ok_or(())
+    }
+}
+
+impl Model for SelectModel {
+    type Message = SizeMsg;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        self.size = msg.0;
+        Command::none()
+    }
+}
+
+impl TemplateLayout for SelectModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let parent = builder.create_element("div", &[]);
+        let select = builder.create_element("select", &[("id", "size"), ("value", &self.size)]);
+        for (value, label) in [("s", "Small"), ("m", "Medium"), ("l", "Large")] {
+            let option = builder.create_element("option", &[("value", value)]);
+            let text = builder.create_text(label, &[]);
+            builder.append_child(option, text);
+            builder.append_child(select, option);
+        }
+        builder.append_child(parent, select);
+        let below = builder.create_element("div", &[("style", "height: 100px;")]);
+        builder.append_child(parent, below);
+        parent
+    }
+}
+
+fn selected_size(runtime: &Runtime<SelectModel, MockMeasurer>) -> Option<String> {
+    runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::Select(_, _, selected, _) => Some(selected.clone()),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_select_picks_option() {
+    let mut runtime = Runtime::new(SelectModel { size: "s".to_string() }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(selected_size(&runtime).as_deref(), Some("s"));
+
+    // The select spans y = 2..32; its options open below in rows as tall.
+    assert!(runtime.handle_event(InputEvent::Click { x: 10.0, y: 10.0 }));
+    let labels: Vec<_> = runtime.ui.build_commands(&Default::default()).into_iter().filter_map(|cmd| match cmd {
+        DrawCommand::DrawText { text, .. } => Some(text),
+        _ => None,
+    }).collect();
+    assert_eq!(labels, ["Small", "Small", "Medium", "Large"], "The closed label, then the open list on top");
+
+    assert!(runtime.handle_event(InputEvent::Click { x: 10.0, y: 70.0 }));
+    assert_eq!(runtime.ui.open_select, None);
+    assert_eq!(selected_size(&runtime).as_deref(), Some("m"), "Rebuild should reflect the picked option");
+
+    assert!(runtime.handle_event(InputEvent::Click { x: 10.0, y: 10.0 }));
+    runtime.handle_event(InputEvent::KeyDown("ArrowDown".to_string()));
+    runtime.handle_event(InputEvent::KeyDown("Enter".to_string()));
+    assert_eq!(selected_size(&runtime).as_deref(), Some("l"));
+}
+
+fn z_index_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let parent = builder.create_element("div", &[("style", "width: 100px; height: 100px;")]);
+    for (style, action) in [
+        ("position: absolute; width: 50px; height: 50px; background-color: #ff0000; z-index: 2;", "tick"),
+        ("position: absolute; width: 50px; height: 50px; background-color: #0000ff;", "toggle"),
+    ] {
+        let child = builder.create_element("div", &[("style", style), ("data-on-click", action)]);
+        builder.append_child(parent, child);
+    }
+    parent
+}
+
+#[test]
+fn test_z_index_paints_above_later_siblings() {
+    let mut runtime = Runtime::new(fixture(z_index_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let colors: Vec<_> = runtime.ui.build_commands(&Default::default()).into_iter().filter_map(|cmd| match cmd {
+        DrawCommand::DrawRect { color: Some(color), .. } => Some(color),
+        _ => None,
+    }).collect();
+    assert_eq!(colors, [Color::from_rgba8(0, 0, 255, 255), Color::from_rgba8(255, 0, 0, 255)]);
+
+    let (interaction, _) = runtime.ui.hit_test(10.0, 10.0).unwrap();
+    assert_eq!(interaction, "tick", "The raised sibling should take the click");
+}
+
+#[test]
+fn test_render_to_pixmap_without_window() {
+    let mut runtime = Runtime::new(fixture(z_index_ui), MockMeasurer);
+    let pixmap = skia_renderer::render_to_pixmap(&mut runtime, 100, 100, &[]).unwrap();
+
+    let pixel = pixmap.pixel(10, 10).unwrap();
+    assert_eq!((pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()), (255, 0, 0, 255), "The raised red box is painted over the blue one");
+}
+
+fn background_image_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let hero = builder.create_element("div", &[("style", "width: 200px; height: 100px; background-color: #000000; background-image: url('bg.png'); background-size: cover; border: 4px solid #ff0000; border-radius: 10px; box-sizing: border-box;")]);
+    let title = builder.create_element("div", &[("style", "height: 20px; background-color: #ffffff;")]);
+    builder.append_child(hero, title);
+    hero
+}
+
+#[test]
+fn test_background_image_under_children() {
+    let mut runtime = Runtime::new(fixture(background_image_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let commands = runtime.ui.build_commands(&Default::default());
+    assert!(matches!(&commands[..], [
+        DrawCommand::DrawRect { .. },
+        DrawCommand::Clip { .. },
+        DrawCommand::DrawImage { src, size: BackgroundSize::Cover, .. },
+        DrawCommand::PopClip,
+        DrawCommand::DrawRect { .. },
+    ] if src == "bg.png"), "The image sits between the background color and the children: {commands:?}");
+    // It fills the padding box, clipped to its corners, inside the border.
+    let padding_box = Rect::new(4.0, 4.0, 192.0, 92.0);
+    assert_eq!(commands[1], DrawCommand::Clip { rect: padding_box, border_radius: 6.0 });
+    assert!(matches!(commands[2], DrawCommand::DrawImage { rect, border_radius: 0.0, .. } if rect == padding_box));
+
+    let hero = Rect::new(0.0, 0.0, 200.0, 100.0);
+    let image = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let top_left = (OriginOffset::Fraction(0.0), OriginOffset::Fraction(0.0));
+    let center = (OriginOffset::Fraction(0.5), OriginOffset::Fraction(0.5));
+    // A 100x100 image covers the 200x100 box by cropping its bottom half.
+    let (rect, source) = BackgroundSize::Cover.fit(hero, image, top_left).unwrap();
+    assert_eq!((rect, source), (hero, Rect::new(0.0, 0.0, 100.0, 50.0)));
+    // Centered, it loses a quarter at the top and bottom instead.
+    let (rect, source) = BackgroundSize::Cover.fit(hero, image, center).unwrap();
+    assert_eq!((rect, source), (hero, Rect::new(0.0, 25.0, 100.0, 50.0)));
+    // Contained, it is as tall as the box and letterboxed in the middle.
+    let (rect, source) = BackgroundSize::Contain.fit(hero, image, center).unwrap();
+    assert_eq!((rect, source), (Rect::new(50.0, 0.0, 100.0, 100.0), image));
+    // An explicit width keeps the aspect ratio.
+    let (rect, source) = BackgroundSize::Length(Some(OriginOffset::Px(50.0)), None).fit(hero, image, top_left).unwrap();
+    assert_eq!((rect, source), (Rect::new(0.0, 0.0, 50.0, 50.0), image));
+}
+
+fn transform_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let card = builder.create_element("div", &[("style", "width: 100px; height: 50px; background-color: #ff0000; transform: translate(10px, 0) scale(2);")]);
+    let badge = builder.create_element("div", &[("style", "width: 10px; height: 10px; background-color: #0000ff;")]);
+    builder.append_child(card, badge);
+    card
+}
+
+#[test]
+fn test_transform_applies_to_own_box_only() {
+    let mut runtime = Runtime::new(fixture(transform_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let commands = runtime.ui.build_commands(&Default::default());
+    let [DrawCommand::PushTransform { matrix, .. }, DrawCommand::DrawRect { .. }, DrawCommand::PopTransform, DrawCommand::DrawRect { .. }] = &commands[..] else {
+        panic!("Only the card's own rect should be transformed: {commands:?}");
+    };
+    // Scaled twice around the card's center, then moved 10px right.
+    assert_eq!(matrix.map_point(0.0, 0.0), (-40.0, -25.0));
+    assert_eq!(matrix.map_point(50.0, 25.0), (60.0, 25.0));
+}
+
+fn max_size_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let parent = builder.create_element("div", &[("style", "display: flex; flex-direction: row; width: 300px; height: 50px;")]);
+    for style in ["flex-grow: 1; max-width: 100px;", "flex-grow: 1; max-width: 50%;"] {
+        let child = builder.create_element("div", &[("style", style)]);
+        builder.append_child(parent, child);
+    }
+    parent
+}
+
+#[test]
+fn test_max_width_caps_flex_grow() {
+    let mut runtime = Runtime::new(fixture(max_size_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let root = runtime.ui.root;
+    let widths: Vec<f32> = runtime.ui.taffy.children(root).unwrap().into_iter()
+        .map(|child| runtime.ui.taffy.layout(child).unwrap().size.width)
+        .collect();
+    assert_eq!(widths, [100.0, 150.0], "Growing items should stop at their max-width");
+}
+
+fn align_self_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let parent = builder.create_element("div", &[("style", "display: flex; flex-direction: row; align-items: flex-start; height: 100px;")]);
+    for style in ["width: 20px; height: 20px;", "width: 20px; height: 20px; align-self: flex-end;"] {
+        let child = builder.create_element("div", &[("style", style)]);
+        builder.append_child(parent, child);
+    }
+    parent
+}
+
+#[test]
+fn test_align_self_overrides_align_items() {
+    let mut runtime = Runtime::new(fixture(align_self_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let root = runtime.ui.root;
+    let tops: Vec<f32> = runtime.ui.taffy.children(root).unwrap().into_iter()
+        .map(|child| runtime.ui.taffy.layout(child).unwrap().location.y)
+        .collect();
+    assert_eq!(tops, [0.0, 80.0]);
+}
+
+fn id_rect_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "padding: 10px;")]);
+    let panel = builder.create_element("div", &[("style", "margin-left: 5px; padding-top: 20px;")]);
+    let target = builder.create_element("div", &[("id", "target"), ("style", "width: 30px; height: 40px;")]);
+    builder.append_child(panel, target);
+    builder.append_child(root, panel);
+    root
+}
+
+#[test]
+fn test_rect_for_id_accumulates_offsets() {
+    let mut runtime = Runtime::new(fixture(id_rect_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    assert_eq!(runtime.rect_for_id("target"), Some(Rect { x: 15.0, y: 30.0, width: 30.0, height: 40.0 }));
+    assert_eq!(runtime.rect_for_id("missing"), None);
+}
+
+#[test]
+fn test_layout_rects_without_renderer() {
+    let measurer: Box<dyn TextMeasurer> = Box::new(MockMeasurer);
+    let mut ui = Ui::new_compiled(&fixture(id_rect_ui), &measurer, ContainerStyle::default(), &|_: &str| true).unwrap();
+    ui.compute_layout(taffy::geometry::Size::MAX_CONTENT).unwrap();
+
+    let rects = ui.layout_rects();
+    let (target, _) = ui.ids.iter().find(|(_, id)| id.as_str() == "target").unwrap();
+    assert_eq!(rects.get(target), Some(&Rect { x: 15.0, y: 30.0, width: 30.0, height: 40.0 }));
+    assert_eq!(rects.iter().count(), ui.taffy.total_node_count());
+}
+
 struct SelectorMockModel;
 impl Model for SelectorMockModel {
     type Message = MockMsg;
-    fn update(&mut self, _msg: Self::Message, _context: &mut Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 impl TemplateLayout for SelectorMockModel {
@@ -178,6 +677,40 @@ fn test_style_selector_matching() {
     assert!(found_green_container, "Should have found container with green background color");
 }
 
+fn specificity_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let parent = builder.create_element("div", &[]);
+    for (attrs, label) in [
+        (&[("class", "note"), ("id", "title")][..], "id"),
+        (&[("class", "note")][..], "class"),
+        (&[][..], "tag"),
+    ] {
+        let child = builder.create_element("div", attrs);
+        let text = builder.create_text(label, &[]);
+        builder.append_child(child, text);
+        builder.append_child(parent, child);
+    }
+    parent
+}
+
+#[test]
+fn test_more_specific_selectors_win_over_later_rules() {
+    // Rules in reverse order of specificity: each later one is less specific.
+    let stylesheet = "#title { color: #0000ff; } .note { color: #00ff00; } div { color: #ff0000; }";
+    let runtime = Runtime::new(styled_fixture(stylesheet, specificity_ui), MockMeasurer);
+    let mut colors: Vec<(String, Color)> = runtime.ui.render_data.values()
+        .filter_map(|data| match data {
+            RenderData::Text(text, style) => Some((text.clone(), style.color)),
+            _ => None,
+        })
+        .collect();
+    colors.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(colors, [
+        ("class".to_string(), Color::from_rgba8(0, 255, 0, 255)),
+        ("id".to_string(), Color::from_rgba8(0, 0, 255, 255)),
+        ("tag".to_string(), Color::from_rgba8(255, 0, 0, 255)),
+    ]);
+}
+
 #[derive(XeruneTemplate)]
 #[template(path = "test_template.html")]
 struct TestMacroModel {
@@ -187,7 +720,9 @@ struct TestMacroModel {
 
 impl Model for TestMacroModel {
     type Message = MockMsg;
-    fn update(&mut self, _msg: Self::Message, _context: &mut Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 #[test]
@@ -244,7 +779,9 @@ struct TestTodoModel {
 
 impl Model for TestTodoModel {
     type Message = MockMsg;
-    fn update(&mut self, _msg: Self::Message, _context: &mut Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 #[test]
@@ -288,3 +825,1492 @@ fn test_todo_layout_comparison() {
     print_taffy_tree(&runtime.ui.taffy, runtime.ui.root, 0);
     println!("----------------------------");
 }
+
+#[test]
+fn test_gradient_line_angles() {
+    let line = |angle: f32, width: f32, height: f32| {
+        let ((x0, y0), (x1, y1)) = LinearGradient { angle, stops: Vec::new().into() }.line(width, height);
+        [x0, y0, x1, y1].map(|v| v.round())
+    };
+    assert_eq!(line(0.0, 200.0, 100.0), [100.0, 100.0, 100.0, 0.0], "0deg goes to the top");
+    assert_eq!(line(90.0, 200.0, 100.0), [0.0, 50.0, 200.0, 50.0], "90deg goes to the right");
+    assert_eq!(line(180.0, 200.0, 100.0), [100.0, 0.0, 100.0, 100.0], "180deg goes to the bottom");
+    assert_eq!(line(270.0, 200.0, 100.0), [200.0, 50.0, 0.0, 50.0], "270deg goes to the left");
+    // Diagonals of a square run corner to corner.
+    assert_eq!(line(45.0, 100.0, 100.0), [0.0, 100.0, 100.0, 0.0]);
+    assert_eq!(line(135.0, 100.0, 100.0), [0.0, 0.0, 100.0, 100.0]);
+}
+
+struct TransitionModel {
+    lit: bool,
+}
+impl Model for TransitionModel {
+    type Message = MockMsg;
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        self.lit = !self.lit;
+        Command::none()
+    }
+}
+
+impl TemplateLayout for TransitionModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let background = if self.lit { "#0000ff" } else { "#ff0000" };
+        let style = format!("width: 20px; height: 20px; background-color: {background}; transition: background-color 50ms linear;");
+        builder.create_element("div", &[("id", "lamp"), ("style", &style)])
+    }
+}
+
+#[test]
+fn test_transition_eases_toward_rebuilt_style() {
+    let mut runtime = Runtime::new(TransitionModel { lit: false }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let background = |runtime: &Runtime<TransitionModel, MockMeasurer>| {
+        let (node, _) = runtime.ui.ids.iter().find(|(_, id)| id.as_str() == "lamp").unwrap();
+        runtime.ui.render_data.get(node).unwrap().style().background_color
+    };
+
+    runtime.handle_event(InputEvent::Message("toggle".to_string()));
+    assert_eq!(background(&runtime), Some(Color::from_rgba8(255, 0, 0, 255)), "starts from the color shown before");
+    assert!(runtime.tick().next_tick_in <= std::time::Duration::from_millis(20), "ticks at frame rate while running");
+
+    std::thread::sleep(std::time::Duration::from_millis(80));
+    runtime.tick();
+    assert_eq!(background(&runtime), Some(Color::from_rgba8(0, 0, 255, 255)));
+    assert!(runtime.tick().next_tick_in > std::time::Duration::from_secs(1), "stops ticking once finished");
+}
+
+fn selection_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let label = builder.create_element("div", &[("data-selection", "2,5")]);
+    let text = builder.create_text("abcdef", &[]);
+    builder.append_child(label, text);
+    label
+}
+
+/// Every character is 5px wide.
+struct MonoMeasurer;
+impl TextMeasurer for MonoMeasurer {
+    fn measure_text(&self, text: &str, _font_size: f32, _weight: u16) -> (f32, f32) {
+        (5.0 * text.chars().count() as f32, 10.0)
+    }
+}
+
+#[test]
+fn test_selection_highlights_character_range() {
+    let mut runtime = Runtime::new(fixture(selection_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let commands = runtime.ui.build_commands(&std::collections::HashMap::new());
+    let selection = commands.iter().position(|c| matches!(c, DrawCommand::DrawTextSelection { .. })).expect("selection is drawn");
+    assert!(matches!(commands[selection + 1], DrawCommand::DrawText { .. }), "behind the text");
+
+    let rect = commands[selection].selection_rect(&MonoMeasurer).unwrap();
+    assert_eq!((rect.x, rect.width), (10.0, 15.0));
+}
+
+#[test]
+fn test_text_hit_finds_nearest_character_boundary() {
+    let mut runtime = Runtime::new(fixture(selection_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let (node, offset) = runtime.ui.text_hit(12.0, 5.0, &MonoMeasurer).expect("over the text");
+    assert!(runtime.ui.text_hit(13.0, 5.0, &MonoMeasurer).is_some_and(|hit| hit == (node, 3)));
+    assert_eq!(offset, 2);
+    assert_eq!(runtime.ui.text_hit(12.0, 50.0, &MonoMeasurer), None);
+}
+
+/// Knows the families of a `FontRegistry`, like the renderers' measurers.
+struct FamilyMeasurer(Vec<String>);
+impl TextMeasurer for FamilyMeasurer {
+    fn measure_text(&self, _text: &str, _font_size: f32, _weight: u16) -> (f32, f32) {
+        (10.0, 10.0)
+    }
+
+    fn font_family_id(&self, name: &str) -> Option<u16> {
+        font_family_id(&self.0, name)
+    }
+}
+
+fn font_family_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[]);
+    for style in ["font-family: 'Test Mono', monospace; font-weight: bold;", "font-family: Unregistered Sans;"] {
+        let line = builder.create_element("div", &[("style", style)]);
+        let text = builder.create_text("text", &[]);
+        builder.append_child(line, text);
+        builder.append_child(root, line);
+    }
+    root
+}
+
+#[test]
+fn test_font_family_picks_registered_faces() {
+    let mut registry = FontRegistry::new(&["regular", "bold"]);
+    registry.register("Test Mono", &["mono", "mono bold"]);
+    let fonts = registry.fonts();
+
+    let mut runtime = Runtime::new(fixture(font_family_ui), FamilyMeasurer(registry.families().to_vec()));
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let faces: Vec<&str> = runtime.ui.build_commands(&std::collections::HashMap::new()).iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { weight, .. } => Some(fonts[font_index(*weight, fonts.len())]),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(faces, ["mono bold", "regular"], "an unknown family falls back to the default one");
+
+    // A measurer that doesn't know the family draws it in the default one.
+    let mut runtime = Runtime::new(fixture(font_family_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let weights: Vec<u16> = runtime.ui.build_commands(&std::collections::HashMap::new()).iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { weight, .. } => Some(*weight),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(weights, [1, 0]);
+}
+
+fn bidi_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[]);
+    for (style, text) in [("", "abc \u{5d0}\u{5d1}\u{5d2} 12"), ("direction: rtl;", "Hello (world)!"), ("direction: rtl;", "\u{5d0}\u{5d1} 12 (x)")] {
+        let line = builder.create_element("div", &[("style", style)]);
+        let text = builder.create_text(text, &[]);
+        builder.append_child(line, text);
+        builder.append_child(root, line);
+    }
+    root
+}
+
+#[test]
+fn test_bidi_text_drawn_in_visual_order() {
+    let mut runtime = Runtime::new(fixture(bidi_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let texts: Vec<String> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { text, .. } => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, [
+        "abc 12 \u{5d2}\u{5d1}\u{5d0}",
+        "!Hello (world)",
+        "(x) 12 \u{5d1}\u{5d0}",
+    ]);
+}
+
+fn line_break_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[]);
+    for child in ["one", "br", "two", "br", "br", "three"] {
+        let child = if child == "br" { builder.create_element("br", &[]) } else { builder.create_text(child, &[]) };
+        builder.append_child(root, child);
+    }
+    root
+}
+
+#[test]
+fn test_br_breaks_lines_and_repeats_leave_blank_lines() {
+    let mut runtime = Runtime::new(fixture(line_break_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size { width: taffy::prelude::AvailableSpace::Definite(200.0), height: taffy::prelude::AvailableSpace::MaxContent });
+    let tops: Vec<f32> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { rect, .. } => Some(rect.y),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tops, [0.0, 10.0, 30.0]);
+}
+
+fn inline_run_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let paragraph = builder.create_element("p", &[]);
+    let before = builder.create_text("Hello ", &[]);
+    let strong = builder.create_element("strong", &[("id", "bold")]);
+    let bold = builder.create_text("bold", &[]);
+    let after = builder.create_text(" world", &[]);
+    builder.append_child(strong, bold);
+    builder.append_child(paragraph, before);
+    builder.append_child(paragraph, strong);
+    builder.append_child(paragraph, after);
+    paragraph
+}
+
+#[test]
+fn test_inline_elements_flow_and_wrap_with_surrounding_text() {
+    let mut runtime = Runtime::new(fixture(inline_run_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size { width: taffy::prelude::AvailableSpace::Definite(60.0), height: taffy::prelude::AvailableSpace::MaxContent });
+    let words: Vec<(String, f32, f32, u16)> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { text, rect, weight, .. } => Some((text, rect.x, rect.y, weight)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(words, [
+        ("Hello ".to_string(), 0.0, 0.0, 0),
+        ("bold ".to_string(), 30.0, 0.0, 1),
+        ("world".to_string(), 0.0, 10.0, 0),
+    ]);
+    // The merged `strong` is gone from the tree; its id finds its first word.
+    assert_eq!(runtime.ui.taffy.total_node_count(), 4);
+    assert_eq!(runtime.ui.rect_for_id("bold"), Some(Rect { x: 30.0, y: 0.0, width: 25.0, height: 10.0 }));
+}
+
+fn clickable_paragraph_ui(span: &'static [(&'static str, &'static str)]) -> impl Fn(&mut UiBuilder) -> taffy::NodeId {
+    move |builder| {
+        let paragraph = builder.create_element("p", &[("data-on-click", "toggle")]);
+        let inline = builder.create_element("span", span);
+        let text = builder.create_text("x", &[]);
+        let after = builder.create_text(" y", &[]);
+        builder.append_child(inline, text);
+        builder.append_child(paragraph, inline);
+        builder.append_child(paragraph, after);
+        paragraph
+    }
+}
+
+#[test]
+fn test_inline_spans_that_stop_or_swallow_clicks_keep_doing_so() {
+    // A span that stops propagation still stops it...
+    let mut runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-on-click", "tick"), ("data-stop-propagation", "")])), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(runtime.ui.hit_path(5.0, 5.0), ["tick"]);
+
+    // ...and a disabled one still swallows the click.
+    let mut runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-disabled", "")])), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert!(runtime.ui.hit_path(5.0, 5.0).is_empty());
+}
+
+#[test]
+fn test_inline_spans_with_listeners_keep_their_node() {
+    // Listeners and hover styles stay on a node in the tree.
+    let listening = |runtime: &Runtime<_, MockMeasurer>, listeners: fn(&Ui) -> &xerune::ui::NodeMap<String>| {
+        let (node, _) = listeners(&runtime.ui).iter().next().expect("the span should be listed");
+        runtime.ui.taffy.parent(node) == Some(runtime.ui.root)
+    };
+    let runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-on-drag", "tick")])), MockMeasurer);
+    assert!(listening(&runtime, |ui| &ui.drag_listeners));
+    let runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-on-scroll", "list")])), MockMeasurer);
+    assert!(listening(&runtime, |ui| &ui.scroll_listeners));
+    let runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-on-input", "name")])), MockMeasurer);
+    assert!(listening(&runtime, |ui| &ui.input_listeners));
+    let mut runtime = Runtime::new(fixture(clickable_paragraph_ui(&[("data-hover-style", "color: red")])), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert!(runtime.ui.update_hover(5.0, 5.0), "the span should show its hover style");
+    assert_eq!(runtime.ui.hovered.and_then(|node| runtime.ui.taffy.parent(node)), Some(runtime.ui.root));
+}
+
+fn link_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column;")]);
+    for attrs in [&[("href", "/tracks")][..], &[("href", "/albums"), ("data-on-click", "tick")]] {
+        let link = builder.create_element("a", attrs);
+        let text = builder.create_text("link", &[]);
+        builder.append_child(link, text);
+        builder.append_child(root, link);
+    }
+    root
+}
+
+#[test]
+fn test_link_navigates_unless_handled_and_is_underlined() {
+    let mut runtime = Runtime::new(fixture(link_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    assert_eq!(runtime.ui.hit_test(5.0, 5.0).map(|(interaction, _)| interaction).as_deref(), Some("navigate:/tracks"));
+    assert_eq!(runtime.ui.hit_test(5.0, 15.0).map(|(interaction, _)| interaction).as_deref(), Some("tick"));
+
+    let underlines = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter(|command| matches!(command, DrawCommand::DrawRect { rect, color: Some(color), .. } if rect.height == 1.0 && *color == Color::from_rgba8(0, 122, 255, 255)))
+        .count();
+    assert_eq!(underlines, 2);
+}
+
+#[test]
+fn test_hovered_link_shows_pointer_cursor() {
+    let mut runtime = Runtime::new(fixture(link_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(runtime.cursor(), Cursor::Default);
+
+    runtime.handle_event(InputEvent::Hover { x: 5.0, y: 15.0 });
+    assert_eq!(runtime.cursor(), Cursor::Pointer);
+    runtime.handle_event(InputEvent::Hover { x: 500.0, y: 500.0 });
+    assert_eq!(runtime.cursor(), Cursor::Default);
+}
+
+fn outline_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex;")]);
+    for class in ["ring", ""] {
+        let cell = builder.create_element("div", &[("class", class), ("style", "width: 20px; height: 20px;")]);
+        builder.append_child(root, cell);
+    }
+    root
+}
+
+#[test]
+fn test_outline_is_drawn_outside_the_box_without_moving_it() {
+    let mut runtime = Runtime::new(styled_fixture(".ring { outline: 2px solid red; }", outline_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+
+    let rects: Vec<Rect> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawRect { rect, border_width, border_color, .. } if border_width == 2.0 && border_color == Some(Color::from_rgba8(255, 0, 0, 255)) => Some(rect),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(rects, vec![Rect { x: -1.0, y: -1.0, width: 22.0, height: 22.0 }]);
+    // The second box still starts where the first one ends.
+    assert_eq!(runtime.ui.hit_test_stack(21.0, 5.0).len(), 2);
+}
+
+fn list_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let list = builder.create_element("ol", &[("style", "margin: 0;")]);
+    for text in ["first", "second"] {
+        let item = builder.create_element("li", &[("style", "margin: 0;")]);
+        let text = builder.create_text(text, &[]);
+        builder.append_child(item, text);
+        builder.append_child(list, item);
+    }
+    let nested = builder.create_element("ul", &[]);
+    let item = builder.create_element("li", &[("style", "margin: 0;")]);
+    let text = builder.create_text("inner", &[]);
+    builder.append_child(item, text);
+    builder.append_child(nested, item);
+    builder.append_child(list, nested);
+    list
+}
+
+#[test]
+fn test_list_items_get_markers_hanging_before_their_content() {
+    let mut runtime = Runtime::new(fixture(list_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size { width: taffy::prelude::AvailableSpace::Definite(200.0), height: taffy::prelude::AvailableSpace::MaxContent });
+    let texts: Vec<(String, f32, f32)> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { text, rect, .. } => Some((text, rect.x, rect.y)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, [
+        ("first".to_string(), 20.0, 0.0),
+        ("1.".to_string(), 2.0, 0.0),
+        ("second".to_string(), 20.0, 10.0),
+        ("2.".to_string(), 2.0, 10.0),
+        ("inner".to_string(), 40.0, 20.0),
+        ("\u{2022}".to_string(), 27.0, 20.0),
+    ]);
+}
+
+fn row_list_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let list = builder.create_element("ul", &[]);
+    for action in ["tick", "toggle"] {
+        let row = builder.create_element("li", &[("data-on-click", action)]);
+        let text = builder.create_text(action, &[]);
+        builder.append_child(row, text);
+        builder.append_child(list, row);
+    }
+    list
+}
+
+#[test]
+fn test_arrow_keys_move_active_row_and_enter_clicks_it() {
+    let mut runtime = Runtime::new(fixture(row_list_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+    let active = |runtime: &Runtime<_, MockMeasurer>| runtime.ui.focused.and_then(|node| runtime.ui.focus_key(node)).map(str::to_string);
+
+    // Until a row has focus, arrows are the model's.
+    runtime.handle_event(InputEvent::KeyDown("ArrowDown".to_string()));
+    assert_eq!(active(&runtime), None);
+    assert_eq!(*sent.lock().unwrap(), ["keydown:ArrowDown"]);
+    sent.lock().unwrap().clear();
+
+    runtime.handle_event(InputEvent::KeyDown("Tab".to_string()));
+    assert_eq!(active(&runtime).as_deref(), Some("tick"));
+    runtime.handle_event(InputEvent::KeyDown("ArrowDown".to_string()));
+    runtime.handle_event(InputEvent::KeyDown("ArrowDown".to_string()));
+    assert_eq!(active(&runtime).as_deref(), Some("toggle"), "the last row stays active");
+    runtime.handle_event(InputEvent::KeyDown("ArrowUp".to_string()));
+    assert_eq!(active(&runtime).as_deref(), Some("tick"));
+
+    let highlighted = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .any(|command| matches!(command, DrawCommand::DrawRect { color: Some(color), border_width: 0.0, .. } if color.a == 40));
+    assert!(highlighted, "the active row is filled");
+
+    runtime.handle_event(InputEvent::KeyDown("Enter".to_string()));
+    assert_eq!(*sent.lock().unwrap(), ["tick"]);
+}
+
+fn button_states_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column;"), ("data-on-click", "toggle")]);
+    let enabled = builder.create_element("button", &[("data-on-click", "tick"), ("style", "margin: 0; width: 40px; height: 20px;")]);
+    let disabled = builder.create_element("button", &[("data-on-click", "tick"), ("data-disabled", ""), ("style", "margin: 0; width: 40px; height: 20px;")]);
+    builder.append_child(root, enabled);
+    builder.append_child(root, disabled);
+    root
+}
+
+#[test]
+fn test_disabled_button_swallows_clicks_and_pressed_button_darkens() {
+    let mut runtime = Runtime::new(fixture(button_states_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+    let background = |runtime: &Runtime<_, MockMeasurer>, y: f32| {
+        let node = *runtime.ui.hit_test_stack(5.0, y).last().unwrap();
+        runtime.ui.render_data.get(node).unwrap().style().background_color
+    };
+    let base = background(&runtime, 5.0);
+
+    runtime.handle_event(InputEvent::PointerDown { x: 5.0, y: 25.0 });
+    runtime.handle_event(InputEvent::Click { x: 5.0, y: 25.0 });
+    assert!(sent.lock().unwrap().is_empty());
+    assert_eq!(runtime.ui.pressed, None);
+    assert_ne!(background(&runtime, 25.0), base);
+    runtime.handle_event(InputEvent::PointerUp { x: 5.0, y: 25.0 });
+
+    runtime.handle_event(InputEvent::PointerDown { x: 5.0, y: 5.0 });
+    runtime.handle_event(InputEvent::Click { x: 5.0, y: 5.0 });
+    assert_eq!(sent.lock().unwrap().last().map(String::as_str), Some("tick"));
+    assert_ne!(background(&runtime, 5.0), base);
+    runtime.handle_event(InputEvent::PointerUp { x: 5.0, y: 5.0 });
+    assert_eq!(background(&runtime, 5.0), base);
+}
+
+#[test]
+fn test_hsl_colors_and_current_color_parse_in_color_properties() {
+    let green = Color::from_rgba8(0, 255, 0, 255);
+    let mut style = ContainerStyle::default();
+    let mut layout = taffy::style::Style::default();
+    css::parse_inline_style(
+        "color: hsl(120, 100%, 50%); background-color: hsla(120, 100%, 50%, 0.5); border: 1px solid hsl(120, 100%, 50%); border-left: 2px solid hsl(0, 100%, 50%)",
+        &mut style,
+        &mut layout,
+    );
+    assert_eq!(style.color, green);
+    assert_eq!(style.background_color.map(|c| (c.r, c.g, c.b)), Some((0, 255, 0)));
+    assert!(style.background_color.is_some_and(|c| c.a > 120 && c.a < 135));
+    assert_eq!((style.border_width, style.border_color), (1.0, Some(green)));
+    assert_eq!(style.border_side_colors[3], Some(Color::from_rgba8(255, 0, 0, 255)));
+
+    let mut style = ContainerStyle { color: Color::from_rgba8(10, 20, 30, 255), ..ContainerStyle::default() };
+    css::parse_inline_style("border-color: currentColor", &mut style, &mut layout);
+    assert_eq!(style.border_color, Some(Color::from_rgba8(10, 20, 30, 255)));
+    css::parse_inline_style("color: hsl(120, 100%, 50%); border-top-color: currentcolor", &mut style, &mut layout);
+    assert_eq!(style.border_side_colors[0], Some(green));
+}
+
+#[test]
+fn test_modern_rgb_syntax_parses_in_colors_and_gradient_stops() {
+    let grey = Color::from_rgba8(128, 128, 128, 255);
+    let mut style = ContainerStyle::default();
+    let mut layout = taffy::style::Style::default();
+    css::parse_inline_style(
+        "color: rgb(50% 50% 50%); background-color: rgb(0 0 0 / 50%); border-color: rgb(50%, 50%, 50%)",
+        &mut style,
+        &mut layout,
+    );
+    assert_eq!(style.color, grey);
+    assert_eq!(style.background_color.map(|c| (c.r, c.g, c.b)), Some((0, 0, 0)));
+    assert!(style.background_color.is_some_and(|c| c.a > 120 && c.a < 135));
+    assert_eq!(style.border_color, Some(grey));
+
+    css::parse_inline_style("background: linear-gradient(90deg, rgb(0 0 0 / 50%) 20%, rgba(12, 34, 56, 0.8), rgb(50% 50% 50%))", &mut style, &mut layout);
+    let Some(Gradient::Linear(gradient)) = style.background_gradient else { panic!("no gradient") };
+    let stops: Vec<(u8, u8, u8, f32)> = gradient.stops.iter().map(|(c, pos)| (c.r, c.g, c.b, *pos)).collect();
+    assert_eq!(stops, [(0, 0, 0, 0.2), (12, 34, 56, 0.6), (128, 128, 128, 1.0)]);
+}
+
+#[test]
+fn test_gradient_stops_keep_space_separated_color_functions_whole() {
+    let mut style = ContainerStyle::default();
+    let mut layout = taffy::style::Style::default();
+    css::parse_inline_style("background: linear-gradient(180deg, rgb(255 0 0) 0%, hsl(120 100% 50%) 40%, rgb( 0 0 255 ))", &mut style, &mut layout);
+    let Some(Gradient::Linear(gradient)) = style.background_gradient.clone() else { panic!("no linear gradient") };
+    let stops: Vec<(u8, u8, u8, f32)> = gradient.stops.iter().map(|(c, pos)| (c.r, c.g, c.b, *pos)).collect();
+    assert_eq!(stops, [(255, 0, 0, 0.0), (0, 255, 0, 0.4), (0, 0, 255, 1.0)]);
+
+    css::parse_inline_style("background: radial-gradient(circle, hsl(0 100% 50%) 10%, rgb(0 0 255 / 50%))", &mut style, &mut layout);
+    let Some(Gradient::Radial(gradient)) = style.background_gradient.clone() else { panic!("no radial gradient") };
+    let stops: Vec<(u8, u8, u8, f32)> = gradient.stops.iter().map(|(c, pos)| (c.r, c.g, c.b, *pos)).collect();
+    assert_eq!(stops, [(255, 0, 0, 0.1), (0, 0, 255, 1.0)]);
+    assert!(gradient.circle);
+}
+
+#[test]
+fn test_linear_gradient_spreads_stops_without_positions_evenly() {
+    let stops = |val: &str| {
+        let mut style = ContainerStyle::default();
+        css::parse_inline_style(val, &mut style, &mut taffy::style::Style::default());
+        let Some(Gradient::Linear(gradient)) = style.background_gradient else { panic!("no gradient") };
+        gradient.stops.iter().map(|(_, pos)| *pos).collect::<Vec<f32>>()
+    };
+    assert_eq!(stops("background: linear-gradient(red, green, blue)"), [0.0, 0.5, 1.0]);
+    assert_eq!(stops("background: linear-gradient(red, green 20%, blue, white, black 80%)"), [0.0, 0.2, 0.4, 0.6, 0.8]);
+    assert_eq!(stops("background: linear-gradient(red 50%, green 10%, blue)"), [0.5, 0.5, 1.0]);
+}
+
+#[test]
+fn test_radial_gradient_positions_stops_like_linear() {
+    let stops = |val: &str| {
+        let mut style = ContainerStyle::default();
+        css::parse_inline_style(val, &mut style, &mut taffy::style::Style::default());
+        let Some(Gradient::Radial(gradient)) = style.background_gradient else { panic!("no gradient") };
+        gradient.stops.iter().map(|(_, pos)| *pos).collect::<Vec<f32>>()
+    };
+    assert_eq!(stops("background: radial-gradient(red, green, blue)"), [0.0, 0.5, 1.0]);
+    // Unpositioned stops spread between their positioned neighbours, not by index.
+    assert_eq!(stops("background: radial-gradient(circle, red, green 20%, blue, white, black 80%)"), [0.0, 0.2, 0.4, 0.6, 0.8]);
+    assert_eq!(stops("background: radial-gradient(red 50%, green 10%, blue)"), [0.5, 0.5, 1.0]);
+}
+
+struct DragModel {
+    spacer: bool,
+}
+impl Model for DragModel {
+    type Message = MockMsg;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if msg == MockMsg::Toggle {
+            self.spacer = !self.spacer;
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for DragModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column;")]);
+        if self.spacer {
+            let spacer = builder.create_element("div", &[("style", "height: 20px;")]);
+            builder.append_child(root, spacer);
+        }
+        let button = builder.create_element("div", &[("data-on-click", "tick"), ("style", "width: 100px; height: 20px;")]);
+        let track = builder.create_element("div", &[("data-on-drag", "seek"), ("style", "width: 100px; height: 20px;")]);
+        builder.append_child(root, button);
+        builder.append_child(root, track);
+        root
+    }
+}
+
+#[test]
+fn test_only_drag_listeners_report_drags_and_capture_survives_rebuilds() {
+    let mut runtime = Runtime::new(DragModel { spacer: false }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+
+    runtime.handle_event(InputEvent::PointerDown { x: 50.0, y: 10.0 });
+    runtime.handle_event(InputEvent::Hover { x: 60.0, y: 10.0 });
+    runtime.handle_event(InputEvent::PointerUp { x: 60.0, y: 10.0 });
+    assert!(sent.lock().unwrap().is_empty());
+
+    runtime.handle_event(InputEvent::PointerDown { x: 50.0, y: 30.0 });
+    // The rebuild moves the track down and renumbers its node.
+    runtime.handle_event(InputEvent::Message("toggle".to_string()));
+    runtime.handle_event(InputEvent::Hover { x: 25.0, y: 50.0 });
+    let track = runtime.captured_node().and_then(|node| runtime.ui.node_bounds(node));
+    assert_eq!(track.map(|bounds| bounds.y), Some(40.0));
+    assert_eq!(sent.lock().unwrap().as_slice(), ["drag:seek:0.5:0.5", "toggle", "drag:seek:0.25:0.5"]);
+}
+
+fn font_size_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("class", "app")]);
+    let title = builder.create_element("h1", &[("class", "big")]);
+    let title_text = builder.create_text("Title", &[]);
+    builder.append_child(title, title_text);
+    let pad = builder.create_element("div", &[("class", "pad"), ("id", "pad")]);
+    let pad_text = builder.create_text("Small", &[]);
+    builder.append_child(pad, pad_text);
+    builder.append_child(title, pad);
+    builder.append_child(root, title);
+    root
+}
+
+#[test]
+fn test_font_size_em_uses_parent_size_and_rem_uses_root_size() {
+    let mut runtime = Runtime::new(styled_fixture(r#"
+        .app { font-size: 20px; }
+        h1 { font-size: 2em; }
+        .big { font-size: 2em; }
+        .pad { padding: 1rem; font-size: 0.5rem; }
+        "#, font_size_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let font_size = |wanted: &str| runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::Text(text, style) if text == wanted => Some(style.font_size),
+        _ => None,
+    });
+    // `h1` and `.big` both say `2em`: twice the parent's 20px, not 2 × 2 × 32px.
+    assert_eq!(font_size("Title"), Some(40.0));
+    // `rem` is the root element's 20px, whatever the parent's size.
+    assert_eq!(font_size("Small"), Some(10.0));
+    let pad = runtime.ui.rect_for_id("pad").unwrap();
+    let text = (&runtime.ui.render_data).into_iter().find_map(|(node, data)| matches!(data, RenderData::Text(text, _) if text == "Small").then_some(node));
+    let text = runtime.ui.node_bounds(text.unwrap()).unwrap();
+    assert_eq!((text.x - pad.x, text.y - pad.y), (20.0, 20.0));
+}
+
+impl Renderer for MockMeasurer {
+    fn render(&mut self, _commands: &[DrawCommand], _canvases: &std::collections::HashMap<String, Canvas>, _dirty_rect: Option<Rect>) {}
+}
+
+struct MovedBoxModel {
+    lit: bool,
+}
+impl Model for MovedBoxModel {
+    type Message = MockMsg;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if msg == MockMsg::Toggle {
+            self.lit = !self.lit;
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for MovedBoxModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let root = builder.create_element("div", &[("style", "width: 300px; height: 100px; background-color: #ffffff;")]);
+        let color = if self.lit { "background-color: #ff0000;" } else { "background-color: #0000ff;" };
+        let moved = builder.create_element("div", &[("style", format!("width: 20px; height: 20px; transform: translate(200px, 0px); {color}").as_str())]);
+        builder.append_child(root, moved);
+        root
+    }
+}
+
+#[test]
+fn test_dirty_region_follows_transforms() {
+    let mut runtime = Runtime::new(MovedBoxModel { lit: false }, MockMeasurer);
+    let mut renderer = MockMeasurer;
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(runtime.render(&mut renderer), None);
+
+    runtime.handle_event(InputEvent::Message("toggle".to_string()));
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let dirty = runtime.render(&mut renderer).expect("a partial repaint");
+    // The box is drawn at (0, 0) but shows up 200px to the right.
+    assert!(dirty.x <= 200.0 && dirty.x + dirty.width >= 220.0, "{dirty:?}");
+    assert!(dirty.x > 100.0, "{dirty:?}");
+}
+
+fn rtl_line_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "width: 200px;")]);
+    for style in ["direction: rtl;", "direction: rtl; text-align: left;"] {
+        let line = builder.create_element("div", &[("style", style)]);
+        let text = builder.create_text("abc", &[]);
+        builder.append_child(line, text);
+        builder.append_child(root, line);
+    }
+    root
+}
+
+#[test]
+fn test_single_line_rtl_text_starts_on_the_right() {
+    let mut runtime = Runtime::new(fixture(rtl_line_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let rects: Vec<Rect> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { rect, .. } => Some(rect),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(rects, [Rect::new(185.0, 0.0, 15.0, 10.0), Rect::new(0.0, 10.0, 15.0, 10.0)]);
+}
+
+#[test]
+fn test_debug_overlay_outlines_and_labels_every_box() {
+    let mut runtime = Runtime::new(MovedBoxModel { lit: false }, MockMeasurer);
+    let mut renderer = MockMeasurer;
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    runtime.render(&mut renderer);
+    assert!(!runtime.needs_redraw());
+
+    let labels: Vec<(String, Rect)> = runtime.ui.build_debug_commands().into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { text, rect, .. } => Some((text, rect)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(labels, [
+        ("div 300x100".to_string(), Rect::new(2.0, 1.0, 296.0, 10.0)),
+        ("div 20x20".to_string(), Rect::new(2.0, 1.0, 16.0, 10.0)),
+    ]);
+
+    runtime.set_debug(true);
+    assert!(runtime.needs_redraw());
+    let dirty = runtime.render(&mut renderer).expect("a partial repaint");
+    assert!(dirty.width >= 300.0 && dirty.height >= 100.0, "{dirty:?}");
+}
+
+#[test]
+fn test_characters_missing_from_the_face_come_from_a_fallback_font() {
+    // Each "font" covers the characters in its string.
+    let fonts = ["ab", "ab", "xyz"];
+    let lookups = std::cell::Cell::new(0);
+    let fallback = |c: char| {
+        lookups.set(lookups.get() + 1);
+        fonts.iter().position(|font| font.contains(c))
+    };
+    let runs = font_runs("ab x?a", 1, |c| fonts[1].contains(c), fallback);
+    // `x` comes from the third font; `?`, which none has, and the space stay in the face.
+    assert_eq!(runs, [(0..3, 1), (3..4, 2), (4..6, 1)]);
+    assert_eq!(lookups.get(), 2, "only characters the face lacks are looked up");
+}
+
+#[test]
+fn test_shadow_bounds_cover_the_whole_blur() {
+    let shadow = DrawCommand::DrawShadow { rect: Rect::new(100.0, 100.0, 50.0, 50.0), border_radius: 0.0, blur: 10.0, color: Color::BLACK };
+    let margin = xerune::graphics::shadow_margin(10.0);
+    assert_eq!(margin, 15.0);
+    let bounds = shadow.bounds().unwrap();
+    assert!(bounds.x <= 100.0 - margin && bounds.x + bounds.width >= 150.0 + margin, "{bounds:?}");
+}
+
+fn line_height_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "width: 30px;")]);
+    for (style, text) in [("line-height: 30px;", "one"), ("line-height: 2;", "aa bb cc dd")] {
+        let line = builder.create_element("div", &[("style", style)]);
+        let text = builder.create_text(text, &[]);
+        builder.append_child(line, text);
+        builder.append_child(root, line);
+    }
+    root
+}
+
+#[test]
+fn test_line_height_centers_glyphs_and_keeps_the_style() {
+    let mut runtime = Runtime::new(fixture(line_height_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let rects: Vec<Rect> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { rect, .. } => Some(rect),
+            _ => None,
+        })
+        .collect();
+    // A 30px line box around 10px glyphs, then two lines 20px apart.
+    assert_eq!(rects, [Rect::new(0.0, 10.0, 15.0, 10.0), Rect::new(0.0, 35.0, 30.0, 10.0), Rect::new(0.0, 55.0, 30.0, 10.0)]);
+
+    // The computed style still holds the declared line height.
+    let heights: Vec<LineHeight> = runtime.ui.render_data.values()
+        .filter_map(|data| match data {
+            RenderData::Text(_, style) => Some(style.line_height),
+            _ => None,
+        })
+        .collect();
+    assert!(heights.contains(&LineHeight::Px(30.0)) && heights.contains(&LineHeight::Multiple(2.0)), "{heights:?}");
+}
+
+fn scrolled_ellipsis_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "width: 50px; overflow: scroll; white-space: nowrap; text-overflow: ellipsis;")]);
+    let text = builder.create_text("abcdefghijklmnop", &[]);
+    builder.append_child(root, text);
+    root
+}
+
+#[test]
+fn test_ellipsis_stays_at_the_edge_of_a_scrolled_box() {
+    let mut runtime = Runtime::new(fixture(scrolled_ellipsis_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let root = runtime.ui.root;
+    let max_widths = |runtime: &Runtime<_, MonoMeasurer>| -> Vec<(f32, Option<f32>)> {
+        runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::DrawText { rect, max_width, .. } => Some((rect.x, max_width)),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(max_widths(&runtime), [(0.0, Some(50.0))]);
+
+    // Scrolled 20px, the text starts left of the box and still ends at its right edge.
+    runtime.ui.scroll_offsets.insert(root, (20.0, 0.0));
+    assert_eq!(max_widths(&runtime), [(-20.0, Some(70.0))]);
+}
+
+#[test]
+fn test_font_index_picks_the_face_for_weight_style_and_family() {
+    const BOLD: u16 = 1;
+    const ITALIC: u16 = 2;
+    const SECOND_FAMILY: u16 = 1 << 2;
+    // Regular, bold, italic and bold italic faces of two families.
+    assert_eq!([0, BOLD, ITALIC, BOLD | ITALIC].map(|weight| font_index(weight, 8)), [0, 1, 2, 3]);
+    assert_eq!([0, BOLD, ITALIC, BOLD | ITALIC].map(|weight| font_index(SECOND_FAMILY | weight, 8)), [4, 5, 6, 7]);
+    // Missing faces fall back to italic, then bold, then regular.
+    assert_eq!([0, BOLD, ITALIC, BOLD | ITALIC].map(|weight| font_index(weight, 3)), [0, 1, 2, 2]);
+    assert_eq!([0, BOLD, ITALIC, BOLD | ITALIC].map(|weight| font_index(weight, 2)), [0, 1, 0, 1]);
+    assert_eq!([0, BOLD, ITALIC, BOLD | ITALIC].map(|weight| font_index(weight, 1)), [0, 0, 0, 0]);
+    // A family that isn't loaded is drawn in the first one.
+    assert_eq!(font_index(SECOND_FAMILY | BOLD, 4), 1);
+}
+
+fn hidden_button_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; width: 200px;")]);
+    for (message, style) in [("hidden", "width: 40px; height: 20px; visibility: hidden;"), ("shown", "width: 40px; height: 20px;")] {
+        let button = builder.create_element("div", &[("style", style), ("data-on-click", message)]);
+        builder.append_child(root, button);
+    }
+    root
+}
+
+#[test]
+fn test_hidden_elements_keep_their_box_but_take_no_focus() {
+    let mut runtime = Runtime::new(fixture(hidden_button_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let buttons = runtime.ui.taffy.children(runtime.ui.root).unwrap();
+    let hidden = runtime.ui.taffy.layout(buttons[0]).unwrap();
+    assert_eq!((hidden.size.width, hidden.size.height), (40.0, 20.0));
+    // The visible button still sits after the hidden one's box.
+    assert_eq!(runtime.ui.taffy.layout(buttons[1]).unwrap().location.x, 40.0);
+
+    assert_eq!(runtime.ui.focusable, [buttons[1]]);
+    assert!(runtime.ui.hit_test(10.0, 10.0).is_none_or(|(_, node)| node != buttons[0]));
+    assert!(runtime.ui.hit_test(50.0, 10.0).is_some_and(|(_, node)| node == buttons[1]));
+}
+
+fn aspect_ratio_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column; align-items: flex-start;")]);
+    // The canvas defaults to 200x200.
+    for style in [
+        "aspect-ratio: 2;",
+        "aspect-ratio: 2; width: 100px;",
+        "aspect-ratio: 2; width: 200px;",
+        "aspect-ratio: 2; height: 200px;",
+        "aspect-ratio: 2; width: 50px; height: 50px;",
+    ] {
+        let canvas = builder.create_element("canvas", &[("style", style)]);
+        builder.append_child(root, canvas);
+    }
+    root
+}
+
+#[test]
+fn test_aspect_ratio_derives_the_side_css_left_alone() {
+    let mut runtime = Runtime::new(fixture(aspect_ratio_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sizes: Vec<(f32, f32)> = runtime.ui.taffy.children(runtime.ui.root).unwrap().into_iter()
+        .map(|canvas| {
+            let size = runtime.ui.taffy.layout(canvas).unwrap().size;
+            (size.width, size.height)
+        })
+        .collect();
+    // Setting a side to its default value still counts as setting it.
+    assert_eq!(sizes, [(200.0, 100.0), (100.0, 50.0), (200.0, 100.0), (400.0, 200.0), (50.0, 50.0)]);
+}
+
+fn white_space_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "width: 30px;")]);
+    for (style, text) in [("", "aa  bb cc dd"), ("white-space: nowrap;", "aa  bb cc dd"), ("white-space: pre;", "a  b\ncc")] {
+        let block = builder.create_element("div", &[("style", style)]);
+        let text = builder.create_text(text, &[]);
+        builder.append_child(block, text);
+        builder.append_child(root, block);
+    }
+    root
+}
+
+#[test]
+fn test_white_space_collapses_wraps_or_keeps_lines() {
+    let mut runtime = Runtime::new(fixture(white_space_ui), MonoMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let texts: Vec<(String, f32, f32)> = runtime.ui.taffy.children(runtime.ui.root).unwrap().into_iter()
+        .map(|block| {
+            let text = runtime.ui.taffy.children(block).unwrap()[0];
+            let Some(RenderData::Text(content, _)) = runtime.ui.render_data.get(text) else { panic!("no text") };
+            let size = runtime.ui.taffy.layout(text).unwrap().size;
+            (content.clone(), size.width, size.height)
+        })
+        .collect();
+    assert_eq!(texts, [
+        // Collapsed and wrapped to two lines.
+        ("aa bb cc dd".to_string(), 30.0, 20.0),
+        // Collapsed, but one line overflowing the container.
+        ("aa bb cc dd".to_string(), 55.0, 10.0),
+        // Kept as written, a line per line of the source.
+        ("a  b\ncc".to_string(), 20.0, 20.0),
+    ]);
+}
+
+fn scrollbars_ui(content: &'static str) -> impl Fn(&mut UiBuilder) -> taffy::NodeId {
+    move |builder| {
+        let root = builder.create_element("div", &[("style", "width: 100px; height: 100px; overflow: scroll;")]);
+        let child = builder.create_element("div", &[("style", content)]);
+        builder.append_child(root, child);
+        root
+    }
+}
+
+#[test]
+fn test_scrollbars_leave_the_corner_free() {
+    let mut runtime = Runtime::new(fixture(scrollbars_ui("width: 400px; height: 200px; flex-shrink: 0;")), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let scrollbars = |runtime: &Runtime<_, MockMeasurer>| -> Vec<(Rect, Rect)> {
+        runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+            .filter_map(|command| match command {
+                DrawCommand::DrawScrollbar { rect, thumb_rect, .. } => Some((rect, thumb_rect)),
+                _ => None,
+            })
+            .collect()
+    };
+    // Both tracks stop 6px short of the corner; the thumbs show half and a quarter of the content.
+    assert_eq!(scrollbars(&runtime), [
+        (Rect::new(94.0, 0.0, 6.0, 94.0), Rect::new(94.0, 0.0, 6.0, 47.0)),
+        (Rect::new(0.0, 94.0, 94.0, 6.0), Rect::new(0.0, 94.0, 23.5, 6.0)),
+    ]);
+
+    // Scrolled to the end, the thumbs end at the corner too.
+    runtime.ui.scroll_offsets.insert(runtime.ui.root, (300.0, 100.0));
+    assert_eq!(scrollbars(&runtime), [
+        (Rect::new(94.0, 0.0, 6.0, 94.0), Rect::new(94.0, 47.0, 6.0, 47.0)),
+        (Rect::new(0.0, 94.0, 94.0, 6.0), Rect::new(70.5, 94.0, 23.5, 6.0)),
+    ]);
+
+    // A single scrollbar runs the whole edge.
+    let mut runtime = Runtime::new(fixture(scrollbars_ui("width: 50px; height: 200px; flex-shrink: 0;")), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(scrollbars(&runtime), [(Rect::new(94.0, 0.0, 6.0, 100.0), Rect::new(94.0, 0.0, 6.0, 50.0))]);
+}
+
+#[test]
+fn test_inset_shorthand_takes_one_to_four_values() {
+    use taffy::style::LengthPercentageAuto as L;
+    let inset = |val: &str| {
+        let mut layout = taffy::style::Style::default();
+        css::parse_inline_style(val, &mut ContainerStyle::default(), &mut layout);
+        let taffy::geometry::Rect { top, right, bottom, left } = layout.inset;
+        [top, right, bottom, left]
+    };
+    assert_eq!(inset("inset: 10px"), [L::length(10.0); 4]);
+    assert_eq!(inset("inset: 10px 20%"), [L::length(10.0), L::percent(0.2), L::length(10.0), L::percent(0.2)]);
+    assert_eq!(inset("inset: 10px auto 30px"), [L::length(10.0), L::auto(), L::length(30.0), L::auto()]);
+    assert_eq!(inset("inset: 1px 2px 3px 4px"), [L::length(1.0), L::length(2.0), L::length(3.0), L::length(4.0)]);
+}
+
+/// Counts the frames it is asked to draw.
+#[derive(Default)]
+struct CountingRenderer {
+    frames: usize,
+}
+
+impl TextMeasurer for CountingRenderer {
+    fn measure_text(&self, _text: &str, _font_size: f32, _weight: u16) -> (f32, f32) {
+        (10.0, 10.0)
+    }
+}
+
+impl Renderer for CountingRenderer {
+    fn render(&mut self, _commands: &[DrawCommand], _canvases: &std::collections::HashMap<String, Canvas>, _dirty_rect: Option<Rect>) {
+        self.frames += 1;
+    }
+}
+
+#[test]
+fn test_render_skips_frames_where_nothing_changed() {
+    let mut runtime = Runtime::new(MovedBoxModel { lit: false }, MockMeasurer);
+    let mut renderer = CountingRenderer::default();
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    // The first frame is drawn in full.
+    assert_eq!(runtime.render(&mut renderer), None);
+    assert_eq!(renderer.frames, 1);
+
+    // Nothing changed: an empty region, and the renderer isn't called.
+    let dirty = runtime.render(&mut renderer).expect("an empty region");
+    assert!(dirty.is_empty(), "{dirty:?}");
+    assert_eq!(renderer.frames, 1);
+
+    runtime.invalidate();
+    assert_eq!(runtime.render(&mut renderer), None);
+    assert_eq!(renderer.frames, 2);
+}
+
+fn page_ui(outer: &'static str, inner: &'static str) -> impl Fn(&mut UiBuilder) -> taffy::NodeId {
+    move |builder| {
+        let root = builder.create_element(outer, &[]);
+        let body = builder.create_element(inner, &[]);
+        let card = builder.create_element("div", &[("style", "background-color: red;")]);
+        builder.append_child(body, card);
+        builder.append_child(root, body);
+        root
+    }
+}
+
+#[test]
+fn test_document_background_only_comes_from_html_and_body() {
+    let runtime = Runtime::new(styled_fixture("body { background-color: blue; }", page_ui("html", "body")), MockMeasurer);
+    assert_eq!(runtime.ui.document_background(), Some(Color::from_rgba8(0, 0, 255, 255)));
+
+    // The first element inside `body` is content, not the page.
+    let runtime = Runtime::new(fixture(page_ui("html", "body")), MockMeasurer);
+    assert_eq!(runtime.ui.document_background(), None);
+    let runtime = Runtime::new(fixture(page_ui("div", "div")), MockMeasurer);
+    assert_eq!(runtime.ui.document_background(), None);
+}
+
+#[test]
+fn test_image_cache_drops_the_least_recently_used_image() {
+    use xerune::decode::{ImageCache, IMAGE_CACHE_LIMIT};
+    let mut cache = ImageCache::default();
+    for i in 0..IMAGE_CACHE_LIMIT {
+        cache.load(&format!("{i}.png"), || Some(i));
+    }
+    // Drawing the first image again makes the second the oldest.
+    cache.load("0.png", || panic!("0.png is cached"));
+    cache.load("new.png", || Some(IMAGE_CACHE_LIMIT));
+    assert_eq!(cache.get("0.png", None), Some(&0));
+    assert!(!cache.contains("1.png"));
+    assert_eq!(cache.get("new.png", None), Some(&IMAGE_CACHE_LIMIT));
+
+    // Tinted copies are made once per color and bounded the same way.
+    let mut tints = 0;
+    for _ in 0..2 {
+        cache.tint("0.png", [255, 0, 0, 255], |image| {
+            tints += 1;
+            image + 1000
+        });
+    }
+    assert_eq!(tints, 1);
+    assert_eq!(cache.get("0.png", Some([255, 0, 0, 255])), Some(&1000));
+    for alpha in 0..IMAGE_CACHE_LIMIT as u8 {
+        cache.tint("0.png", [0, 0, 0, alpha], |image| *image);
+    }
+    assert_eq!(cache.get("0.png", Some([255, 0, 0, 255])), None);
+    assert_eq!(cache.get("0.png", Some([0, 0, 0, 0])), Some(&0));
+
+    // Images that failed to load are remembered, and have no tinted copies.
+    cache.load("missing.png", || None);
+    cache.tint("missing.png", [255, 0, 0, 255], |_| panic!("nothing to tint"));
+    assert!(cache.contains("missing.png"));
+    assert_eq!(cache.get("missing.png", None), None);
+}
+
+const PSEUDO_ELEMENT_CSS: &str = r##"
+    .tag::before { content: "#"; color: #ff0000; }
+    .tag::after { content: '!'; }
+    .other::before { content: "*"; color: #0000ff; }
+"##;
+
+fn pseudo_element_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let list = builder.create_element("div", &[]);
+    for (class, label) in [("tag", "x"), ("other", "y"), ("plain", "z")] {
+        let item = builder.create_element("div", &[("class", class)]);
+        let text = builder.create_text(label, &[]);
+        builder.append_child(item, text);
+        builder.append_child(list, item);
+    }
+    list
+}
+
+fn drawn_text(ui: &Ui) -> Vec<(String, Color)> {
+    ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawText { text, color, .. } => Some((text, color)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn pseudo_element_text() -> Vec<(String, Color)> {
+    let black = Color::from_rgba8(0, 0, 0, 255);
+    vec![
+        ("#".to_string(), Color::from_rgba8(255, 0, 0, 255)),
+        ("x".to_string(), black),
+        ("!".to_string(), black),
+        ("*".to_string(), Color::from_rgba8(0, 0, 255, 255)),
+        ("y".to_string(), black),
+        ("z".to_string(), black),
+    ]
+}
+
+#[test]
+fn test_before_and_after_insert_their_content() {
+    let mut runtime = Runtime::new(styled_fixture(PSEUDO_ELEMENT_CSS, pseudo_element_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    assert_eq!(drawn_text(&runtime.ui), pseudo_element_text());
+}
+
+#[cfg(feature = "dynamic-parser")]
+#[test]
+fn test_before_and_after_insert_their_content_in_parsed_documents() {
+    let html = format!(
+        "<html><head><style>{PSEUDO_ELEMENT_CSS}</style></head><body>\
+         <div class=\"tag\">x</div><div class=\"other\">y</div><div class=\"plain\">z</div></body></html>"
+    );
+    let mut ui = Ui::from_html(&html, &MockMeasurer).unwrap();
+    ui.compute_layout(taffy::geometry::Size::MAX_CONTENT).unwrap();
+    assert_eq!(drawn_text(&ui), pseudo_element_text());
+}
+
+struct TextFieldModel {
+    value: String,
+}
+impl Model for TextFieldModel {
+    type Message = String;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if let Some(value) = msg.strip_prefix("input:name:") {
+            self.value = value.to_string();
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for TextFieldModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let root = builder.create_element("div", &[]);
+        let field = builder.create_input_text(&self.value, &[("id", "name"), ("data-on-input", "name"), ("style", "width: 100px; height: 20px;")]);
+        builder.append_child(root, field);
+        root
+    }
+}
+
+#[test]
+fn test_typing_into_an_input_reports_each_edit() {
+    let mut runtime = Runtime::new(TextFieldModel { value: String::new() }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+    assert!(runtime.focus("name"));
+
+    runtime.handle_event(InputEvent::TextInput { id: String::new(), text: "hi".to_string() });
+    runtime.handle_event(InputEvent::TextInput { id: "name".to_string(), text: "!\n".to_string() });
+    runtime.handle_event(InputEvent::KeyDown("Backspace".to_string()));
+    assert_eq!(sent.lock().unwrap().as_slice(), ["input:name:hi", "input:name:hi!", "input:name:hi"]);
+    let value = runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::TextInput(_, text, _) => text.clone(),
+        _ => None,
+    });
+    assert_eq!(value.as_deref(), Some("hi"), "The rebuilt input shows the model's value");
+
+    // Focus survives the rebuilds, and the caret follows the text.
+    let carets: Vec<String> = runtime.ui.build_commands(&std::collections::HashMap::new()).into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::DrawCaret { text, .. } => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(carets, ["hi"]);
+}
+
+fn hover_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column;")]);
+    let button = builder.create_element("div", &[
+        ("style", "height: 20px; background-color: #0000ff; color: #000000;"),
+        ("data-hover-style", "background-color: #ff0000; color: #ffff00;"),
+    ]);
+    let label = builder.create_text("Play", &[]);
+    builder.append_child(button, label);
+    let other = builder.create_element("div", &[("style", "height: 20px;")]);
+    builder.append_child(root, button);
+    builder.append_child(root, other);
+    root
+}
+
+#[test]
+fn test_hover_style_swaps_in_and_out_and_survives_rebuilds() {
+    let mut runtime = Runtime::new(fixture(hover_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    // The button's background and the color of its label.
+    let looks = |runtime: &Runtime<_, MockMeasurer>| -> (Option<Color>, Color) {
+        let button = runtime.ui.taffy.children(runtime.ui.root).unwrap()[0];
+        let label = runtime.ui.taffy.children(button).unwrap()[0];
+        let background = runtime.ui.render_data.get(button).and_then(|data| data.style().background_color);
+        (background, runtime.ui.render_data.get(label).unwrap().style().color)
+    };
+    let normal = (Some(Color::from_rgba8(0, 0, 255, 255)), Color::from_rgba8(0, 0, 0, 255));
+    let hovered = (Some(Color::from_rgba8(255, 0, 0, 255)), Color::from_rgba8(255, 255, 0, 255));
+    assert_eq!(looks(&runtime), normal);
+
+    assert!(runtime.handle_event(InputEvent::Hover { x: 10.0, y: 10.0 }));
+    assert_eq!(looks(&runtime), hovered);
+    assert!(runtime.handle_event(InputEvent::Hover { x: 10.0, y: 30.0 }));
+    assert_eq!(looks(&runtime), normal);
+
+    // A rebuild while hovered shows the hover style on the new tree.
+    runtime.handle_event(InputEvent::Hover { x: 10.0, y: 10.0 });
+    runtime.handle_event(InputEvent::Message("tick".to_string()));
+    assert_eq!(looks(&runtime), hovered);
+    runtime.handle_event(InputEvent::Hover { x: 10.0, y: 30.0 });
+    assert_eq!(looks(&runtime), normal);
+}
+
+struct SliderModel {
+    volume: f32,
+}
+impl Model for SliderModel {
+    type Message = String;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if let Some(volume) = msg.strip_prefix("slider:volume:").and_then(|value| value.parse().ok()) {
+            self.volume = volume;
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for SliderModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let root = builder.create_element("div", &[]);
+        let slider = builder.create_slider(self.volume, &[("id", "volume"), ("style", "width: 100px; height: 20px;")]);
+        builder.append_child(root, slider);
+        root
+    }
+}
+
+#[test]
+fn test_dragging_a_slider_reports_its_id_and_value() {
+    let mut runtime = Runtime::new(SliderModel { volume: 0.0 }, MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+
+    runtime.handle_event(InputEvent::PointerDown { x: 25.0, y: 10.0 });
+    // Each message rebuilds the slider; the drag goes on with the new node.
+    runtime.handle_event(InputEvent::Hover { x: 75.0, y: 10.0 });
+    runtime.handle_event(InputEvent::Hover { x: 150.0, y: 50.0 });
+    runtime.handle_event(InputEvent::PointerUp { x: 150.0, y: 50.0 });
+    runtime.handle_event(InputEvent::Hover { x: 50.0, y: 10.0 });
+    assert_eq!(sent.lock().unwrap().as_slice(), ["slider:volume:0.25", "slider:volume:0.75", "slider:volume:1"]);
+
+    let value = runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::Slider(value, _) => Some(*value),
+        _ => None,
+    });
+    assert_eq!(value, Some(1.0));
+}
+
+fn hidden_listeners_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[]);
+    let hidden = builder.create_element("div", &[("style", "display: none;")]);
+    let list = builder.create_element("div", &[
+        ("id", "list"),
+        ("data-key", "list"),
+        ("data-on-scroll", "list"),
+        ("data-on-drag", "seek"),
+        ("style", "overflow: scroll;"),
+    ]);
+    let field = builder.create_input_text("", &[("id", "name"), ("data-on-input", "name")]);
+    builder.append_child(list, field);
+    builder.append_child(hidden, list);
+    builder.append_child(root, hidden);
+    root
+}
+
+#[test]
+fn test_elements_under_display_none_register_nothing() {
+    let runtime = Runtime::new(fixture(hidden_listeners_ui), MockMeasurer);
+    let ui = &runtime.ui;
+    assert!(ui.ids.values().next().is_none());
+    assert!(ui.keys.values().next().is_none());
+    assert!(ui.scroll_listeners.values().next().is_none());
+    assert!(ui.drag_listeners.values().next().is_none());
+    assert!(ui.input_listeners.values().next().is_none());
+    assert_eq!(ui.node_by_key("list"), None);
+}
+
+fn repeated_interaction_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[("style", "display: flex; flex-direction: column; padding: 5px;")]);
+    let plain = builder.create_element("div", &[("style", "height: 10px; flex-shrink: 0;")]);
+    // Made before the first one in document order, so it has the lower `NodeId`.
+    let last = builder.create_element("div", &[("style", "width: 100px; height: 30px; flex-shrink: 0;"), ("data-on-click", "tick")]);
+    let first = builder.create_element("div", &[("style", "width: 100px; height: 20px; flex-shrink: 0;"), ("data-on-click", "tick")]);
+    let inner = builder.create_element("div", &[("style", "width: 50px; height: 5px;"), ("data-on-click", "toggle")]);
+    builder.append_child(first, inner);
+    builder.append_child(root, plain);
+    builder.append_child(root, first);
+    builder.append_child(root, last);
+    root
+}
+
+#[test]
+fn test_interactions_find_the_first_node_in_document_order() {
+    let mut runtime = Runtime::new(fixture(repeated_interaction_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let ui = &runtime.ui;
+    let children = ui.taffy.children(ui.root).unwrap();
+    assert_eq!(ui.node_for_interaction("tick"), Some(children[1]));
+    assert_eq!(ui.bounds_of_interaction("tick"), Some(Rect::new(5.0, 15.0, 100.0, 20.0)));
+    assert_eq!(ui.bounds_of_interaction("toggle"), Some(Rect::new(5.0, 15.0, 50.0, 5.0)));
+    assert_eq!(ui.node_for_interaction("missing"), None);
+    assert_eq!(ui.bounds_of_interaction("missing"), None);
+}
+
+struct TickerModel {
+    ticking: bool,
+}
+impl Model for TickerModel {
+    type Message = String;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        match msg.as_str() {
+            "start" => self.ticking = true,
+            "stop" => self.ticking = false,
+            _ => {}
+        }
+        Command::none()
+    }
+    fn subscriptions(&self) -> Vec<Subscription> {
+        if self.ticking {
+            vec![Subscription::Interval(std::time::Duration::from_millis(1), "tick".to_string())]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl TemplateLayout for TickerModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        builder.create_element("div", &[])
+    }
+}
+
+#[test]
+fn test_interval_subscriptions_follow_the_model() {
+    let mut runtime = Runtime::new(TickerModel { ticking: true }, MockMeasurer);
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = sent.clone();
+    runtime.on_interaction(move |msg| {
+        log.lock().unwrap().push(msg.clone());
+        true
+    });
+    let ticks = |runtime: &mut Runtime<TickerModel, MockMeasurer>| {
+        sent.lock().unwrap().clear();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        runtime.tick();
+        sent.lock().unwrap().iter().filter(|msg| *msg == "tick").count()
+    };
+    assert_eq!(ticks(&mut runtime), 1, "Listed by the first model, so running from the start");
+    assert_eq!(ticks(&mut runtime), 1, "Rebuilding after a tick keeps the one timer");
+
+    runtime.handle_messages(["stop".to_string()]);
+    assert_eq!(ticks(&mut runtime), 0);
+
+    runtime.handle_messages(["start".to_string()]);
+    runtime.handle_messages(["start".to_string()]);
+    assert_eq!(ticks(&mut runtime), 1, "Listing it again starts a single timer");
+}
+
+struct LoaderModel {
+    status: String,
+}
+impl Model for LoaderModel {
+    type Message = String;
+    fn update(&mut self, msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        if msg == "load" {
+            self.status = "loading".to_string();
+            return Command::perform(async { "done" }, |result| format!("loaded:{}", result));
+        }
+        if let Some(result) = msg.strip_prefix("loaded:") {
+            self.status = result.to_string();
+        }
+        Command::none()
+    }
+}
+
+impl TemplateLayout for LoaderModel {
+    fn stylesheet(&self) -> &'static str {
+        ""
+    }
+    fn build_ui(&self, builder: &mut UiBuilder) -> taffy::NodeId {
+        let root = builder.create_element("div", &[]);
+        let status = builder.create_text(&self.status, &[]);
+        builder.append_child(root, status);
+        root
+    }
+}
+
+#[test]
+fn test_command_results_reach_update_on_tick() {
+    let mut runtime = Runtime::new(LoaderModel { status: "idle".to_string() }, MockMeasurer);
+    let status = |runtime: &Runtime<LoaderModel, MockMeasurer>| runtime.ui.render_data.values().find_map(|data| match data {
+        RenderData::Text(text, _) => Some(text.clone()),
+        _ => None,
+    });
+    assert!(runtime.handle_messages(["load".to_string()]));
+    assert_eq!(status(&runtime).as_deref(), Some("loading"));
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while status(&runtime).as_deref() == Some("loading") && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        runtime.tick();
+    }
+    assert_eq!(status(&runtime).as_deref(), Some("done"));
+}
+
+fn labelled_ui(builder: &mut UiBuilder) -> taffy::NodeId {
+    let root = builder.create_element("div", &[]);
+    let nav = builder.create_element("nav", &[("aria-label", "Main")]);
+    let heading = builder.create_element("h1", &[]);
+    let title = builder.create_text("Inbox", &[]);
+    builder.append_child(heading, title);
+    // Purely presentational, so its children move up to the navigation.
+    let wrapper = builder.create_element("div", &[("style", "padding: 4px;")]);
+    let list = builder.create_element("div", &[("role", "tablist")]);
+    let item = builder.create_element("div", &[("role", "tab"), ("aria-label", "Unread")]);
+    builder.append_child(list, item);
+    let refresh = builder.create_element("div", &[("data-on-click", "refresh")]);
+    let close = builder.create_element("button", &[("aria-label", "Close"), ("data-on-click", "close")]);
+    builder.append_child(wrapper, list);
+    builder.append_child(wrapper, refresh);
+    builder.append_child(nav, heading);
+    builder.append_child(nav, wrapper);
+    builder.append_child(nav, close);
+    builder.append_child(root, nav);
+    root
+}
+
+fn access_outline(node: &AccessNode, depth: usize, out: &mut Vec<(usize, String, Option<String>)>) {
+    out.push((depth, node.role.clone(), node.label.clone()));
+    for child in &node.children {
+        access_outline(child, depth + 1, out);
+    }
+}
+
+#[test]
+fn test_accessibility_tree_reports_roles_and_labels() {
+    let mut runtime = Runtime::new(fixture(labelled_ui), MockMeasurer);
+    runtime.compute_layout(taffy::geometry::Size::MAX_CONTENT);
+    let tree = runtime.accessibility_tree();
+    let mut outline = Vec::new();
+    access_outline(&tree, 0, &mut outline);
+    let label = |text: &str| Some(text.to_string());
+    assert_eq!(outline, [
+        (0, "document".to_string(), None),
+        (1, "navigation".to_string(), label("Main")),
+        (2, "heading".to_string(), None),
+        (3, "text".to_string(), label("Inbox")),
+        (2, "tablist".to_string(), None),
+        (3, "tab".to_string(), label("Unread")),
+        (2, "button".to_string(), None),
+        (2, "button".to_string(), label("Close")),
+    ]);
+    let close = &tree.children[0].children[3];
+    assert_eq!(close.action.as_deref(), Some("close"));
+}
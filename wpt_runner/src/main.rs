@@ -12,7 +12,7 @@ use regex::Regex;
 use tiny_skia::Pixmap;
 
 use taffy::prelude::NodeId;
-use xerune::{Context, Model, Runtime, Ui, RenderData};
+use xerune::{Command, Context, Model, Runtime, Ui, RenderData};
 use skia_renderer::{TinySkiaMeasurer, TinySkiaRenderer};
 
 const WIDTH: u32 = 800;
@@ -34,7 +34,9 @@ impl Model for RawHtmlModel {
     fn view(&self) -> String {
         self.html.clone()
     }
-    fn update(&mut self, _msg: Self::Message, _context: &mut Context) {}
+    fn update(&mut self, _msg: Self::Message, _context: &mut Context) -> Command<Self::Message> {
+        Command::none()
+    }
 }
 
 impl xerune::ui::TemplateLayout for RawHtmlModel {
@@ -227,7 +229,7 @@ fn render_html_to_pixmap(html: &str, fonts: &'static [fontdue::Font]) -> Result<
     let mut pixmap = Pixmap::new(WIDTH, HEIGHT).ok_or_else(|| "Failed to create Pixmap".to_string())?;
     pixmap.fill(tiny_skia::Color::WHITE);
 
-    let measurer = TinySkiaMeasurer { fonts };
+    let measurer = TinySkiaMeasurer { fonts, families: &[] };
     let model = RawHtmlModel { html: html.to_string() };
     
     // Use a catch_unwind to handle any potential layout engine panics gracefully
@@ -235,7 +237,7 @@ fn render_html_to_pixmap(html: &str, fonts: &'static [fontdue::Font]) -> Result<
         let mut runtime = Runtime::new(model, measurer);
         runtime.set_size(WIDTH as f32, HEIGHT as f32);
         
-        let mut image_cache = HashMap::new();
+        let mut image_cache = xerune::decode::ImageCache::default();
         let mut gradient_cache = HashMap::new();
         let mut glyph_cache = HashMap::new();
         
@@ -489,7 +491,7 @@ fn check_all_nodes(node_id: NodeId, ui: &Ui, errors: &mut Vec<String>) {
 }
 
 fn run_attribute_test(html: &str, fonts: &'static [fontdue::Font]) -> Result<Vec<String>, String> {
-    let measurer = TinySkiaMeasurer { fonts };
+    let measurer = TinySkiaMeasurer { fonts, families: &[] };
     let model = RawHtmlModel { html: html.to_string() };
     
     let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {